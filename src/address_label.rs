@@ -0,0 +1,172 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{helper, locale::Locale};
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use starcoin_types::account_address::AccountAddress;
+use std::{collections::HashMap, str::FromStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressCategory {
+    Exchange,
+    Bridge,
+    Team,
+    Foundation,
+    Blacklisted,
+}
+
+impl AddressCategory {
+    pub const NAMES: &'static str = "exchange, bridge, team, foundation, blacklisted";
+
+    pub fn display_name(&self, locale: Locale) -> &'static str {
+        (locale.messages().address_category)(*self)
+    }
+}
+
+impl FromStr for AddressCategory {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s.to_lowercase().as_str() {
+            "exchange" => AddressCategory::Exchange,
+            "bridge" => AddressCategory::Bridge,
+            "team" => AddressCategory::Team,
+            "foundation" => AddressCategory::Foundation,
+            "blacklisted" => AddressCategory::Blacklisted,
+            _ => bail!("Unknown address category: {}", s),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AddressLabel {
+    pub category: AddressCategory,
+    pub name: String,
+}
+
+/// An address with its label, if any, described in the locale of each chat
+/// it is sent to
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabeledAddress {
+    pub address: String,
+    pub label: Option<AddressLabel>,
+}
+
+impl LabeledAddress {
+    /// The address followed by its label, if any
+    pub fn describe(&self, locale: Locale) -> String {
+        match &self.label {
+            Some(label) => format!(
+                "{} [{}: {}]",
+                self.address,
+                label.category.display_name(locale),
+                label.name
+            ),
+            None => self.address.clone(),
+        }
+    }
+}
+
+impl From<&str> for LabeledAddress {
+    fn from(address: &str) -> Self {
+        Self {
+            address: address.to_string(),
+            label: None,
+        }
+    }
+}
+
+/// Labels of known addresses, kept in `DATA_DIR` and edited with `/label`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AddressLabels {
+    /// Full length address -> label
+    labels: HashMap<String, AddressLabel>,
+}
+
+fn normalize_address(address: &str) -> Result<String> {
+    AccountAddress::from_hex_literal(address)
+        .map(helper::format_address)
+        .map_err(|e| anyhow!("Invalid address {}: {:?}", address, e))
+}
+
+impl AddressLabels {
+    pub const FILE_NAME: &'static str = "address_labels.json";
+
+    pub fn get(&self, address: &str) -> Option<&AddressLabel> {
+        self.labels.get(&normalize_address(address).ok()?)
+    }
+
+    /// Add or replace the label of an address
+    pub fn set(&mut self, address: &str, label: AddressLabel) -> Result<()> {
+        self.labels.insert(normalize_address(address)?, label);
+        Ok(())
+    }
+
+    pub fn labeled(&self, address: &str) -> LabeledAddress {
+        LabeledAddress {
+            address: address.to_string(),
+            label: self.get(address).cloned(),
+        }
+    }
+
+    /// The address followed by its label, if any
+    pub fn describe(&self, address: &str, locale: Locale) -> String {
+        self.labeled(address).describe(locale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_address_labels() -> Result<()> {
+        let mut labels = AddressLabels::default();
+        labels.set(
+            "0x4a50777e0e4f67625400148b04afd572",
+            AddressLabel {
+                category: "Exchange".parse()?,
+                name: "Binance".to_string(),
+            },
+        )?;
+        assert!(labels
+            .set(
+                "not an address",
+                AddressLabel {
+                    category: AddressCategory::Team,
+                    name: String::new(),
+                }
+            )
+            .is_err());
+        assert!("casino".parse::<AddressCategory>().is_err());
+
+        assert_eq!(
+            labels.describe("0x4a50777e0e4f67625400148b04afd572", Locale::ZhCn),
+            "0x4a50777e0e4f67625400148b04afd572 [交易所: Binance]"
+        );
+        assert_eq!(
+            labels.describe("0x4a50777e0e4f67625400148b04afd572", Locale::EnUs),
+            "0x4a50777e0e4f67625400148b04afd572 [Exchange: Binance]"
+        );
+        assert_eq!(
+            labels.describe("0xa77e09f66ea8ed586467e36ce89362b9", Locale::EnUs),
+            "0xa77e09f66ea8ed586467e36ce89362b9"
+        );
+        // Labels are found whatever the form of the address
+        assert_eq!(
+            labels
+                .get("0x4A50777E0E4F67625400148B04AFD572")
+                .map(|l| l.name.as_str()),
+            Some("Binance")
+        );
+
+        let restored: AddressLabels = serde_json::from_str(&serde_json::to_string(&labels)?)?;
+        assert_eq!(
+            restored.get("0x4a50777e0e4f67625400148b04afd572"),
+            labels.get("0x4a50777e0e4f67625400148b04afd572")
+        );
+        Ok(())
+    }
+}
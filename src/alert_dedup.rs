@@ -0,0 +1,68 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The transactions whose transfers were alerted recently, kept in `DATA_DIR`
+/// so the blocks replayed after a restart or a reconnection do not alert them
+/// again, however they are grouped into digests
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AlertedTxns {
+    /// Transaction hash -> time it was alerted, in seconds
+    txns: HashMap<String, u64>,
+}
+
+impl AlertedTxns {
+    pub const FILE_NAME: &'static str = "alerted_txns.json";
+
+    /// Whether the transaction was alerted less than `window` seconds before
+    /// `now`
+    pub fn contains(&self, txn_hash: &str, now: u64, window: u64) -> bool {
+        self.txns
+            .get(txn_hash)
+            .is_some_and(|alerted_at| now.saturating_sub(*alerted_at) < window)
+    }
+
+    /// Record the transactions as alerted at `now`. The ones alerted more than
+    /// `window` seconds before are forgotten.
+    pub fn record<'a>(
+        &mut self,
+        txn_hashes: impl IntoIterator<Item = &'a str>,
+        now: u64,
+        window: u64,
+    ) {
+        self.txns
+            .retain(|_, alerted_at| now.saturating_sub(*alerted_at) < window);
+        self.txns.extend(
+            txn_hashes
+                .into_iter()
+                .map(|txn_hash| (txn_hash.to_string(), now)),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_record() -> Result<()> {
+        let mut alerted = AlertedTxns::default();
+        assert!(!alerted.contains("0x6a1c", 1000, 3600));
+        alerted.record(["0x6a1c", "0x7b2d"], 1000, 3600);
+        assert!(alerted.contains("0x6a1c", 2000, 3600));
+        assert!(alerted.contains("0x7b2d", 2000, 3600));
+        alerted.record(["0x8c3e"], 2000, 3600);
+
+        // Restored after a restart
+        let mut alerted: AlertedTxns = serde_json::from_str(&serde_json::to_string(&alerted)?)?;
+        assert!(alerted.contains("0x6a1c", 4599, 3600));
+        assert!(!alerted.contains("0x6a1c", 4600, 3600));
+        alerted.record(None, 4600, 3600);
+        assert_eq!(alerted.txns.len(), 1);
+        assert!(alerted.contains("0x8c3e", 4600, 3600));
+        Ok(())
+    }
+}
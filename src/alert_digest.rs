@@ -0,0 +1,72 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+/// Groups the items raised in quick succession, to notify them in a single
+/// digest: the first ones are released at once, the next ones held until the
+/// window that started with them is over
+#[derive(Debug, Clone)]
+pub struct DigestBuffer<T> {
+    window_secs: u64,
+    /// Time the latest items were released, in seconds, none if nothing was
+    /// released within the window since
+    window_start: Option<u64>,
+    pending: Vec<T>,
+}
+
+impl<T> DigestBuffer<T> {
+    pub fn new(window_secs: u64) -> Self {
+        Self {
+            window_secs,
+            window_start: None,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Add the items raised at `now`, returning the ones due for a digest.
+    /// Called for each block even without items, so the held items are
+    /// released once the window is over.
+    pub fn push(&mut self, items: impl IntoIterator<Item = T>, now: u64) -> Vec<T> {
+        self.pending.extend(items);
+        if matches!(self.window_start, Some(start) if now < start + self.window_secs) {
+            return Vec::new();
+        }
+        if self.pending.is_empty() {
+            self.window_start = None;
+            return Vec::new();
+        }
+        self.window_start = Some(now);
+        std::mem::take(&mut self.pending)
+    }
+
+    /// Whether an item held for the next digest matches
+    pub fn holds(&self, f: impl Fn(&T) -> bool) -> bool {
+        self.pending.iter().any(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push() {
+        let mut buffer = DigestBuffer::new(60);
+        // A quiet buffer releases at once
+        assert_eq!(buffer.push([1, 2], 1000), vec![1, 2]);
+        assert!(buffer.push([3], 1010).is_empty());
+        assert!(buffer.holds(|item| *item == 3));
+        assert!(!buffer.holds(|item| *item == 1));
+        assert!(buffer.push(None, 1030).is_empty());
+        assert!(buffer.push([4], 1059).is_empty());
+        assert_eq!(buffer.push(None, 1060), vec![3, 4]);
+        // Nothing held once the window is over, the next items released at once
+        assert!(buffer.push(None, 1120).is_empty());
+        assert_eq!(buffer.push([5], 1121), vec![5]);
+
+        // Without a window, each block is a digest of its own
+        let mut buffer = DigestBuffer::new(0);
+        assert_eq!(buffer.push([1, 2], 1000), vec![1, 2]);
+        assert_eq!(buffer.push([3], 1000), vec![3]);
+        assert!(buffer.push(None, 1001).is_empty());
+    }
+}
@@ -0,0 +1,324 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    alert_sender::{Alert, AlertCategory, Severity},
+    helper,
+    locale::Locale,
+};
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use starcoin_types::account_address::AccountAddress;
+use std::str::FromStr;
+
+/// What silences the alerts, set from the alert buttons or with `/snooze`
+/// and `/mute`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MuteRule {
+    /// The alerts with a key, until a time
+    Snooze { key: String, until: DateTime<Utc> },
+    /// The alerts with a key while they keep firing, until they have been
+    /// quiet for `ALERT_ACK_RESET_SECS`
+    Ack {
+        key: String,
+        last_fired: DateTime<Utc>,
+    },
+    /// All the alerts about an address, until a time or `/unmute`
+    Address {
+        address: String,
+        until: Option<DateTime<Utc>>,
+    },
+}
+
+impl MuteRule {
+    /// The alert key or the address
+    pub fn target(&self) -> &str {
+        match self {
+            MuteRule::Snooze { key, .. } | MuteRule::Ack { key, .. } => key,
+            MuteRule::Address { address, .. } => address,
+        }
+    }
+
+    fn expired(&self, now: DateTime<Utc>, ack_reset: Duration) -> bool {
+        match self {
+            MuteRule::Snooze { until, .. } => *until <= now,
+            MuteRule::Ack { last_fired, .. } => *last_fired + ack_reset <= now,
+            MuteRule::Address { until, .. } => until.is_some_and(|until| until <= now),
+        }
+    }
+
+    fn matches(&self, alert: &Alert) -> bool {
+        match self {
+            MuteRule::Snooze { key, .. } | MuteRule::Ack { key, .. } => alert.key() == *key,
+            MuteRule::Address { address, .. } => alert
+                .addresses
+                .iter()
+                .any(|a| parse_address(a).is_ok_and(|a| a == *address)),
+        }
+    }
+
+    /// What the rule silences and until when, worded in a locale
+    pub fn describe(&self, locale: Locale) -> String {
+        (locale.messages().mute_rule)(self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertMute {
+    pub rule: MuteRule,
+    /// Who set it
+    pub by: String,
+}
+
+/// Telegram limit on the callback data of a button
+const MAX_CALLBACK_DATA_LEN: usize = 64;
+
+/// The action of an alert button, sent back as its callback data of at most
+/// 64 bytes
+#[derive(Debug, Clone, PartialEq)]
+pub enum MuteAction {
+    Ack { key: String },
+    Snooze { key: String },
+    MuteAddress { address: String },
+}
+
+impl MuteAction {
+    /// The buttons of an alert, muting the first address it is about. The
+    /// keys too long for a button, such as those with a node URL, are left to
+    /// `/snooze`.
+    pub fn for_alert(alert: &Alert) -> Vec<MuteAction> {
+        let mut actions = vec![
+            MuteAction::Ack { key: alert.key() },
+            MuteAction::Snooze { key: alert.key() },
+        ];
+        if let Some(address) = alert.addresses.first() {
+            actions.push(MuteAction::MuteAddress {
+                address: address.clone(),
+            });
+        }
+        actions.retain(|action| action.to_string().len() <= MAX_CALLBACK_DATA_LEN);
+        actions
+    }
+
+    pub fn label(&self, locale: Locale) -> &'static str {
+        let m = locale.messages();
+        match self {
+            MuteAction::Ack { .. } => m.ack_button,
+            MuteAction::Snooze { .. } => m.snooze_button,
+            MuteAction::MuteAddress { .. } => m.mute_address_button,
+        }
+    }
+}
+
+impl std::fmt::Display for MuteAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MuteAction::Ack { key } => write!(f, "ack:{}", key),
+            MuteAction::Snooze { key } => write!(f, "snooze:{}", key),
+            MuteAction::MuteAddress { address } => write!(f, "mute:{}", address),
+        }
+    }
+}
+
+impl FromStr for MuteAction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (action, target) = s
+            .split_once(':')
+            .ok_or_else(|| anyhow!("Invalid alert action: {}", s))?;
+        let target = target.to_string();
+        Ok(match action {
+            "ack" => MuteAction::Ack { key: target },
+            "snooze" => MuteAction::Snooze { key: target },
+            "mute" => MuteAction::MuteAddress { address: target },
+            _ => bail!("Invalid alert action: {}", s),
+        })
+    }
+}
+
+/// Parse a duration such as `30m`, `2h` or `1d`
+pub fn parse_duration(duration: &str) -> Result<Duration> {
+    let invalid = || anyhow!("Invalid duration: {}, e.g. 30m, 2h or 1d", duration);
+    let unit = duration.chars().last().ok_or_else(invalid)?;
+    let value: i64 = duration[..duration.len() - unit.len_utf8()]
+        .parse()
+        .map_err(|_| invalid())?;
+    match unit {
+        'm' if value > 0 => Ok(Duration::minutes(value)),
+        'h' if value > 0 => Ok(Duration::hours(value)),
+        'd' if value > 0 => Ok(Duration::days(value)),
+        _ => Err(invalid()),
+    }
+}
+
+/// An address as written in the alerts
+pub fn parse_address(address: &str) -> Result<String> {
+    AccountAddress::from_hex_literal(address)
+        .map(helper::format_address)
+        .map_err(|_| anyhow!("Invalid address: {}", address))
+}
+
+/// An alert key, `<category>:<severity>` followed by what raised the alert
+/// or the address it is about, if any
+pub fn parse_alert_key(key: &str) -> Result<String> {
+    let mut parts = key.splitn(3, ':');
+    let category: AlertCategory = parts.next().unwrap_or_default().parse()?;
+    let severity: Severity = parts
+        .next()
+        .ok_or_else(|| anyhow!("Missing the severity of alert key {}", key))?
+        .parse()?;
+    Ok(match parts.next() {
+        Some(address) if address.starts_with("0x") => {
+            format!("{}:{}:{}", category, severity, parse_address(address)?)
+        }
+        Some(kind) => format!("{}:{}:{}", category, severity, kind),
+        None => format!("{}:{}", category, severity),
+    })
+}
+
+/// The alerts silenced from Telegram, kept in `DATA_DIR` and checked before
+/// any alert is sent
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AlertMutes {
+    mutes: Vec<AlertMute>,
+}
+
+impl AlertMutes {
+    pub const FILE_NAME: &'static str = "alert_mutes.json";
+
+    /// Add a rule, replacing the one on the same target
+    pub fn mute(&mut self, rule: MuteRule, by: &str) {
+        self.mutes
+            .retain(|mute| mute.rule.target() != rule.target());
+        self.mutes.push(AlertMute {
+            rule,
+            by: by.to_string(),
+        });
+    }
+
+    /// Remove the rules on a target, or all of them if `None`, returning how
+    /// many were removed
+    pub fn unmute(&mut self, target: Option<&str>) -> usize {
+        let before = self.mutes.len();
+        self.mutes
+            .retain(|mute| target.is_some_and(|target| mute.rule.target() != target));
+        before - self.mutes.len()
+    }
+
+    pub fn mutes(&self) -> &[AlertMute] {
+        &self.mutes
+    }
+
+    /// Whether an alert firing at `now` is silenced, dropping the expired
+    /// rules and keeping the acknowledged alerts silent while they fire
+    pub fn check(&mut self, alert: &Alert, now: DateTime<Utc>, ack_reset: Duration) -> bool {
+        self.mutes.retain(|mute| !mute.rule.expired(now, ack_reset));
+        let mut muted = false;
+        for mute in self
+            .mutes
+            .iter_mut()
+            .filter(|mute| mute.rule.matches(alert))
+        {
+            if let MuteRule::Ack { last_fired, .. } = &mut mute.rule {
+                *last_fired = now;
+            }
+            muted = true;
+        }
+        muted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alert_sender::Severity;
+
+    #[test]
+    fn test_alert_mutes() -> Result<()> {
+        let now = Utc::now();
+        let ack_reset = Duration::hours(1);
+        let lagging = Alert::new(AlertCategory::IndexLag, Severity::Warning, "lagging");
+        let balance = Alert::new(AlertCategory::Balance, Severity::Warning, "balance")
+            .with_address("0x4a50777e0e4f67625400148b04afd572");
+        let mut mutes = AlertMutes::default();
+        assert!(!mutes.check(&lagging, now, ack_reset));
+
+        // An acknowledged alert stays silent while it keeps firing
+        mutes.mute(
+            MuteRule::Ack {
+                key: lagging.key(),
+                last_fired: now,
+            },
+            "alice",
+        );
+        assert!(mutes.check(&lagging, now + Duration::minutes(50), ack_reset));
+        assert!(mutes.check(&lagging, now + Duration::minutes(100), ack_reset));
+        assert!(!mutes.check(&balance, now, ack_reset));
+        // But not the same alert turning more severe
+        let lagging_more = Alert::new(AlertCategory::IndexLag, Severity::Critical, "lagging");
+        assert!(!mutes.check(&lagging_more, now + Duration::minutes(100), ack_reset));
+        // And fires again once it has been quiet
+        assert!(!mutes.check(&lagging, now + Duration::minutes(170), ack_reset));
+        assert!(mutes.mutes().is_empty());
+
+        mutes.mute(
+            MuteRule::Snooze {
+                key: balance.key(),
+                until: now + Duration::hours(1),
+            },
+            "alice",
+        );
+        mutes.mute(
+            MuteRule::Address {
+                address: parse_address("0x4a50777e0e4f67625400148b04afd572")?,
+                until: None,
+            },
+            "bob",
+        );
+        assert!(mutes.check(&balance, now + Duration::hours(2), ack_reset));
+        assert_eq!(mutes.mutes().len(), 1);
+        assert_eq!(mutes.unmute(Some("0x4a50777e0e4f67625400148b04afd572")), 1);
+        assert!(!mutes.check(&balance, now, ack_reset));
+        Ok(())
+    }
+
+    #[test]
+    fn test_mute_action() -> Result<()> {
+        let balance = Alert::new(AlertCategory::Balance, Severity::Warning, "balance")
+            .with_address("0x4a50777e0e4f67625400148b04afd572");
+        let actions = MuteAction::for_alert(&balance);
+        assert_eq!(actions.len(), 3);
+        for action in actions {
+            // Telegram limits the callback data to 64 bytes
+            assert!(action.to_string().len() <= 64);
+            assert_eq!(action.to_string().parse::<MuteAction>()?, action);
+        }
+        assert!("delete:index_lag".parse::<MuteAction>().is_err());
+        // A key too long for the callback data leaves only the /snooze command
+        let stale = Alert::new(AlertCategory::NodeHealth, Severity::Warning, "stale")
+            .with_kind("stale:https://main-seed.starcoin.org/rpc/v1");
+        assert!(MuteAction::for_alert(&stale).is_empty());
+
+        assert_eq!(parse_duration("30m")?, Duration::minutes(30));
+        assert_eq!(parse_duration("1d")?, Duration::days(1));
+        assert!(parse_duration("0h").is_err());
+        assert!(parse_duration("2w").is_err());
+        assert!(parse_duration("").is_err());
+
+        assert_eq!(
+            parse_alert_key("balance:warning:0x4a50777e0e4f67625400148b04afd572")?,
+            balance.key()
+        );
+        assert_eq!(parse_alert_key("index_lag:Warning")?, "index_lag:warning");
+        assert_eq!(
+            parse_alert_key("gas:warning:high_gas_price")?,
+            "gas:warning:high_gas_price"
+        );
+        assert!(parse_alert_key("index_lag").is_err());
+        assert!(parse_alert_key("index_lag:warning:0xzz").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    alert_sender::{Alert, AlertCategory, Severity},
+    config::Config,
+    locale::Locale,
+    rich_text::RichText,
+};
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// A chat, or a topic thread of a forum chat
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlertDestination {
+    pub chat_id: i64,
+    pub thread_id: Option<i32>,
+}
+
+impl FromStr for AlertDestination {
+    type Err = anyhow::Error;
+
+    /// `<chat_id>` or `<chat_id>:<thread_id>`
+    fn from_str(s: &str) -> Result<Self> {
+        let (chat_id, thread_id) = match s.trim().split_once(':') {
+            Some((chat_id, thread_id)) => (chat_id, Some(thread_id.trim().parse()?)),
+            None => (s.trim(), None),
+        };
+        Ok(Self {
+            chat_id: chat_id.trim().parse()?,
+            thread_id,
+        })
+    }
+}
+
+/// Sends the alerts of a category, at least as severe as `min_severity`, to a
+/// destination
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRoute {
+    pub category: AlertCategory,
+    pub min_severity: Severity,
+    pub destination: AlertDestination,
+}
+
+/// Parse `ALERT_ROUTES`, e.g.
+/// `large_transfer=-1001234:12,node_health@critical=-1005678`
+pub fn parse_alert_routes(routes: &str) -> Result<Vec<AlertRoute>> {
+    routes
+        .split(',')
+        .map(|route| route.trim())
+        .filter(|route| !route.is_empty())
+        .map(|route| {
+            let (rule, destination) = route
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Invalid alert route: {}", route))?;
+            let (category, min_severity) = match rule.split_once('@') {
+                Some((category, severity)) => (category, severity.parse()?),
+                None => (rule, Severity::Info),
+            };
+            Ok(AlertRoute {
+                category: category.parse()?,
+                min_severity,
+                destination: destination.parse()?,
+            })
+        })
+        .collect()
+}
+
+/// Where each alert goes, and who is mentioned in it
+#[derive(Debug, Clone)]
+pub struct AlertRouter {
+    routes: Vec<AlertRoute>,
+    default_destination: AlertDestination,
+    oncall_users: Vec<String>,
+    mention_severity: Severity,
+}
+
+impl AlertRouter {
+    pub fn new(
+        routes: Vec<AlertRoute>,
+        default_destination: AlertDestination,
+        oncall_users: Vec<String>,
+        mention_severity: Severity,
+    ) -> Self {
+        Self {
+            routes,
+            default_destination,
+            oncall_users,
+            mention_severity,
+        }
+    }
+
+    pub fn from_config(config: &Config) -> Result<Self> {
+        Ok(Self::new(
+            config.alert_routes.clone(),
+            config.telegram_chat_id.parse()?,
+            config.alert_oncall_users.clone(),
+            config.alert_mention_severity,
+        ))
+    }
+
+    /// The destinations of the routes matching the alert, or the alert chat
+    /// if none does
+    pub fn destinations(&self, alert: &Alert) -> Vec<AlertDestination> {
+        let mut destinations: Vec<AlertDestination> = Vec::new();
+        for route in &self.routes {
+            if route.category == alert.category
+                && alert.severity >= route.min_severity
+                && !destinations.contains(&route.destination)
+            {
+                destinations.push(route.destination);
+            }
+        }
+        if destinations.is_empty() {
+            destinations.push(self.default_destination);
+        }
+        destinations
+    }
+
+    /// The message of the alert in a locale, mentioning the on-call users if
+    /// it is severe enough
+    pub fn render(&self, alert: &Alert, locale: Locale) -> RichText {
+        let message = alert.message.get(locale).clone();
+        if alert.severity < self.mention_severity || self.oncall_users.is_empty() {
+            return message;
+        }
+        message.text(format!("\n{}", self.oncall_users.join(" ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alert_router() -> Result<()> {
+        let routes = parse_alert_routes(
+            "large_transfer=-100:12, node_health@critical=-200, large_transfer=-300",
+        )?;
+        assert!(parse_alert_routes("large_transfer").is_err());
+        assert!(parse_alert_routes("mempool=-100").is_err());
+        assert!(parse_alert_routes("gas@urgent=-100").is_err());
+
+        let router = AlertRouter::new(
+            routes,
+            "-1".parse()?,
+            vec!["@alice".to_string(), "@bob".to_string()],
+            Severity::Critical,
+        );
+        let transfer = Alert::new(AlertCategory::LargeTransfer, Severity::Warning, "transfer");
+        assert_eq!(
+            router.destinations(&transfer),
+            vec![
+                AlertDestination {
+                    chat_id: -100,
+                    thread_id: Some(12)
+                },
+                AlertDestination {
+                    chat_id: -300,
+                    thread_id: None
+                }
+            ]
+        );
+        // Below the severity of its route, a node alert stays in the alert chat
+        let lagging = Alert::new(AlertCategory::NodeHealth, Severity::Warning, "lagging");
+        assert_eq!(router.destinations(&lagging), vec!["-1".parse()?]);
+        let forked = Alert::new(AlertCategory::NodeHealth, Severity::Critical, "forked");
+        assert_eq!(router.destinations(&forked), vec!["-200".parse()?]);
+
+        assert_eq!(
+            router.render(&transfer, Locale::EnUs).to_string(),
+            "transfer"
+        );
+        assert_eq!(
+            router.render(&forked, Locale::ZhCn).to_string(),
+            "forked\n@alice @bob"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,199 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::locale::LocalizedText;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Kinds of alerts, each of them routable to its own chats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertCategory {
+    /// Large transfers, confirmed or still in the txpool
+    LargeTransfer,
+    /// Cumulative transfers of an address
+    Velocity,
+    Balance,
+    /// Governance, package upgrades and token supply
+    Governance,
+    /// Block production anomalies
+    BlockProduction,
+    Gas,
+    /// StcScan index falling behind the chain
+    IndexLag,
+    NodeHealth,
+    DailySummary,
+}
+
+impl AlertCategory {
+    pub const NAMES: &'static str = "large_transfer, velocity, balance, governance, block_production, gas, index_lag, node_health, daily_summary";
+}
+
+impl std::fmt::Display for AlertCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            AlertCategory::LargeTransfer => "large_transfer",
+            AlertCategory::Velocity => "velocity",
+            AlertCategory::Balance => "balance",
+            AlertCategory::Governance => "governance",
+            AlertCategory::BlockProduction => "block_production",
+            AlertCategory::Gas => "gas",
+            AlertCategory::IndexLag => "index_lag",
+            AlertCategory::NodeHealth => "node_health",
+            AlertCategory::DailySummary => "daily_summary",
+        })
+    }
+}
+
+impl FromStr for AlertCategory {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s.trim().to_lowercase().as_str() {
+            "large_transfer" => AlertCategory::LargeTransfer,
+            "velocity" => AlertCategory::Velocity,
+            "balance" => AlertCategory::Balance,
+            "governance" => AlertCategory::Governance,
+            "block_production" => AlertCategory::BlockProduction,
+            "gas" => AlertCategory::Gas,
+            "index_lag" => AlertCategory::IndexLag,
+            "node_health" => AlertCategory::NodeHealth,
+            "daily_summary" => AlertCategory::DailySummary,
+            _ => bail!("Unknown alert category: {}", s),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        })
+    }
+}
+
+impl FromStr for Severity {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s.trim().to_lowercase().as_str() {
+            "info" => Severity::Info,
+            "warning" => Severity::Warning,
+            "critical" => Severity::Critical,
+            _ => bail!("Unknown severity: {}", s),
+        })
+    }
+}
+
+/// A transfer an alert is about, for the subscriptions to addresses and
+/// tokens
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertTransfer {
+    pub sender: String,
+    pub receiver: String,
+    pub token: String,
+    pub amount: u128,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    pub category: AlertCategory,
+    pub severity: Severity,
+    /// What raised it within its category, such as an anomaly, an event type
+    /// or a transaction
+    pub kind: Option<String>,
+    /// Worded in each locale, the chats it is sent to reading their own
+    pub message: LocalizedText,
+    /// Addresses the alert is about
+    pub addresses: Vec<String>,
+    /// Transfers the alert is about, several for a digest
+    pub transfers: Vec<AlertTransfer>,
+    /// Below the alert thresholds, only sent to the chats that subscribed to
+    /// it with a lower threshold
+    pub subscribers_only: bool,
+}
+
+impl Alert {
+    pub fn new(
+        category: AlertCategory,
+        severity: Severity,
+        message: impl Into<LocalizedText>,
+    ) -> Self {
+        Self {
+            category,
+            severity,
+            kind: None,
+            message: message.into(),
+            addresses: Vec::new(),
+            transfers: Vec::new(),
+            subscribers_only: false,
+        }
+    }
+
+    pub fn with_kind(mut self, kind: impl Into<String>) -> Self {
+        self.kind = Some(kind.into());
+        self
+    }
+
+    /// An alert about a single transaction, its hash shortened to 16 digits
+    /// for the key to fit in the alert buttons
+    pub fn with_txn_hash(self, txn_hash: &str) -> Self {
+        let short_hash: String = txn_hash.trim_start_matches("0x").chars().take(16).collect();
+        self.with_kind(short_hash)
+    }
+
+    pub fn with_address(mut self, address: impl Into<String>) -> Self {
+        self.addresses.push(address.into());
+        self
+    }
+
+    pub fn with_transfer(mut self, transfer: AlertTransfer) -> Self {
+        self.addresses.push(transfer.sender.clone());
+        self.addresses.push(transfer.receiver.clone());
+        self.transfers.push(transfer);
+        self
+    }
+
+    pub fn subscribers_only(mut self, subscribers_only: bool) -> Self {
+        self.subscribers_only = subscribers_only;
+        self
+    }
+
+    /// What identifies the repeats of an alert, to acknowledge or snooze
+    /// them: the category, the severity, so that a more severe alert is never
+    /// silenced with it, and what raised it or else the first address it is
+    /// about
+    pub fn key(&self) -> String {
+        match self.kind.as_ref().or(self.addresses.first()) {
+            Some(discriminator) => format!("{}:{}:{}", self.category, self.severity, discriminator),
+            None => format!("{}:{}", self.category, self.severity),
+        }
+    }
+}
+
+/// Destination of the alert messages emitted by the monitor handlers
+#[async_trait::async_trait]
+pub trait AlertSender: Send + Sync {
+    async fn send_message(&self, message: &str) -> Result<()>;
+
+    /// Send an alert to where its category is routed, by default the single
+    /// destination of `send_message`, which has no subscribers and reads the
+    /// default locale
+    async fn send_alert(&self, alert: &Alert) -> Result<()> {
+        if alert.subscribers_only {
+            return Ok(());
+        }
+        self.send_message(&alert.message.to_string()).await
+    }
+}
@@ -0,0 +1,44 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::HashMap;
+use tracing::debug;
+
+/// Limits how often alerts with the same key are notified
+#[derive(Debug, Clone, Default)]
+pub struct AlertThrottle {
+    /// Alert key -> time of the latest notification, in seconds
+    latest_notify_time: HashMap<String, u64>,
+}
+
+impl AlertThrottle {
+    /// Whether the alert `key` may be notified at `now`, recording the
+    /// notification if so
+    pub fn should_notify(&mut self, key: &str, now: u64, max_interval: u64) -> bool {
+        match self.latest_notify_time.get(key) {
+            Some(latest) if now.saturating_sub(*latest) <= max_interval => {
+                debug!("Alert {} already notified at {}", key, latest);
+                false
+            }
+            _ => {
+                self.latest_notify_time.insert(key.to_string(), now);
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_notify() {
+        let mut throttle = AlertThrottle::default();
+        assert!(throttle.should_notify("a", 1000, 600));
+        assert!(!throttle.should_notify("a", 1600, 600));
+        assert!(throttle.should_notify("b", 1600, 600));
+        assert!(throttle.should_notify("a", 1601, 600));
+        assert!(!throttle.should_notify("a", 1700, 600));
+    }
+}
@@ -0,0 +1,186 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::types::AccountBalance;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Configuration for balance change detection
+#[derive(Debug, Clone)]
+pub struct BalanceCheckConfig {
+    /// Percentage a balance may change between two checks before alerting
+    pub max_change_percent: f64,
+    /// Amount a balance may change between two checks before alerting, in nano units
+    pub max_change_amount: Option<u64>,
+    /// How long balance records are kept
+    pub history_retention: Duration,
+}
+
+impl Default for BalanceCheckConfig {
+    fn default() -> Self {
+        Self {
+            max_change_percent: 10.0,
+            max_change_amount: None,
+            history_retention: Duration::days(2),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BalanceChangeAlert {
+    pub address: String,
+    pub token: String,
+    pub previous: u128,
+    pub current: u128,
+}
+
+impl BalanceChangeAlert {
+    pub fn change(&self) -> i128 {
+        self.current as i128 - self.previous as i128
+    }
+
+    /// Change relative to the previous balance, `None` if it was zero
+    pub fn change_percent(&self) -> Option<f64> {
+        (self.previous > 0).then(|| self.change() as f64 / self.previous as f64 * 100.0)
+    }
+}
+
+/// Balance records of the watched addresses, persisted between restarts
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BalanceHistory {
+    /// Address -> balances, oldest first
+    records: HashMap<String, Vec<AccountBalance>>,
+}
+
+impl BalanceHistory {
+    pub const FILE_NAME: &'static str = "balance_history.json";
+
+    pub fn latest(&self, address: &str) -> Option<&AccountBalance> {
+        self.records.get(address).and_then(|records| records.last())
+    }
+
+    /// Append a balance, dropping the records older than `retention`
+    pub fn record(&mut self, balance: AccountBalance, retention: Duration) {
+        let oldest = balance.last_updated - retention;
+        let records = self.records.entry(balance.address.clone()).or_default();
+        records.push(balance);
+        records.retain(|record| record.last_updated >= oldest);
+    }
+
+    /// Change of `current` compared with the balance at `since`, or with the
+    /// oldest record if the history does not go back that far. Returns the
+    /// change and the time of the record it is compared with.
+    pub fn delta_since(
+        &self,
+        address: &str,
+        since: DateTime<Utc>,
+        current: u128,
+    ) -> Option<(i128, DateTime<Utc>)> {
+        let records = self.records.get(address)?;
+        let baseline = records
+            .iter()
+            .rev()
+            .find(|record| record.last_updated <= since)
+            .or_else(|| records.first())?;
+        Some((
+            current as i128 - baseline.balance as i128,
+            baseline.last_updated,
+        ))
+    }
+}
+
+/// Compare two balances of the same address, returning an alert if the change
+/// exceeds the percentage or the absolute threshold
+pub fn check_balance_change(
+    previous: &AccountBalance,
+    current: &AccountBalance,
+    config: &BalanceCheckConfig,
+) -> Option<BalanceChangeAlert> {
+    let alert = BalanceChangeAlert {
+        address: current.address.clone(),
+        token: current.token.clone(),
+        previous: previous.balance,
+        current: current.balance,
+    };
+    if alert.change() == 0 {
+        return None;
+    }
+
+    let exceeds_percent = alert
+        .change_percent()
+        .is_none_or(|percent| percent.abs() > config.max_change_percent);
+    let exceeds_amount = config
+        .max_change_amount
+        .is_some_and(|amount| alert.change().unsigned_abs() > amount as u128);
+    (exceeds_percent || exceeds_amount).then_some(alert)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn balance(balance: u128, secs: i64) -> AccountBalance {
+        AccountBalance {
+            address: "0x1".to_string(),
+            balance,
+            token: "0x00000000000000000000000000000001::STC::STC".to_string(),
+            last_updated: Utc.timestamp_opt(secs, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_check_balance_change() {
+        let config = BalanceCheckConfig::default();
+        assert!(check_balance_change(&balance(1000, 0), &balance(1000, 60), &config).is_none());
+        assert!(check_balance_change(&balance(1000, 0), &balance(1050, 60), &config).is_none());
+
+        let alert = check_balance_change(&balance(1000, 0), &balance(800, 60), &config).unwrap();
+        assert_eq!(alert.change(), -200);
+        assert_eq!(alert.change_percent(), Some(-20.0));
+
+        // Any deposit on an empty account is notified
+        assert!(check_balance_change(&balance(0, 0), &balance(1, 60), &config).is_some());
+
+        let config = BalanceCheckConfig {
+            max_change_percent: 100.0,
+            max_change_amount: Some(30),
+            ..BalanceCheckConfig::default()
+        };
+        assert!(check_balance_change(&balance(1000, 0), &balance(1050, 60), &config).is_some());
+    }
+
+    #[test]
+    fn test_balance_history() {
+        let mut history = BalanceHistory::default();
+        let retention = Duration::hours(48);
+        assert!(history
+            .delta_since("0x1", Utc.timestamp_opt(0, 0).unwrap(), 0)
+            .is_none());
+
+        for (amount, secs) in [(100, 0), (200, 3600), (300, 7200)] {
+            history.record(balance(amount, secs), retention);
+        }
+        assert_eq!(history.latest("0x1").unwrap().balance, 300);
+
+        // Compared with the latest record at or before `since`
+        let (delta, at) = history
+            .delta_since("0x1", Utc.timestamp_opt(5000, 0).unwrap(), 350)
+            .unwrap();
+        assert_eq!((delta, at.timestamp()), (150, 3600));
+
+        // The history does not go back that far, compared with the oldest record
+        let (delta, at) = history
+            .delta_since("0x1", Utc.timestamp_opt(-100, 0).unwrap(), 350)
+            .unwrap();
+        assert_eq!((delta, at.timestamp()), (250, 0));
+
+        // Records older than the retention are dropped
+        history.record(balance(400, 48 * 3600 + 3600), retention);
+        let (_, at) = history
+            .delta_since("0x1", Utc.timestamp_opt(0, 0).unwrap(), 400)
+            .unwrap();
+        assert_eq!(at.timestamp(), 3600);
+    }
+}
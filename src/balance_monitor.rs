@@ -0,0 +1,126 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    balance_check::{check_balance_change, BalanceCheckConfig, BalanceHistory},
+    chain_reader::ChainReader,
+    config::Config,
+    helper,
+    monitor_dispatcher::MonitorDispatcher,
+    storage,
+    types::AccountBalance,
+};
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use starcoin_types::account_address::AccountAddress;
+use std::{path::Path, sync::Arc, thread::JoinHandle, time::Duration};
+use tracing::{debug, error, info};
+
+pub const STC_TOKEN_CODE: &str = "0x00000000000000000000000000000001::STC::STC";
+
+/// Polls the STC balances of the watched addresses, keeping their history in
+/// `DATA_DIR` and alerting when a balance changes beyond the thresholds
+pub struct BalanceMonitor {
+    config: Arc<Config>,
+    dispatcher: Arc<dyn MonitorDispatcher>,
+    chain_reader: Arc<dyn ChainReader>,
+}
+
+impl BalanceMonitor {
+    pub fn new(
+        config: Arc<Config>,
+        dispatcher: Arc<dyn MonitorDispatcher>,
+        chain_reader: Arc<dyn ChainReader>,
+    ) -> Self {
+        Self {
+            config,
+            dispatcher,
+            chain_reader,
+        }
+    }
+
+    pub fn run(&self) -> Result<JoinHandle<()>> {
+        let addresses = self
+            .config
+            .watched_addresses
+            .iter()
+            .map(|address| {
+                AccountAddress::from_hex_literal(address)
+                    .map_err(|e| anyhow!("Invalid watched address {}: {:?}", address, e))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let config = self.config.clone();
+        let dispatcher = self.dispatcher.clone();
+        let chain_reader = self.chain_reader.clone();
+        Ok(std::thread::spawn(move || {
+            info!("BalanceMonitor::run | entered");
+            if addresses.is_empty() {
+                info!("BalanceMonitor::run | no watched address, exited");
+                return;
+            }
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_io()
+                .enable_time()
+                .build()
+                .unwrap();
+
+            let history_path = Path::new(&config.data_dir).join(BalanceHistory::FILE_NAME);
+            let mut history: BalanceHistory =
+                storage::load_json(&history_path).unwrap_or_else(|e| {
+                    error!("Failed to load balance history, starting empty: {}", e);
+                    BalanceHistory::default()
+                });
+            let check_config = BalanceCheckConfig {
+                max_change_percent: config.balance_change_percent,
+                max_change_amount: config.balance_change_amount,
+                ..BalanceCheckConfig::default()
+            };
+
+            loop {
+                for address in &addresses {
+                    let balance = match chain_reader.get_balance(*address) {
+                        Ok(Some(balance)) => balance,
+                        // Not a zero balance, nothing recorded until it has one
+                        Ok(None) => {
+                            debug!("No STC balance resource for {}", address);
+                            continue;
+                        }
+                        Err(e) => {
+                            error!("Failed to get balance of {}: {}", address, e);
+                            continue;
+                        }
+                    };
+                    let current = AccountBalance {
+                        address: helper::format_address(*address),
+                        balance,
+                        token: STC_TOKEN_CODE.to_string(),
+                        last_updated: Utc::now(),
+                    };
+                    debug!("BalanceMonitor::run | balance: {:?}", current);
+
+                    let alert = history.latest(&current.address).and_then(|previous| {
+                        check_balance_change(previous, &current, &check_config)
+                    });
+                    history.record(current, check_config.history_retention);
+
+                    if let Some(alert) = alert {
+                        info!("Balance change detected: {:?}", alert);
+                        let dispatcher = dispatcher.clone();
+                        if let Err(e) =
+                            rt.block_on(
+                                async move { dispatcher.dispatch_balance_change(&alert).await },
+                            )
+                        {
+                            error!("Failed to dispatch balance change: {}", e);
+                        }
+                    }
+                }
+
+                if let Err(e) = storage::save_json(&history_path, &history) {
+                    error!("Failed to save balance history: {}", e);
+                }
+                std::thread::sleep(Duration::from_secs(config.balance_check_interval));
+            }
+        }))
+    }
+}
@@ -0,0 +1,316 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::alert_throttle::AlertThrottle;
+use starcoin_types::block::BlockNumber;
+use std::collections::{HashMap, VecDeque};
+use tracing::{debug, info};
+
+/// Configuration for block production anomaly detection
+#[derive(Debug, Clone)]
+pub struct BlockAnomalyConfig {
+    /// Number of recent blocks the statistics are computed on
+    pub window_size: usize,
+    /// Seconds between two consecutive blocks above which to alert
+    pub max_block_interval_secs: u64,
+    /// Percentage a block difficulty may deviate from the window average
+    pub max_difficulty_change_percent: f64,
+    /// Percentage of the window blocks a single miner may produce
+    pub max_miner_share_percent: f64,
+    /// Minimal seconds between two alerts of the same kind
+    pub max_notify_time_interval: u64,
+}
+
+impl Default for BlockAnomalyConfig {
+    fn default() -> Self {
+        Self {
+            window_size: 100,
+            max_block_interval_secs: 120,
+            max_difficulty_change_percent: 100.0,
+            max_miner_share_percent: 50.0,
+            max_notify_time_interval: 600,
+        }
+    }
+}
+
+/// Header fields of a block the anomaly detection works on
+#[derive(Debug, Clone)]
+pub struct BlockSample {
+    pub number: BlockNumber,
+    /// Block timestamp in milliseconds
+    pub timestamp: u64,
+    pub difficulty: u128,
+    pub miner: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockAnomaly {
+    /// The block came `interval_secs` after its parent
+    SlowBlock {
+        number: BlockNumber,
+        interval_secs: u64,
+    },
+    /// The block difficulty deviates from the recent average
+    DifficultySwing {
+        number: BlockNumber,
+        difficulty: u128,
+        average_difficulty: u128,
+        change_percent: f64,
+    },
+    /// One miner produced an outsized share of the recent blocks
+    MinerDominance {
+        miner: String,
+        blocks: usize,
+        window: usize,
+    },
+}
+
+impl BlockAnomaly {
+    /// Identifies the anomaly for notification throttling
+    pub fn key(&self) -> String {
+        match self {
+            BlockAnomaly::SlowBlock { .. } => "slow_block".to_string(),
+            BlockAnomaly::DifficultySwing { .. } => "difficulty_swing".to_string(),
+            BlockAnomaly::MinerDominance { miner, .. } => format!("miner_dominance:{}", miner),
+        }
+    }
+}
+
+/// Keeps the recent block headers and reports anomalies as blocks arrive
+#[derive(Debug, Default)]
+pub struct BlockProductionTracker {
+    samples: VecDeque<BlockSample>,
+    throttle: AlertThrottle,
+}
+
+impl BlockProductionTracker {
+    /// Minimal number of blocks before difficulty swings are checked
+    const MIN_DIFFICULTY_SAMPLES: usize = 10;
+
+    /// Record a new block, returning the anomalies to notify.
+    ///
+    /// Blocks older than the latest one seen, e.g. from a resubscription, are
+    /// ignored.
+    pub fn observe(
+        &mut self,
+        sample: BlockSample,
+        now: u64,
+        config: &BlockAnomalyConfig,
+    ) -> Vec<BlockAnomaly> {
+        let mut anomalies = Vec::new();
+
+        if let Some(parent) = self.samples.back() {
+            if sample.number <= parent.number {
+                debug!(
+                    "BlockProductionTracker::observe | ignore block {}, latest is {}",
+                    sample.number, parent.number
+                );
+                return anomalies;
+            }
+
+            // A gap means blocks were missed, the interval is not between siblings
+            if sample.number == parent.number + 1 {
+                let interval_secs = sample.timestamp.saturating_sub(parent.timestamp) / 1000;
+                if interval_secs > config.max_block_interval_secs {
+                    anomalies.push(BlockAnomaly::SlowBlock {
+                        number: sample.number,
+                        interval_secs,
+                    });
+                }
+            }
+        }
+
+        // Nothing to compare with if the window is empty, e.g. of size 0
+        if !self.samples.is_empty()
+            && self.samples.len() >= Self::MIN_DIFFICULTY_SAMPLES.min(config.window_size)
+        {
+            let average_difficulty = self.samples.iter().map(|s| s.difficulty).sum::<u128>()
+                / self.samples.len() as u128;
+            if average_difficulty > 0 {
+                let change_percent = (sample.difficulty as f64 - average_difficulty as f64)
+                    / average_difficulty as f64
+                    * 100.0;
+                if change_percent.abs() > config.max_difficulty_change_percent {
+                    anomalies.push(BlockAnomaly::DifficultySwing {
+                        number: sample.number,
+                        difficulty: sample.difficulty,
+                        average_difficulty,
+                        change_percent,
+                    });
+                }
+            }
+        }
+
+        self.samples.push_back(sample);
+        while self.samples.len() > config.window_size {
+            self.samples.pop_front();
+        }
+
+        // Shares are only meaningful once the window is full
+        if self.samples.len() >= config.window_size {
+            let mut blocks_by_miner: HashMap<&str, usize> = HashMap::new();
+            for s in &self.samples {
+                *blocks_by_miner.entry(s.miner.as_str()).or_default() += 1;
+            }
+            let mut dominant: Vec<(&str, usize)> = blocks_by_miner
+                .into_iter()
+                .filter(|(_, blocks)| {
+                    *blocks as f64 / self.samples.len() as f64 * 100.0
+                        > config.max_miner_share_percent
+                })
+                .collect();
+            dominant.sort();
+            anomalies.extend(dominant.into_iter().map(|(miner, blocks)| {
+                BlockAnomaly::MinerDominance {
+                    miner: miner.to_string(),
+                    blocks,
+                    window: self.samples.len(),
+                }
+            }));
+        }
+
+        anomalies
+            .into_iter()
+            .filter(|anomaly| {
+                if !self.throttle.should_notify(
+                    &anomaly.key(),
+                    now,
+                    config.max_notify_time_interval,
+                ) {
+                    return false;
+                }
+                info!("Block production anomaly detected: {:?}", anomaly);
+                true
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(
+        number: BlockNumber,
+        timestamp_secs: u64,
+        difficulty: u128,
+        miner: &str,
+    ) -> BlockSample {
+        BlockSample {
+            number,
+            timestamp: timestamp_secs * 1000,
+            difficulty,
+            miner: miner.to_string(),
+        }
+    }
+
+    fn config() -> BlockAnomalyConfig {
+        BlockAnomalyConfig {
+            window_size: 10,
+            ..BlockAnomalyConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_slow_block() {
+        let config = config();
+        let mut tracker = BlockProductionTracker::default();
+
+        assert!(tracker
+            .observe(sample(1, 0, 100, "a"), 1000, &config)
+            .is_empty());
+        assert!(tracker
+            .observe(sample(2, 10, 100, "b"), 1000, &config)
+            .is_empty());
+        assert_eq!(
+            tracker.observe(sample(3, 200, 100, "c"), 1000, &config),
+            vec![BlockAnomaly::SlowBlock {
+                number: 3,
+                interval_secs: 190
+            }]
+        );
+
+        // A gap in block numbers is not an interval between siblings
+        assert!(tracker
+            .observe(sample(6, 500, 100, "d"), 5000, &config)
+            .is_empty());
+
+        // Older blocks are ignored
+        assert!(tracker
+            .observe(sample(5, 900, 100, "e"), 5000, &config)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_difficulty_swing() {
+        let config = config();
+        let mut tracker = BlockProductionTracker::default();
+        for number in 0..9 {
+            let miner = format!("miner{}", number);
+            assert!(tracker
+                .observe(sample(number, number * 10, 1000, &miner), 1000, &config)
+                .is_empty());
+        }
+
+        // Not enough blocks to compare the difficulty with yet
+        assert!(tracker
+            .observe(sample(9, 90, 5000, "x"), 1000, &config)
+            .is_empty());
+
+        let anomalies = tracker.observe(sample(10, 100, 5000, "y"), 1000, &config);
+        assert!(matches!(
+            anomalies.as_slice(),
+            [BlockAnomaly::DifficultySwing {
+                number: 10,
+                difficulty: 5000,
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn test_empty_window() {
+        let config = BlockAnomalyConfig {
+            window_size: 0,
+            ..BlockAnomalyConfig::default()
+        };
+        let mut tracker = BlockProductionTracker::default();
+        for number in 0..3 {
+            assert!(tracker
+                .observe(sample(number, number * 10, 1000, "a"), 1000, &config)
+                .is_empty());
+        }
+    }
+
+    #[test]
+    fn test_miner_dominance() {
+        let config = config();
+        let mut tracker = BlockProductionTracker::default();
+        for number in 0..9 {
+            let miner = if number % 3 == 0 { "other" } else { "big" };
+            assert!(tracker
+                .observe(sample(number, number * 10, 1000, miner), 1000, &config)
+                .is_empty());
+        }
+
+        assert_eq!(
+            tracker.observe(sample(9, 90, 1000, "big"), 1000, &config),
+            vec![BlockAnomaly::MinerDominance {
+                miner: "big".to_string(),
+                blocks: 7,
+                window: 10,
+            }]
+        );
+
+        // Notified once per max_notify_time_interval
+        assert!(tracker
+            .observe(sample(10, 100, 1000, "big"), 1100, &config)
+            .is_empty());
+        assert_eq!(
+            tracker
+                .observe(sample(11, 110, 1000, "big"), 2000, &config)
+                .len(),
+            1
+        );
+    }
+}
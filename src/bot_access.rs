@@ -0,0 +1,208 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::config::Config;
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// What a user may do with the bot, each role including the ones below it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Lookups only
+    Viewer,
+    /// Also the queries that cost RPC or ES work
+    Operator,
+    /// Also the changes of the shared state, such as address labels
+    Admin,
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Role::Viewer => "viewer",
+            Role::Operator => "operator",
+            Role::Admin => "admin",
+        })
+    }
+}
+
+impl FromStr for Role {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s.trim().to_lowercase().as_str() {
+            "viewer" => Role::Viewer,
+            "operator" => Role::Operator,
+            "admin" => Role::Admin,
+            _ => bail!("Unknown role: {}", s),
+        })
+    }
+}
+
+/// Parse `BOT_USER_ROLES`, e.g. `123456:admin,234567:operator`
+pub fn parse_user_roles(roles: &str) -> Result<HashMap<u64, Role>> {
+    roles
+        .split(',')
+        .map(|role| role.trim())
+        .filter(|role| !role.is_empty())
+        .map(|role| {
+            let (user_id, role) = role
+                .split_once(':')
+                .ok_or_else(|| anyhow!("Invalid user role: {}", role))?;
+            Ok((user_id.trim().parse()?, role.parse()?))
+        })
+        .collect()
+}
+
+/// Who may run the bot commands: the members of the allowed chats with the
+/// chat role, and the listed users with their own role in any chat
+#[derive(Debug, Clone)]
+pub struct AccessControl {
+    allowed_chats: HashSet<i64>,
+    chat_role: Role,
+    user_roles: HashMap<u64, Role>,
+}
+
+impl AccessControl {
+    pub fn new(
+        allowed_chats: HashSet<i64>,
+        chat_role: Role,
+        user_roles: HashMap<u64, Role>,
+    ) -> Self {
+        Self {
+            allowed_chats,
+            chat_role,
+            user_roles,
+        }
+    }
+
+    /// The alert chat is always allowed
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let mut allowed_chats: HashSet<i64> = config.bot_allowed_chats.iter().copied().collect();
+        allowed_chats.insert(config.telegram_chat_id.parse()?);
+        Ok(Self::new(
+            allowed_chats,
+            config.bot_chat_role,
+            config.bot_user_roles.clone(),
+        ))
+    }
+
+    /// The role of a user in a chat, `None` if the user may not use the bot
+    /// there. A listed user keeps the higher of both roles.
+    pub fn role_of(&self, chat_id: i64, user_id: Option<u64>) -> Option<Role> {
+        let user_role = user_id.and_then(|user_id| self.user_roles.get(&user_id).copied());
+        let chat_role = self
+            .allowed_chats
+            .contains(&chat_id)
+            .then_some(self.chat_role);
+        user_role.max(chat_role)
+    }
+}
+
+/// One command run, or refused, written as a JSON line of the audit log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub time: DateTime<Utc>,
+    pub chat_id: i64,
+    pub user_id: Option<u64>,
+    pub username: Option<String>,
+    pub command: String,
+    pub role: Option<Role>,
+    pub allowed: bool,
+}
+
+/// Append-only log of the bot commands, kept in `DATA_DIR`
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    pub const FILE_NAME: &'static str = "bot_audit.log";
+
+    pub fn new(data_dir: &str) -> Self {
+        Self {
+            path: Path::new(data_dir).join(Self::FILE_NAME),
+        }
+    }
+
+    pub fn record(&self, entry: &AuditEntry) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_role_of() -> Result<()> {
+        let access = AccessControl::new(
+            HashSet::from([-100]),
+            Role::Operator,
+            parse_user_roles("1:admin, 2:viewer")?,
+        );
+        assert!(parse_user_roles("1:root").is_err());
+        assert!(parse_user_roles("admin").is_err());
+
+        assert_eq!(access.role_of(-100, Some(3)), Some(Role::Operator));
+        assert_eq!(access.role_of(-100, None), Some(Role::Operator));
+        // Listed users keep the higher role
+        assert_eq!(access.role_of(-100, Some(1)), Some(Role::Admin));
+        assert_eq!(access.role_of(-100, Some(2)), Some(Role::Operator));
+        // Only listed users outside the allowed chats
+        assert_eq!(access.role_of(-200, Some(1)), Some(Role::Admin));
+        assert_eq!(access.role_of(-200, Some(3)), None);
+        assert!(Role::Admin > Role::Operator && Role::Operator > Role::Viewer);
+        Ok(())
+    }
+
+    #[test]
+    fn test_audit_log() -> Result<()> {
+        let dir =
+            std::env::temp_dir().join(format!("starcoin-monitor-audit-{}", std::process::id()));
+        let log = AuditLog::new(dir.to_str().unwrap());
+        let entry = AuditEntry {
+            time: Utc::now(),
+            chat_id: -100,
+            user_id: Some(1),
+            username: Some("alice".to_string()),
+            command: "/transactions 1000 1100".to_string(),
+            role: Some(Role::Operator),
+            allowed: true,
+        };
+        log.record(&entry)?;
+        log.record(&AuditEntry {
+            allowed: false,
+            ..entry.clone()
+        })?;
+
+        let content = std::fs::read_to_string(dir.join(AuditLog::FILE_NAME))?;
+        let entries: Vec<AuditEntry> = content
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<std::result::Result<_, _>>()?;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0], entry);
+        assert!(!entries[1].allowed);
+
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
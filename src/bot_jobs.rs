@@ -0,0 +1,226 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use chrono::{DateTime, Utc};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+use tokio::{sync::watch, task::AbortHandle};
+
+pub type JobId = u64;
+
+/// A bot query running in the background
+#[derive(Debug, Clone, PartialEq)]
+pub struct JobInfo {
+    pub id: JobId,
+    pub user_id: Option<u64>,
+    pub chat_id: i64,
+    pub description: String,
+    pub started_at: DateTime<Utc>,
+    /// The message showing the progress of the job, edited as it runs
+    pub status_message_id: Option<i32>,
+}
+
+/// Why a job was refused, with the number of jobs running overall or of the
+/// user
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JobLimit {
+    TooManyJobs(usize),
+    TooManyUserJobs(usize),
+}
+
+impl std::fmt::Display for JobLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JobLimit::TooManyJobs(jobs) => write!(f, "{} jobs already running", jobs),
+            JobLimit::TooManyUserJobs(jobs) => {
+                write!(f, "{} jobs of the user already running", jobs)
+            }
+        }
+    }
+}
+
+impl std::error::Error for JobLimit {}
+
+struct RunningJob {
+    info: JobInfo,
+    abort_handle: Option<AbortHandle>,
+}
+
+/// The expensive bot queries, run as background jobs so they do not hold up
+/// the other commands, limited in number overall and per user
+pub struct JobQueue {
+    max_jobs: usize,
+    max_jobs_per_user: usize,
+    next_id: AtomicU64,
+    jobs: Mutex<HashMap<JobId, RunningJob>>,
+}
+
+impl JobQueue {
+    pub fn new(max_jobs: usize, max_jobs_per_user: usize) -> Self {
+        Self {
+            max_jobs,
+            max_jobs_per_user,
+            next_id: AtomicU64::new(1),
+            jobs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Reserve a slot for a new job, failing if the limits are reached
+    pub fn start(
+        &self,
+        user_id: Option<u64>,
+        chat_id: i64,
+        description: &str,
+    ) -> Result<JobId, JobLimit> {
+        let mut jobs = self.jobs.lock().unwrap();
+        if jobs.len() >= self.max_jobs {
+            return Err(JobLimit::TooManyJobs(jobs.len()));
+        }
+        let user_jobs = jobs
+            .values()
+            .filter(|job| job.info.user_id == user_id)
+            .count();
+        if user_jobs >= self.max_jobs_per_user {
+            return Err(JobLimit::TooManyUserJobs(user_jobs));
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        jobs.insert(
+            id,
+            RunningJob {
+                info: JobInfo {
+                    id,
+                    user_id,
+                    chat_id,
+                    description: description.to_string(),
+                    started_at: Utc::now(),
+                    status_message_id: None,
+                },
+                abort_handle: None,
+            },
+        );
+        Ok(id)
+    }
+
+    pub fn set_status_message(&self, id: JobId, message_id: i32) {
+        if let Some(job) = self.jobs.lock().unwrap().get_mut(&id) {
+            job.info.status_message_id = Some(message_id);
+        }
+    }
+
+    /// Attach the task of a job, for `/cancel`. Does nothing if the job
+    /// already finished.
+    pub fn attach(&self, id: JobId, abort_handle: AbortHandle) {
+        if let Some(job) = self.jobs.lock().unwrap().get_mut(&id) {
+            job.abort_handle = Some(abort_handle);
+        }
+    }
+
+    pub fn finish(&self, id: JobId) {
+        self.jobs.lock().unwrap().remove(&id);
+    }
+
+    /// Cancel a job of the user, or all of them if `id` is `None`, returning
+    /// the cancelled jobs. `any_user` allows cancelling the jobs of others.
+    pub fn cancel(&self, id: Option<JobId>, user_id: Option<u64>, any_user: bool) -> Vec<JobInfo> {
+        let mut jobs = self.jobs.lock().unwrap();
+        let ids: Vec<JobId> = jobs
+            .values()
+            .filter(|job| id.is_none_or(|id| job.info.id == id))
+            .filter(|job| job.info.user_id == user_id || (any_user && id.is_some()))
+            .map(|job| job.info.id)
+            .collect();
+        ids.into_iter()
+            .filter_map(|id| jobs.remove(&id))
+            .map(|job| {
+                if let Some(abort_handle) = job.abort_handle {
+                    abort_handle.abort();
+                }
+                job.info
+            })
+            .collect()
+    }
+
+    /// The jobs started from a chat
+    pub fn jobs(&self, chat_id: i64) -> Vec<JobInfo> {
+        let mut jobs: Vec<JobInfo> = self
+            .jobs
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|job| job.info.chat_id == chat_id)
+            .map(|job| job.info.clone())
+            .collect();
+        jobs.sort_by_key(|job| job.id);
+        jobs
+    }
+}
+
+/// Progress reported by a running job, shown in its status message
+#[derive(Clone)]
+pub struct JobProgress {
+    sender: Arc<watch::Sender<Option<String>>>,
+}
+
+impl JobProgress {
+    pub fn new() -> (Self, watch::Receiver<Option<String>>) {
+        let (sender, receiver) = watch::channel(None);
+        (
+            Self {
+                sender: Arc::new(sender),
+            },
+            receiver,
+        )
+    }
+
+    pub fn set(&self, progress: impl Into<String>) {
+        self.sender.send_replace(Some(progress.into()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[tokio::test]
+    async fn test_job_queue() -> Result<()> {
+        let queue = JobQueue::new(2, 1);
+        let first = queue.start(Some(1), -100, "/transactions 1 2000")?;
+        // One job per user at a time
+        assert_eq!(
+            queue.start(Some(1), -100, "/trace 0x01"),
+            Err(JobLimit::TooManyUserJobs(1))
+        );
+        let second = queue.start(Some(2), -200, "/trace 0x01")?;
+        assert_ne!(first, second);
+        // And two in all
+        assert_eq!(
+            queue.start(Some(3), -100, "/trace 0x02"),
+            Err(JobLimit::TooManyJobs(2))
+        );
+
+        let task = tokio::spawn(futures::future::pending::<()>());
+        queue.attach(first, task.abort_handle());
+        // The jobs of others can only be cancelled by id, with `any_user`
+        assert!(queue.cancel(Some(first), Some(2), false).is_empty());
+        assert!(queue.cancel(None, Some(3), true).is_empty());
+        let cancelled = queue.cancel(None, Some(1), false);
+        assert_eq!(cancelled.len(), 1);
+        assert_eq!(cancelled[0].id, first);
+        assert!(task.await.unwrap_err().is_cancelled());
+
+        // Only the jobs of the chat are listed
+        assert!(queue.jobs(-100).is_empty());
+        assert_eq!(queue.jobs(-200).len(), 1);
+        queue.finish(second);
+        assert!(queue.jobs(-200).is_empty());
+        queue.start(Some(1), -100, "/transactions 1 2000")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use starcoin_crypto::HashValue;
+use starcoin_rpc_api::{
+    chain::GetTransactionOption,
+    types::{
+        BlockView, ChainInfoView, SignedUserTransactionView, StructTagView, TransactionInfoView,
+        TransactionView,
+    },
+};
+use starcoin_rpc_client::RpcClient;
+use starcoin_types::{account_address::AccountAddress, block::BlockNumber};
+
+/// Resource holding the STC balance of an account
+pub const STC_BALANCE_RESOURCE: &str =
+    "0x00000000000000000000000000000001::Account::Balance<0x00000000000000000000000000000001::STC::STC>";
+
+/// Read-only chain queries needed by the monitor handlers.
+///
+/// Handlers depend on this trait instead of `RpcClient` directly, so they can
+/// be driven by a live node, a pool of nodes or recorded fixtures.
+pub trait ChainReader: Send + Sync {
+    /// Get the current chain head
+    fn get_chain_info(&self) -> Result<ChainInfoView>;
+
+    /// Get `count` blocks starting from `number`
+    fn get_blocks_by_number(
+        &self,
+        number: Option<BlockNumber>,
+        count: u64,
+    ) -> Result<Vec<BlockView>>;
+
+    /// Get a transaction by hash, with its payload decoded
+    fn get_transaction(&self, txn_hash: HashValue) -> Result<Option<TransactionView>>;
+
+    /// Get the execution results of the transactions in a block
+    fn get_block_txn_infos(&self, block_hash: HashValue) -> Result<Vec<TransactionInfoView>>;
+
+    /// Get a transaction waiting in the txpool by hash
+    fn get_pending_transaction(
+        &self,
+        txn_hash: HashValue,
+    ) -> Result<Option<SignedUserTransactionView>>;
+
+    /// Get the STC balance of an account, `None` if it has no balance resource
+    fn get_balance(&self, address: AccountAddress) -> Result<Option<u128>>;
+}
+
+impl ChainReader for RpcClient {
+    fn get_chain_info(&self) -> Result<ChainInfoView> {
+        self.chain_info()
+    }
+
+    fn get_blocks_by_number(
+        &self,
+        number: Option<BlockNumber>,
+        count: u64,
+    ) -> Result<Vec<BlockView>> {
+        self.chain_get_blocks_by_number(number, count, None)
+    }
+
+    fn get_transaction(&self, txn_hash: HashValue) -> Result<Option<TransactionView>> {
+        self.chain_get_transaction(txn_hash, Some(GetTransactionOption { decode: true }))
+    }
+
+    fn get_block_txn_infos(&self, block_hash: HashValue) -> Result<Vec<TransactionInfoView>> {
+        self.chain_get_block_txn_infos(block_hash)
+    }
+
+    fn get_pending_transaction(
+        &self,
+        txn_hash: HashValue,
+    ) -> Result<Option<SignedUserTransactionView>> {
+        self.get_pending_txn_by_hash(txn_hash)
+    }
+
+    fn get_balance(&self, address: AccountAddress) -> Result<Option<u128>> {
+        let resource_type: StructTagView = STC_BALANCE_RESOURCE.parse()?;
+        // `Balance<T>` wraps a `Token<T>` holding a single u128
+        self.state_get_resource(address, resource_type.0, false, None)?
+            .map(|resource| bcs_ext::from_bytes::<u128>(&resource.raw.0))
+            .transpose()
+    }
+}
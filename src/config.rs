@@ -1,20 +1,73 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::alert_routing::{parse_alert_routes, AlertRoute};
+use crate::alert_sender::Severity;
+use crate::bot_access::{parse_user_roles, Role};
+use crate::locale::Locale;
+use crate::rich_text::TextFormat;
+use crate::supply_check::parse_supply_limits;
 use anyhow::Result;
 use serde::Deserialize;
-use std::env;
+use std::{collections::HashMap, env};
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
-    pub starcoin_rpc_url: String,
+    pub starcoin_rpc_urls: Vec<String>,
+    pub rpc_max_head_lag: u64,
+    pub rpc_health_check_interval: u64,
     pub telegram_bot_token: String,
     pub telegram_chat_id: String,
     pub telegram_proxy: Option<String>,
+    pub telegram_text_format: TextFormat,
+    /// Locale of the chats that did not choose one with `/language`
+    pub default_locale: Locale,
+    /// Messages sent per second in all, and per minute to each group chat
+    pub telegram_max_messages_per_sec: usize,
+    pub telegram_max_chat_messages_per_min: usize,
     pub min_transaction_amount: u128,
     pub es_url: String,
     pub es_user_name: String,
     pub es_password: String,
+    pub node_health_check_interval: u64,
+    pub node_head_stale_secs: u64,
+    pub node_max_head_difference: u64,
+    pub block_anomaly_window_size: usize,
+    pub max_block_interval_secs: u64,
+    pub max_difficulty_change_percent: f64,
+    pub max_miner_share_percent: f64,
+    pub max_failure_rate_percent: f64,
+    pub min_txns_for_failure_rate: usize,
+    pub max_block_gas_used: Option<u64>,
+    pub max_gas_price: Option<u64>,
+    pub watched_addresses: Vec<String>,
+    pub balance_check_interval: u64,
+    pub balance_change_percent: f64,
+    pub balance_change_amount: Option<u64>,
+    pub data_dir: String,
+    pub token_supply_limits: HashMap<String, u128>,
+    pub supply_window_secs: u64,
+    pub enabled_handlers: Vec<String>,
+    pub velocity_max_amount: u128,
+    pub velocity_window_secs: u64,
+    pub velocity_window_blocks: Option<u64>,
+    pub price_source: String,
+    pub price_api_url: String,
+    pub price_file: String,
+    pub price_cache_secs: u64,
+    pub min_transaction_usd: Option<f64>,
+    pub bot_allowed_chats: Vec<i64>,
+    pub bot_chat_role: Role,
+    pub bot_user_roles: HashMap<u64, Role>,
+    pub bot_max_jobs: usize,
+    pub bot_max_jobs_per_user: usize,
+    pub bot_command_cooldown_secs: u64,
+    pub alert_routes: Vec<AlertRoute>,
+    pub alert_oncall_users: Vec<String>,
+    pub alert_mention_severity: Severity,
+    pub alert_ack_reset_secs: u64,
+    pub alert_digest_window_secs: u64,
+    pub alert_dedup_secs: u64,
 }
 
 impl Config {
@@ -22,8 +75,21 @@ impl Config {
         dotenv::dotenv().ok();
 
         let config = Config {
-            starcoin_rpc_url: env::var("STARCOIN_RPC_URL")
-                .unwrap_or_else(|_| "ws://main.seed.starcoin.org:9870".to_string()),
+            starcoin_rpc_urls: env::var("STARCOIN_RPC_URLS")
+                .or_else(|_| env::var("STARCOIN_RPC_URL"))
+                .unwrap_or_else(|_| "ws://main.seed.starcoin.org:9870".to_string())
+                .split(',')
+                .map(|url| url.trim().to_string())
+                .filter(|url| !url.is_empty())
+                .collect(),
+            rpc_max_head_lag: env::var("RPC_MAX_HEAD_LAG")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            rpc_health_check_interval: env::var("RPC_HEALTH_CHECK_INTERVAL")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10),
             telegram_bot_token: env::var("TELEGRAM_BOT_TOKEN")
                 .expect("TELEGRAM_BOT_TOKEN is not set"),
             telegram_chat_id: env::var("TELEGRAM_CHAT_ID").expect("TELEGRAM_CHAT_ID is not set"),
@@ -31,6 +97,20 @@ impl Config {
                 env::VarError::NotPresent => Ok(None),
                 _ => Err(e),
             })?,
+            telegram_text_format: env::var("TELEGRAM_TEXT_FORMAT")
+                .unwrap_or_else(|_| "markdownv2".to_string())
+                .parse()?,
+            default_locale: env::var("DEFAULT_LOCALE")
+                .unwrap_or_else(|_| "zh-CN".to_string())
+                .parse()?,
+            telegram_max_messages_per_sec: env::var("TELEGRAM_MAX_MESSAGES_PER_SEC")
+                .unwrap_or_else(|_| "30".to_string())
+                .parse()
+                .unwrap_or(30),
+            telegram_max_chat_messages_per_min: env::var("TELEGRAM_MAX_CHAT_MESSAGES_PER_MIN")
+                .unwrap_or_else(|_| "20".to_string())
+                .parse()
+                .unwrap_or(20),
             min_transaction_amount: env::var("MIN_TRANSACTION_AMOUNT")
                 .unwrap_or_else(|_| "1000000000".to_string())
                 .parse()
@@ -38,6 +118,145 @@ impl Config {
             es_url: env::var("ES_URL").unwrap_or_else(|_| "http://127.0.0.1:9200".to_string()),
             es_user_name: env::var("ES_USER_NAME").unwrap_or_else(|_| "elastic".to_string()),
             es_password: env::var("ES_PASSWORD").unwrap_or_else(|_| "changeme".to_string()),
+            node_health_check_interval: env::var("NODE_HEALTH_CHECK_INTERVAL")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+            node_head_stale_secs: env::var("NODE_HEAD_STALE_SECS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .unwrap_or(300),
+            node_max_head_difference: env::var("NODE_MAX_HEAD_DIFFERENCE")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10),
+            block_anomaly_window_size: env::var("BLOCK_ANOMALY_WINDOW_SIZE")
+                .unwrap_or_else(|_| "100".to_string())
+                .parse()
+                .unwrap_or(100),
+            max_block_interval_secs: env::var("MAX_BLOCK_INTERVAL_SECS")
+                .unwrap_or_else(|_| "120".to_string())
+                .parse()
+                .unwrap_or(120),
+            max_difficulty_change_percent: env::var("MAX_DIFFICULTY_CHANGE_PERCENT")
+                .unwrap_or_else(|_| "100".to_string())
+                .parse()
+                .unwrap_or(100.0),
+            max_miner_share_percent: env::var("MAX_MINER_SHARE_PERCENT")
+                .unwrap_or_else(|_| "50".to_string())
+                .parse()
+                .unwrap_or(50.0),
+            max_failure_rate_percent: env::var("MAX_FAILURE_RATE_PERCENT")
+                .unwrap_or_else(|_| "50".to_string())
+                .parse()
+                .unwrap_or(50.0),
+            min_txns_for_failure_rate: env::var("MIN_TXNS_FOR_FAILURE_RATE")
+                .unwrap_or_else(|_| "5".to_string())
+                .parse()
+                .unwrap_or(5),
+            // Gas bounds are disabled unless set
+            max_block_gas_used: env::var("MAX_BLOCK_GAS_USED")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            max_gas_price: env::var("MAX_GAS_PRICE").ok().and_then(|v| v.parse().ok()),
+            watched_addresses: env::var("WATCHED_ADDRESSES")
+                .unwrap_or_default()
+                .split(',')
+                .map(|address| address.trim().to_string())
+                .filter(|address| !address.is_empty())
+                .collect(),
+            balance_check_interval: env::var("BALANCE_CHECK_INTERVAL")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .unwrap_or(300),
+            balance_change_percent: env::var("BALANCE_CHANGE_PERCENT")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10.0),
+            balance_change_amount: env::var("BALANCE_CHANGE_AMOUNT")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            data_dir: env::var("DATA_DIR").unwrap_or_else(|_| "data".to_string()),
+            token_supply_limits: parse_supply_limits(
+                &env::var("TOKEN_SUPPLY_LIMITS").unwrap_or_default(),
+            )?,
+            supply_window_secs: env::var("SUPPLY_WINDOW_SECS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .unwrap_or(3600),
+            enabled_handlers: env::var("ENABLED_HANDLERS")
+                .unwrap_or_else(|_| "default,governance,velocity".to_string())
+                .split(',')
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .collect(),
+            velocity_max_amount: env::var("VELOCITY_MAX_AMOUNT")
+                .unwrap_or_else(|_| "10000000000000".to_string())
+                .parse()
+                .unwrap_or(10_000_000_000_000), // 10000 STC in nano units
+            velocity_window_secs: env::var("VELOCITY_WINDOW_SECS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .unwrap_or(3600),
+            velocity_window_blocks: env::var("VELOCITY_WINDOW_BLOCKS")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            price_source: env::var("PRICE_SOURCE").unwrap_or_else(|_| "coingecko".to_string()),
+            price_api_url: env::var("PRICE_API_URL")
+                .unwrap_or_else(|_| "https://api.coingecko.com/api/v3".to_string()),
+            price_file: env::var("PRICE_FILE").unwrap_or_else(|_| "prices.json".to_string()),
+            price_cache_secs: env::var("PRICE_CACHE_SECS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .unwrap_or(300),
+            min_transaction_usd: env::var("MIN_TRANSACTION_USD")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+            bot_allowed_chats: env::var("BOT_ALLOWED_CHATS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|chat_id| chat_id.trim())
+                .filter(|chat_id| !chat_id.is_empty())
+                .map(|chat_id| chat_id.parse())
+                .collect::<Result<_, _>>()?,
+            bot_chat_role: env::var("BOT_CHAT_ROLE")
+                .unwrap_or_else(|_| "operator".to_string())
+                .parse()?,
+            bot_user_roles: parse_user_roles(&env::var("BOT_USER_ROLES").unwrap_or_default())?,
+            bot_max_jobs: env::var("BOT_MAX_JOBS")
+                .unwrap_or_else(|_| "4".to_string())
+                .parse()
+                .unwrap_or(4),
+            bot_max_jobs_per_user: env::var("BOT_MAX_JOBS_PER_USER")
+                .unwrap_or_else(|_| "1".to_string())
+                .parse()
+                .unwrap_or(1),
+            bot_command_cooldown_secs: env::var("BOT_COMMAND_COOLDOWN_SECS")
+                .unwrap_or_else(|_| "10".to_string())
+                .parse()
+                .unwrap_or(10),
+            alert_routes: parse_alert_routes(&env::var("ALERT_ROUTES").unwrap_or_default())?,
+            alert_oncall_users: env::var("ALERT_ONCALL_USERS")
+                .unwrap_or_default()
+                .split(',')
+                .map(|user| user.trim().to_string())
+                .filter(|user| !user.is_empty())
+                .collect(),
+            alert_mention_severity: env::var("ALERT_MENTION_SEVERITY")
+                .unwrap_or_else(|_| "critical".to_string())
+                .parse()?,
+            alert_ack_reset_secs: env::var("ALERT_ACK_RESET_SECS")
+                .unwrap_or_else(|_| "3600".to_string())
+                .parse()
+                .unwrap_or(3600),
+            alert_digest_window_secs: env::var("ALERT_DIGEST_WINDOW_SECS")
+                .unwrap_or_else(|_| "60".to_string())
+                .parse()
+                .unwrap_or(60),
+            alert_dedup_secs: env::var("ALERT_DEDUP_SECS")
+                .unwrap_or_else(|_| "86400".to_string())
+                .parse()
+                .unwrap_or(86400),
         };
 
         Ok(config)
@@ -75,4 +294,20 @@ mod tests {
         std::env::remove_var("ES_USER_NAME");
         std::env::remove_var("ES_PASSWORD");
     }
+
+    #[test]
+    fn test_config_rpc_urls() {
+        std::env::set_var(
+            "STARCOIN_RPC_URLS",
+            "ws://node1:9870, ws://node2:9870,,wss://node3:9870",
+        );
+
+        let config = Config::load().expect("Failed to load config");
+        assert_eq!(
+            config.starcoin_rpc_urls,
+            vec!["ws://node1:9870", "ws://node2:9870", "wss://node3:9870"]
+        );
+
+        std::env::remove_var("STARCOIN_RPC_URLS");
+    }
 }
@@ -1,69 +1,43 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::alert_sender::{Alert, AlertCategory, AlertSender, Severity};
+use crate::balance_monitor::STC_TOKEN_CODE;
 use crate::config::Config;
+use crate::locale::LocalizedText;
+use crate::price_provider::{self, PriceProvider};
 use crate::telegram::TelegramBot;
+use crate::templates;
+use crate::transfer_index::{parse_hex_amount, EsHit, EsTransferIndex, TransferDocument};
 use anyhow::Result;
-use base64::Engine;
 use chrono::{TimeZone, Utc};
-use reqwest::Client;
-use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::thread::JoinHandle;
 use tracing::info;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-struct TransferDocument {
-    amount: String,
-    amount_value: Option<u128>,
-    identifier: String,
-    receiver: String,
-    sender: String,
-    timestamp: i64,
-    txn_hash: String,
-    #[serde(rename = "type_tag")]
-    type_tag: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct EsSearchResponse {
-    hits: EsHits,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct EsHits {
-    total: EsTotal,
-    hits: Vec<EsHit>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct EsTotal {
-    value: i64,
-    relation: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct EsHit {
-    #[serde(rename = "_source")]
-    source: TransferDocument,
-}
-
 pub struct DailyNotificationService {
     config: Arc<Config>,
     telegram_bot: Arc<TelegramBot>,
+    price_provider: Arc<dyn PriceProvider>,
 }
 
 impl DailyNotificationService {
-    pub fn new(config: Arc<Config>, telegram_bot: Arc<TelegramBot>) -> Self {
+    pub fn new(
+        config: Arc<Config>,
+        telegram_bot: Arc<TelegramBot>,
+        price_provider: Arc<dyn PriceProvider>,
+    ) -> Self {
         Self {
             config,
             telegram_bot,
+            price_provider,
         }
     }
 
     pub fn run(&self) -> Result<JoinHandle<()>> {
         let config = self.config.clone();
         let telegram_bot = self.telegram_bot.clone();
+        let price_provider = self.price_provider.clone();
         Ok(std::thread::spawn(move || {
             info!("Starting Daily Notification Service...");
 
@@ -118,7 +92,13 @@ impl DailyNotificationService {
                             info!("Retrieved {} transfer documents", transfers.len());
 
                             // Send daily summary
-                            if let Err(e) = send_daily_summary(transfers, &telegram_bot).await {
+                            if let Err(e) = send_daily_summary(
+                                transfers,
+                                &telegram_bot,
+                                price_provider.as_ref(),
+                            )
+                            .await
+                            {
                                 tracing::error!("Failed to send daily summary: {}", e);
                             }
                         }
@@ -174,18 +154,7 @@ async fn query_daily_transfers(
 ) -> Result<Vec<TransferDocument>> {
     info!("Starting daily ES query for transfers...");
 
-    let client = Client::new();
-    let url = format!(
-        "{}/main.0727.transfer/_search",
-        es_url.trim_end_matches('/')
-    );
-
-    // Create basic auth header
-    let auth_header = format!(
-        "Basic {}",
-        base64::engine::general_purpose::STANDARD
-            .encode(format!("{}:{}", es_user_name, es_password))
-    );
+    let index = EsTransferIndex::new(es_url, es_user_name, es_password);
 
     let query = serde_json::json!({
       "query": {
@@ -213,75 +182,41 @@ async fn query_daily_transfers(
       "sort": [{"timestamp": "desc"}]
     });
 
-    let response = client
-        .post(&url)
-        .header("Authorization", auth_header)
-        .header("Content-Type", "application/json")
-        .json(&query)
-        .send()
-        .await?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let error_body = response.text().await?;
-        anyhow::bail!(
-            "Elasticsearch request failed with status: {} - Error body: {}",
-            status,
-            error_body
-        );
-    }
-
-    let response_text = response.text().await?;
-    let es_response: EsSearchResponse = serde_json::from_str(&response_text)?;
-
-    info!(
-        "Retrieved {} transfer documents",
-        es_response.hits.hits.len()
-    );
+    let hits = index.search(&query).await?;
+    info!("Retrieved {} transfer documents", hits.len());
 
     // Process and store the results for later notification
-    Ok(process_transfers(&es_response.hits.hits, min_trans_amount))
+    Ok(process_transfers(&hits, min_trans_amount))
 }
 
 async fn send_daily_summary(
     daily_results: Vec<TransferDocument>,
     telegram_bot: &TelegramBot,
+    price_provider: &dyn PriceProvider,
 ) -> Result<()> {
     info!("Sending daily summary notification...");
 
-    let today = Utc::now().format("%Y-%m-%d");
-
-    if daily_results.is_empty() {
-        let message = format!(
-            "📊 【每日交易汇总】\n\n\
-        📅 日期: {}\n\
-        今日没有发现大额交易",
-            today
-        );
-        telegram_bot.send_message(&message).await?;
-        return Ok(());
-    }
-
-    // Calculate summary statistics
-    let total_transfers = daily_results.len();
+    let today = Utc::now().format("%Y-%m-%d").to_string();
     let total_amount: u128 = daily_results
         .iter()
         .map(|t| parse_hex_amount(&t.amount).unwrap_or(0))
         .sum();
+    let value = if daily_results.is_empty() {
+        None
+    } else {
+        price_provider::fiat_value(price_provider, STC_TOKEN_CODE, total_amount).await
+    };
+    let message = LocalizedText::new(|locale| {
+        templates::daily_summary(locale, &today, daily_results.len(), total_amount, value)
+    });
 
-    // Format the message
-    let message = format!(
-        "📊 【每日交易汇总】\n\n\
-            📅 日期: {}\n\
-            🔢 大额交易总数: {}\n\
-            💰 交易总额: {:.9} STC\n",
-        Utc::now().format("%Y-%m-%d"),
-        total_transfers,
-        (total_amount as f64) / 1e9,
-    );
-
-    // Send the message
-    telegram_bot.send_message(&message).await?;
+    telegram_bot
+        .send_alert(&Alert::new(
+            AlertCategory::DailySummary,
+            Severity::Info,
+            message,
+        ))
+        .await?;
     info!("Daily summary notification sent successfully");
 
     Ok(())
@@ -319,43 +254,6 @@ fn process_transfers(hits: &[EsHit], min_amount: u128) -> Vec<TransferDocument>
     large_transfers
 }
 
-fn parse_hex_amount(hex_amount: &str) -> Result<u128> {
-    // Remove "0x" prefix if present
-    let hex_str = if hex_amount.starts_with("0x") {
-        &hex_amount[2..]
-    } else {
-        hex_amount
-    };
-
-    // Convert hex string to bytes using hex crate
-    let bytes = hex::decode(hex_str)?;
-
-    // The amount is stored as a Move u128 in BCS format
-    // BCS stores u128 as 16 bytes in little-endian order
-    // We need to reverse the bytes to get the correct value
-
-    if bytes.len() > 16 {
-        anyhow::bail!("Amount exceeds u128 size: {} bytes", bytes.len());
-    }
-
-    // Pad to 16 bytes if necessary (add zeros at the end for little-endian)
-    let mut padded_bytes = vec![0u8; 16];
-    for (i, &byte) in bytes.iter().enumerate() {
-        if i < 16 {
-            padded_bytes[i] = byte;
-        }
-    }
-
-    // Convert from little-endian bytes to u128
-    // In little-endian, the least significant byte is at index 0
-    let mut amount = 0u128;
-    for (i, &byte) in padded_bytes.iter().enumerate() {
-        amount = amount.wrapping_add((byte as u128).wrapping_shl((i * 8) as u32));
-    }
-
-    Ok(amount)
-}
-
 fn get_today_start_timestamp() -> u64 {
     // Get today's start timestamp (00:00:00)
     let today = Utc::now().date_naive();
@@ -394,26 +292,6 @@ mod tests {
         utc_datetime.timestamp_millis() as u64
     }
 
-    #[test]
-    fn test_parse_hex_amount() {
-        // Test with actual ES data format
-        // These are real amounts from ES, stored as Move u128 in BCS format
-
-        // Test a small amount
-        let amount = parse_hex_amount("0x64").unwrap();
-        assert_eq!(amount, 100);
-
-        // Test with padding zeros (this should be a small amount)
-        let amount = parse_hex_amount("0x0a000000000000000000000000000000").unwrap();
-        println!("Parsed amount: {}", amount);
-        // This should be a reasonable amount, not necessarily 10 STC
-
-        // Test another example from actual ES data
-        let amount = parse_hex_amount("0x583e2993a60100000000000000000000").unwrap();
-        println!("Parsed amount: {}", amount);
-        // This should be a reasonable amount
-    }
-
     #[test]
     fn test_get_date_start_timestamp() {
         // Test the new function for generating timestamps from date strings
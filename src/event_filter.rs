@@ -0,0 +1,194 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use starcoin_rpc_api::types::{pubsub::EventFilter, StrView};
+use starcoin_types::{account_address::AccountAddress, language_storage::TypeTag};
+use std::{
+    collections::{HashSet, VecDeque},
+    hash::Hash,
+};
+
+/// Events a dispatcher wants to receive.
+///
+/// An event matches when it was emitted by a handle created by one of
+/// `addresses` and has one of `type_tags`. An empty list matches anything, the
+/// same as the node does for the fields of an `EventFilter`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EventInterest {
+    pub addresses: Vec<AccountAddress>,
+    pub type_tags: Vec<TypeTag>,
+}
+
+impl EventInterest {
+    /// Every event on chain
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    pub fn is_all(&self) -> bool {
+        self.addresses.is_empty() && self.type_tags.is_empty()
+    }
+
+    pub fn matches(&self, creator: &AccountAddress, type_tag: &TypeTag) -> bool {
+        (self.addresses.is_empty() || self.addresses.contains(creator))
+            && (self.type_tags.is_empty() || self.type_tags.contains(type_tag))
+    }
+
+    pub fn to_event_filter(&self) -> EventFilter {
+        EventFilter {
+            from_block: None,
+            to_block: None,
+            event_keys: None,
+            addrs: (!self.addresses.is_empty()).then(|| self.addresses.clone()),
+            type_tags: (!self.type_tags.is_empty())
+                .then(|| self.type_tags.iter().cloned().map(StrView).collect()),
+            limit: None,
+        }
+    }
+}
+
+/// Merge interests into as few subscriptions as possible.
+///
+/// The node requires an event to match every field of a filter, so only the
+/// interests on the same addresses can share a subscription, with the union of
+/// their type tags.
+pub fn merge_interests(interests: &[EventInterest]) -> Vec<EventInterest> {
+    if interests.iter().any(EventInterest::is_all) {
+        return vec![EventInterest::all()];
+    }
+
+    let mut merged: Vec<EventInterest> = Vec::new();
+    for interest in interests {
+        let mut addresses = interest.addresses.clone();
+        addresses.sort();
+        addresses.dedup();
+        match merged.iter_mut().find(|m| m.addresses == addresses) {
+            Some(m) if m.type_tags.is_empty() => {}
+            Some(m) if interest.type_tags.is_empty() => m.type_tags.clear(),
+            Some(m) => {
+                m.type_tags.extend(interest.type_tags.iter().cloned());
+                m.type_tags.sort();
+                m.type_tags.dedup();
+            }
+            None => {
+                let mut type_tags = interest.type_tags.clone();
+                type_tags.sort();
+                type_tags.dedup();
+                merged.push(EventInterest {
+                    addresses,
+                    type_tags,
+                });
+            }
+        }
+    }
+    merged
+}
+
+/// Remembers the latest keys seen, to drop an event delivered by several
+/// subscriptions
+pub struct RecentKeys<K> {
+    capacity: usize,
+    keys: HashSet<K>,
+    order: VecDeque<K>,
+}
+
+impl<K: Clone + Eq + Hash> RecentKeys<K> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            keys: HashSet::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    /// Insert a key, returning false if it was seen recently
+    pub fn insert(&mut self, key: K) -> bool {
+        if !self.keys.insert(key.clone()) {
+            return false;
+        }
+        self.order.push_back(key);
+        while self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.keys.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use starcoin_rpc_api::types::TypeTagView;
+
+    fn address(address: &str) -> AccountAddress {
+        AccountAddress::from_hex_literal(address).unwrap()
+    }
+
+    fn type_tag(type_tag: &str) -> TypeTag {
+        type_tag.parse::<TypeTagView>().unwrap().0
+    }
+
+    #[test]
+    fn test_matches() {
+        let interest = EventInterest {
+            addresses: vec![address("0x1")],
+            type_tags: vec![type_tag("0x1::Dao::VoteChangedEvent")],
+        };
+        assert!(interest.matches(&address("0x1"), &type_tag("0x1::Dao::VoteChangedEvent")));
+        assert!(!interest.matches(&address("0x2"), &type_tag("0x1::Dao::VoteChangedEvent")));
+        assert!(!interest.matches(&address("0x1"), &type_tag("0x1::Token::MintEvent")));
+        assert!(EventInterest::all().matches(&address("0x2"), &type_tag("0x1::Token::MintEvent")));
+    }
+
+    #[test]
+    fn test_merge_interests() {
+        let dao = EventInterest {
+            addresses: vec![address("0x1")],
+            type_tags: vec![type_tag("0x1::Dao::VoteChangedEvent")],
+        };
+        let token = EventInterest {
+            addresses: vec![address("0x1")],
+            type_tags: vec![
+                type_tag("0x1::Token::MintEvent"),
+                type_tag("0x1::Dao::VoteChangedEvent"),
+            ],
+        };
+        let other = EventInterest {
+            addresses: vec![address("0x2")],
+            type_tags: Vec::new(),
+        };
+
+        let merged = merge_interests(&[dao.clone(), token.clone(), other.clone()]);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].addresses, vec![address("0x1")]);
+        assert_eq!(merged[0].type_tags.len(), 2);
+        assert_eq!(merged[1], other);
+
+        // Any type tag on the same addresses
+        let any_on_core = EventInterest {
+            addresses: vec![address("0x1")],
+            type_tags: Vec::new(),
+        };
+        let merged = merge_interests(&[dao.clone(), any_on_core.clone(), token]);
+        assert_eq!(merged, vec![any_on_core]);
+
+        assert_eq!(
+            merge_interests(&[dao, EventInterest::all()]),
+            vec![EventInterest::all()]
+        );
+        assert!(merge_interests(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_recent_keys() {
+        let mut recent = RecentKeys::new(2);
+        assert!(recent.insert(1));
+        assert!(!recent.insert(1));
+        assert!(recent.insert(2));
+        assert!(recent.insert(3));
+        // 1 was evicted
+        assert!(recent.insert(1));
+        assert!(!recent.insert(3));
+    }
+}
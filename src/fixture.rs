@@ -0,0 +1,381 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Recorded-fixture harness for regression testing the monitor handlers offline.
+//!
+//! A fixture is a JSON dump of the RPC views a handler sees while running:
+//! the blocks, events and txpool transactions pushed by the subscriptions, and
+//! the transactions fetched while processing them. `replay_fixture` feeds them
+//! through a `MonitorDispatcher`, and `RecordingAlertSender` captures the alerts
+//! so they can be compared with the `expected_alerts` of the fixture.
+
+use crate::{
+    alert_sender::AlertSender, chain_reader::ChainReader, monitor_dispatcher::MonitorDispatcher,
+};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use starcoin_crypto::HashValue;
+use starcoin_rpc_api::types::{
+    BlockView, ChainInfoView, SignedUserTransactionView, TransactionEventView, TransactionInfoView,
+    TransactionView,
+};
+use starcoin_types::{account_address::AccountAddress, block::BlockNumber};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayFixture {
+    /// What the fixture covers and where its data comes from
+    pub description: String,
+    /// The `min_transaction_amount` the expected alerts were produced with
+    pub min_transaction_amount: u128,
+    /// Transactions served by `ChainReader::get_transaction`
+    #[serde(default)]
+    pub transactions: Vec<TransactionView>,
+    /// Execution results served by `ChainReader::get_block_txn_infos`
+    #[serde(default)]
+    pub transaction_infos: Vec<TransactionInfoView>,
+    /// Txpool transactions replayed through `dispatch_pending_txn`, after the
+    /// events and before the blocks
+    #[serde(default)]
+    pub pending_transactions: Vec<SignedUserTransactionView>,
+    /// Events replayed through `dispatch_event`, before the blocks
+    #[serde(default)]
+    pub events: Vec<TransactionEventView>,
+    /// Blocks replayed through `dispatch_block`, in order
+    #[serde(default)]
+    pub blocks: Vec<BlockView>,
+    /// Alert messages the replay must produce, in order
+    #[serde(default)]
+    pub expected_alerts: Vec<String>,
+}
+
+impl ReplayFixture {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn chain_reader(&self) -> FixtureChainReader {
+        FixtureChainReader {
+            transactions: self
+                .transactions
+                .iter()
+                .map(|txn| (txn.transaction_hash, txn.clone()))
+                .collect(),
+            transaction_infos: self.transaction_infos.clone(),
+            pending_transactions: self
+                .pending_transactions
+                .iter()
+                .map(|txn| (txn.transaction_hash, txn.clone()))
+                .collect(),
+        }
+    }
+}
+
+/// Serves the transactions recorded in a fixture
+pub struct FixtureChainReader {
+    transactions: HashMap<HashValue, TransactionView>,
+    transaction_infos: Vec<TransactionInfoView>,
+    pending_transactions: HashMap<HashValue, SignedUserTransactionView>,
+}
+
+impl ChainReader for FixtureChainReader {
+    fn get_chain_info(&self) -> Result<ChainInfoView> {
+        bail!("chain info is not recorded in fixtures")
+    }
+
+    fn get_blocks_by_number(
+        &self,
+        _number: Option<BlockNumber>,
+        _count: u64,
+    ) -> Result<Vec<BlockView>> {
+        bail!("block queries are not recorded in fixtures")
+    }
+
+    fn get_transaction(&self, txn_hash: HashValue) -> Result<Option<TransactionView>> {
+        Ok(self.transactions.get(&txn_hash).cloned())
+    }
+
+    fn get_block_txn_infos(&self, block_hash: HashValue) -> Result<Vec<TransactionInfoView>> {
+        Ok(self
+            .transaction_infos
+            .iter()
+            .filter(|info| info.block_hash == block_hash)
+            .cloned()
+            .collect())
+    }
+
+    fn get_pending_transaction(
+        &self,
+        txn_hash: HashValue,
+    ) -> Result<Option<SignedUserTransactionView>> {
+        Ok(self.pending_transactions.get(&txn_hash).cloned())
+    }
+
+    fn get_balance(&self, _address: AccountAddress) -> Result<Option<u128>> {
+        bail!("balances are not recorded in fixtures")
+    }
+}
+
+/// Collects the alerts instead of sending them
+#[derive(Default)]
+pub struct RecordingAlertSender {
+    messages: Mutex<Vec<String>>,
+}
+
+impl RecordingAlertSender {
+    pub fn messages(&self) -> Vec<String> {
+        self.messages.lock().unwrap().clone()
+    }
+}
+
+#[async_trait::async_trait]
+impl AlertSender for RecordingAlertSender {
+    async fn send_message(&self, message: &str) -> Result<()> {
+        self.messages.lock().unwrap().push(message.to_string());
+        Ok(())
+    }
+}
+
+/// Feed the events, the pending transactions and then the blocks of the
+/// fixture through the dispatcher
+pub async fn replay_fixture(
+    fixture: &ReplayFixture,
+    dispatcher: &dyn MonitorDispatcher,
+) -> Result<()> {
+    for event in &fixture.events {
+        dispatcher.dispatch_event(event).await?;
+    }
+    for txn in &fixture.pending_transactions {
+        dispatcher
+            .dispatch_pending_txn(txn.transaction_hash)
+            .await?;
+    }
+    for block in &fixture.blocks {
+        dispatcher.dispatch_block(block).await?;
+    }
+    Ok(())
+}
+
+pub fn fixture_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        alert_sender::Severity,
+        balance_monitor::STC_TOKEN_CODE,
+        bot_access::Role,
+        config::Config,
+        locale::Locale,
+        monitor_handler::composite_monitor_handler::CompositeMonitorHandler,
+        price_provider::{FiatPrice, StaticPriceProvider, TokenPrice, STC_DECIMALS},
+        rich_text::TextFormat,
+        stores::Stores,
+    };
+    use anyhow::anyhow;
+    use starcoin_rpc_client::RpcClient;
+    use std::{collections::BTreeSet, sync::Arc};
+
+    fn fixture_config(min_transaction_amount: u128) -> Config {
+        Config {
+            starcoin_rpc_urls: vec!["ws://127.0.0.1:9870".to_string()],
+            rpc_max_head_lag: 5,
+            rpc_health_check_interval: 10,
+            telegram_bot_token: String::new(),
+            telegram_chat_id: "0".to_string(),
+            telegram_proxy: None,
+            telegram_text_format: TextFormat::MarkdownV2,
+            default_locale: Locale::ZhCn,
+            telegram_max_messages_per_sec: 30,
+            telegram_max_chat_messages_per_min: 20,
+            min_transaction_amount,
+            es_url: "http://127.0.0.1:9200".to_string(),
+            es_user_name: "elastic".to_string(),
+            es_password: "changeme".to_string(),
+            node_health_check_interval: 60,
+            node_head_stale_secs: 300,
+            node_max_head_difference: 10,
+            block_anomaly_window_size: 100,
+            max_block_interval_secs: 120,
+            max_difficulty_change_percent: 100.0,
+            max_miner_share_percent: 50.0,
+            max_failure_rate_percent: 50.0,
+            min_txns_for_failure_rate: 5,
+            max_block_gas_used: None,
+            max_gas_price: None,
+            watched_addresses: Vec::new(),
+            balance_check_interval: 300,
+            balance_change_percent: 10.0,
+            balance_change_amount: None,
+            // Address labels of the fixtures, if any
+            data_dir: fixture_dir().join("data").display().to_string(),
+            token_supply_limits: HashMap::new(),
+            supply_window_secs: 3600,
+            enabled_handlers: vec![
+                "default".to_string(),
+                "governance".to_string(),
+                "velocity".to_string(),
+            ],
+            velocity_max_amount: 10_000_000_000_000,
+            velocity_window_secs: 3600,
+            velocity_window_blocks: None,
+            price_source: "none".to_string(),
+            price_api_url: String::new(),
+            price_file: String::new(),
+            price_cache_secs: 300,
+            min_transaction_usd: None,
+            bot_allowed_chats: Vec::new(),
+            bot_chat_role: Role::Operator,
+            bot_user_roles: HashMap::new(),
+            bot_max_jobs: 4,
+            bot_max_jobs_per_user: 1,
+            bot_command_cooldown_secs: 10,
+            alert_routes: Vec::new(),
+            alert_oncall_users: Vec::new(),
+            alert_mention_severity: Severity::Critical,
+            alert_ack_reset_secs: 3600,
+            alert_digest_window_secs: 60,
+            // Each run alerts the fixtures again, nothing written to `data_dir`
+            alert_dedup_secs: 0,
+        }
+    }
+
+    async fn replay_with_handlers(fixture: &ReplayFixture) -> Result<Vec<String>> {
+        let alert_sender = Arc::new(RecordingAlertSender::default());
+        let prices = HashMap::from([(
+            STC_TOKEN_CODE.to_string(),
+            TokenPrice {
+                price: FiatPrice {
+                    usd: 0.05,
+                    cny: 0.35,
+                },
+                decimals: STC_DECIMALS,
+            },
+        )]);
+        let config = fixture_config(fixture.min_transaction_amount);
+        let stores = Arc::new(Stores::open(&config.data_dir)?);
+        let handler = CompositeMonitorHandler::from_config(
+            Arc::new(config),
+            Arc::new(fixture.chain_reader()),
+            alert_sender.clone(),
+            Arc::new(StaticPriceProvider::new(prices)?),
+            stores,
+        )?;
+        replay_fixture(fixture, &handler).await?;
+        Ok(alert_sender.messages())
+    }
+
+    /// Dump the blocks containing `txn_hashes`, with all their transactions, as
+    /// one fixture
+    fn record_fixture(
+        rpc_client: &RpcClient,
+        txn_hashes: &[HashValue],
+        min_transaction_amount: u128,
+    ) -> Result<ReplayFixture> {
+        let mut block_numbers = BTreeSet::new();
+        for txn_hash in txn_hashes {
+            let txn = rpc_client
+                .get_transaction(*txn_hash)?
+                .ok_or_else(|| anyhow!("transaction {} not found", txn_hash.to_hex_literal()))?;
+            block_numbers.insert(txn.block_number.0);
+        }
+        let mut blocks = Vec::new();
+        for number in &block_numbers {
+            blocks.extend(rpc_client.chain_get_blocks_by_number(Some(*number), 1, None)?);
+        }
+
+        let mut transactions = Vec::new();
+        let mut transaction_infos = Vec::new();
+        for block in &blocks {
+            for hash in block.body.txn_hashes() {
+                if let Some(txn) = rpc_client.get_transaction(hash)? {
+                    transactions.push(txn);
+                }
+            }
+            transaction_infos.extend(rpc_client.get_block_txn_infos(block.header.block_hash)?);
+        }
+
+        Ok(ReplayFixture {
+            description: format!(
+                "Main network blocks {} containing transactions {}",
+                block_numbers
+                    .iter()
+                    .map(|number| number.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                txn_hashes
+                    .iter()
+                    .map(|hash| hash.to_hex_literal())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            min_transaction_amount,
+            transactions,
+            transaction_infos,
+            pending_transactions: Vec::new(),
+            events: Vec::new(),
+            blocks,
+            expected_alerts: Vec::new(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_replay_fixtures() -> Result<()> {
+        let mut paths = std::fs::read_dir(fixture_dir())?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        paths.retain(|path| path.extension().is_some_and(|ext| ext == "json"));
+        paths.sort();
+        assert!(!paths.is_empty(), "no fixture found in {:?}", fixture_dir());
+
+        for path in paths {
+            let fixture = ReplayFixture::load(&path)?;
+            let alerts = replay_with_handlers(&fixture).await?;
+            assert_eq!(
+                alerts,
+                fixture.expected_alerts,
+                "unexpected alerts replaying {}",
+                path.display()
+            );
+        }
+        Ok(())
+    }
+
+    /// Record a main network fixture from the blocks of the transactions in
+    /// `FIXTURE_TXNS`, comma separated, read from `FIXTURE_RPC_URL`, with the
+    /// alerts the current handlers emit as `expected_alerts`. Review the
+    /// alerts before committing the file.
+    #[ignore]
+    #[test]
+    fn test_record_main_fixture() -> Result<()> {
+        let txn_hashes = std::env::var("FIXTURE_TXNS")
+            .unwrap_or_else(|_| {
+                "0x6ed3afdf412404f98fc16d9350b9a19d3258598be5f6b73215a6ab06247b6a53".to_string()
+            })
+            .split(',')
+            .map(|txn_hash| HashValue::from_hex_literal(txn_hash.trim()))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let rpc_url = std::env::var("FIXTURE_RPC_URL")
+            .unwrap_or_else(|_| "ws://main.seed.starcoin.org:9870".to_string());
+        let rpc_client = RpcClient::connect_websocket(&rpc_url)?;
+        let mut fixture = record_fixture(&rpc_client, &txn_hashes, 1_000_000_000)?;
+
+        let rt = tokio::runtime::Runtime::new()?;
+        fixture.expected_alerts = rt.block_on(replay_with_handlers(&fixture))?;
+        fixture
+            .save(fixture_dir().join(format!("main_{}.json", txn_hashes[0].to_hex_literal())))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,308 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    address_label::AddressLabels,
+    locale::Locale,
+    transfer_index::{EsTransferIndex, TransferDocument},
+};
+use anyhow::Result;
+use std::collections::{HashSet, VecDeque};
+
+/// Deepest trace the `/trace` command accepts
+pub const MAX_TRACE_DEPTH: usize = 5;
+
+/// Configuration of a fund-flow trace
+#[derive(Debug, Clone)]
+pub struct TraceConfig {
+    pub depth: usize,
+    /// Largest outgoing transfers followed from each address
+    pub max_branches: usize,
+    /// Only the transfers made within this time after the incoming transfer
+    /// are followed
+    pub window_secs: i64,
+    /// Transfers listed at most, to keep the reply in one message
+    pub max_nodes: usize,
+}
+
+impl Default for TraceConfig {
+    fn default() -> Self {
+        Self {
+            depth: 2,
+            max_branches: 3,
+            window_secs: 7 * 24 * 3600,
+            max_nodes: 30,
+        }
+    }
+}
+
+/// Where the transfers are looked up, the ES transfer index in production
+#[async_trait::async_trait]
+pub trait TransferSource: Send + Sync {
+    async fn find_transfer(&self, txn_hash: &str) -> Result<Option<TransferDocument>>;
+
+    async fn find_outgoing(
+        &self,
+        sender: &str,
+        start_time_mill_sec: i64,
+        end_time_mill_sec: i64,
+        limit: usize,
+    ) -> Result<Vec<TransferDocument>>;
+}
+
+#[async_trait::async_trait]
+impl TransferSource for EsTransferIndex {
+    async fn find_transfer(&self, txn_hash: &str) -> Result<Option<TransferDocument>> {
+        EsTransferIndex::find_transfer(self, txn_hash).await
+    }
+
+    async fn find_outgoing(
+        &self,
+        sender: &str,
+        start_time_mill_sec: i64,
+        end_time_mill_sec: i64,
+        limit: usize,
+    ) -> Result<Vec<TransferDocument>> {
+        EsTransferIndex::find_outgoing(self, sender, start_time_mill_sec, end_time_mill_sec, limit)
+            .await
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TraceNode {
+    pub transfer: TransferDocument,
+    /// Indexes of the transfers made with the received funds
+    pub children: Vec<usize>,
+}
+
+/// The transfers found from a root transfer, the root being the first node
+#[derive(Debug, Clone)]
+pub struct TraceTree {
+    pub nodes: Vec<TraceNode>,
+    /// Some transfers were left out after reaching `max_nodes`
+    pub truncated: bool,
+}
+
+/// Follow the funds of the transfer made by `txn_hash`, hop by hop, returning
+/// `None` if the transfer is not indexed
+pub async fn trace_funds(
+    source: &dyn TransferSource,
+    txn_hash: &str,
+    config: &TraceConfig,
+) -> Result<Option<TraceTree>> {
+    let root = match source.find_transfer(txn_hash).await? {
+        Some(root) => root,
+        None => return Ok(None),
+    };
+    let mut seen = HashSet::from([root.txn_hash.clone()]);
+    let mut nodes = vec![TraceNode {
+        transfer: root,
+        children: Vec::new(),
+    }];
+
+    // (node index, depth) of the transfers whose receiver is to be followed
+    let mut queue = VecDeque::from([(0, 0)]);
+    let mut truncated = false;
+    while let Some((index, depth)) = queue.pop_front() {
+        if depth >= config.depth {
+            continue;
+        }
+        if nodes.len() >= config.max_nodes {
+            truncated = true;
+            break;
+        }
+        let incoming = nodes[index].transfer.clone();
+        let outgoing = source
+            .find_outgoing(
+                &incoming.receiver,
+                incoming.timestamp,
+                incoming.timestamp + config.window_secs * 1000,
+                config.max_branches,
+            )
+            .await?;
+        for transfer in outgoing {
+            // Cycles and the same transfer reached from two branches
+            if !seen.insert(transfer.txn_hash.clone()) {
+                continue;
+            }
+            if nodes.len() >= config.max_nodes {
+                truncated = true;
+                break;
+            }
+            nodes.push(TraceNode {
+                transfer,
+                children: Vec::new(),
+            });
+            let child = nodes.len() - 1;
+            nodes[index].children.push(child);
+            queue.push_back((child, depth + 1));
+        }
+    }
+    Ok(Some(TraceTree { nodes, truncated }))
+}
+
+fn format_transfer(transfer: &TransferDocument, labels: &AddressLabels, locale: Locale) -> String {
+    let token = transfer
+        .type_tag
+        .rsplit("::")
+        .next()
+        .unwrap_or(&transfer.type_tag);
+    let txn_hash = transfer.txn_hash.get(..10).unwrap_or(&transfer.txn_hash);
+    format!(
+        "{} → {}: {:.9} {} ({}…)",
+        labels.describe(&transfer.sender, locale),
+        labels.describe(&transfer.receiver, locale),
+        transfer.amount() as f64 / 1e9,
+        token,
+        txn_hash
+    )
+}
+
+fn render_children(
+    tree: &TraceTree,
+    index: usize,
+    prefix: &str,
+    labels: &AddressLabels,
+    locale: Locale,
+    lines: &mut Vec<String>,
+) {
+    let children = &tree.nodes[index].children;
+    for (i, child) in children.iter().enumerate() {
+        let last = i + 1 == children.len();
+        lines.push(format!(
+            "{}{}{}",
+            prefix,
+            if last { "└─ " } else { "├─ " },
+            format_transfer(&tree.nodes[*child].transfer, labels, locale)
+        ));
+        let child_prefix = format!("{}{}", prefix, if last { "   " } else { "│  " });
+        render_children(tree, *child, &child_prefix, labels, locale, lines);
+    }
+}
+
+/// Render the trace as a compact tree, one transfer per line
+pub fn render_trace(tree: &TraceTree, labels: &AddressLabels, locale: Locale) -> String {
+    let m = locale.messages();
+    let mut lines = vec![format_transfer(&tree.nodes[0].transfer, labels, locale)];
+    render_children(tree, 0, "", labels, locale, &mut lines);
+    if tree.nodes.len() == 1 {
+        lines.push(m.trace_no_outgoing.to_string());
+    }
+    if tree.truncated {
+        lines.push((m.trace_truncated)(tree.nodes.len()));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestSource {
+        transfers: Vec<TransferDocument>,
+    }
+
+    #[async_trait::async_trait]
+    impl TransferSource for TestSource {
+        async fn find_transfer(&self, txn_hash: &str) -> Result<Option<TransferDocument>> {
+            Ok(self
+                .transfers
+                .iter()
+                .find(|t| t.txn_hash == txn_hash)
+                .cloned())
+        }
+
+        async fn find_outgoing(
+            &self,
+            sender: &str,
+            start_time_mill_sec: i64,
+            end_time_mill_sec: i64,
+            limit: usize,
+        ) -> Result<Vec<TransferDocument>> {
+            let mut outgoing: Vec<_> = self
+                .transfers
+                .iter()
+                .filter(|t| {
+                    t.sender == sender
+                        && t.timestamp >= start_time_mill_sec
+                        && t.timestamp <= end_time_mill_sec
+                })
+                .cloned()
+                .collect();
+            outgoing.sort_by_key(|t| std::cmp::Reverse(t.amount()));
+            outgoing.truncate(limit);
+            Ok(outgoing)
+        }
+    }
+
+    fn transfer(
+        txn_hash: &str,
+        sender: &str,
+        receiver: &str,
+        amount: u128,
+        timestamp: i64,
+    ) -> TransferDocument {
+        TransferDocument {
+            amount: String::new(),
+            amount_value: Some(amount),
+            identifier: String::new(),
+            receiver: receiver.to_string(),
+            sender: sender.to_string(),
+            timestamp,
+            txn_hash: txn_hash.to_string(),
+            type_tag: "0x00000000000000000000000000000001::STC::STC".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_trace_funds() -> Result<()> {
+        let source = TestSource {
+            transfers: vec![
+                transfer("0x01", "0xa", "0xb", 3_000_000_000_000, 1000),
+                transfer("0x02", "0xb", "0xc", 2_000_000_000_000, 2000),
+                transfer("0x03", "0xb", "0xd", 1_000_000_000_000, 3000),
+                // Before the funds arrived
+                transfer("0x04", "0xb", "0xe", 5_000_000_000_000, 500),
+                transfer("0x05", "0xc", "0xf", 2_000_000_000_000, 4000),
+                // Back to an address already traced
+                transfer("0x06", "0xf", "0xb", 2_000_000_000_000, 5000),
+            ],
+        };
+        let labels = AddressLabels::default();
+
+        assert!(trace_funds(&source, "0x99", &TraceConfig::default())
+            .await?
+            .is_none());
+
+        let tree = trace_funds(&source, "0x01", &TraceConfig::default())
+            .await?
+            .unwrap();
+        assert_eq!(
+            render_trace(&tree, &labels, Locale::ZhCn),
+            "0xa → 0xb: 3000.000000000 STC (0x01…)\n\
+             ├─ 0xb → 0xc: 2000.000000000 STC (0x02…)\n\
+             │  └─ 0xc → 0xf: 2000.000000000 STC (0x05…)\n\
+             └─ 0xb → 0xd: 1000.000000000 STC (0x03…)"
+        );
+
+        // The loop back to 0xb is followed once, without revisiting 0xb's transfers
+        let config = TraceConfig {
+            depth: MAX_TRACE_DEPTH,
+            ..TraceConfig::default()
+        };
+        let tree = trace_funds(&source, "0x01", &config).await?.unwrap();
+        assert_eq!(tree.nodes.len(), 5);
+        assert!(!tree.truncated);
+
+        let config = TraceConfig {
+            max_nodes: 2,
+            ..TraceConfig::default()
+        };
+        let tree = trace_funds(&source, "0x01", &config).await?.unwrap();
+        assert!(tree.truncated);
+        assert!(render_trace(&tree, &labels, Locale::ZhCn).ends_with("(仅列出前 2 笔转账)"));
+        assert!(render_trace(&tree, &labels, Locale::EnUs)
+            .ends_with("(only the first 2 transfers listed)"));
+        Ok(())
+    }
+}
@@ -0,0 +1,248 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    alert_throttle::AlertThrottle,
+    types::{Transaction, TransactionStatus},
+};
+use starcoin_types::block::BlockNumber;
+use tracing::info;
+
+/// Configuration for failed transaction and gas monitoring
+#[derive(Debug, Clone)]
+pub struct GasMonitorConfig {
+    /// Percentage of failed transactions in a block above which to alert
+    pub max_failure_rate_percent: f64,
+    /// Minimal number of transactions in a block before the failure rate is checked
+    pub min_txns_for_failure_rate: usize,
+    /// Gas used by all the transactions of a block above which to alert
+    pub max_block_gas_used: Option<u64>,
+    /// Gas price at `gas_price_percentile` above which to alert
+    pub max_gas_price: Option<u64>,
+    /// Percentile of the block gas prices compared with `max_gas_price`
+    pub gas_price_percentile: usize,
+    /// Minimal seconds between two alerts of the same kind
+    pub max_notify_time_interval: u64,
+}
+
+impl Default for GasMonitorConfig {
+    fn default() -> Self {
+        Self {
+            max_failure_rate_percent: 50.0,
+            min_txns_for_failure_rate: 5,
+            max_block_gas_used: None,
+            max_gas_price: None,
+            gas_price_percentile: 90,
+            max_notify_time_interval: 600,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GasAlert {
+    /// Too many transactions of the block failed
+    HighFailureRate {
+        block_number: BlockNumber,
+        failed: usize,
+        total: usize,
+    },
+    /// The transactions of the block used more gas than allowed
+    HighGasUsed {
+        block_number: BlockNumber,
+        gas_used: u64,
+        max_gas_used: u64,
+    },
+    /// The gas price distribution of the block is above the bound
+    HighGasPrice {
+        block_number: BlockNumber,
+        percentile: usize,
+        gas_price: u64,
+        median_gas_price: u64,
+        max_gas_price: u64,
+    },
+}
+
+impl GasAlert {
+    /// Identifies the alert for notification throttling
+    pub fn key(&self) -> &'static str {
+        match self {
+            GasAlert::HighFailureRate { .. } => "high_failure_rate",
+            GasAlert::HighGasUsed { .. } => "high_gas_used",
+            GasAlert::HighGasPrice { .. } => "high_gas_price",
+        }
+    }
+}
+
+/// Nearest-rank percentile of sorted, non-empty values
+fn percentile(sorted: &[u64], percentile: usize) -> u64 {
+    let rank = (percentile * sorted.len())
+        .div_ceil(100)
+        .clamp(1, sorted.len());
+    sorted[rank - 1]
+}
+
+/// Checks the transactions of each block against the configured gas bounds
+#[derive(Debug, Default)]
+pub struct GasMonitor {
+    throttle: AlertThrottle,
+}
+
+impl GasMonitor {
+    /// Check the executed transactions of a block, returning the alerts to notify
+    pub fn check_block(
+        &mut self,
+        block_number: BlockNumber,
+        txns: &[Transaction],
+        now: u64,
+        config: &GasMonitorConfig,
+    ) -> Vec<GasAlert> {
+        let mut alerts = Vec::new();
+        if txns.is_empty() {
+            return alerts;
+        }
+
+        let failed = txns
+            .iter()
+            .filter(|txn| matches!(txn.status, TransactionStatus::Failed))
+            .count();
+        if txns.len() >= config.min_txns_for_failure_rate
+            && failed as f64 / txns.len() as f64 * 100.0 > config.max_failure_rate_percent
+        {
+            alerts.push(GasAlert::HighFailureRate {
+                block_number,
+                failed,
+                total: txns.len(),
+            });
+        }
+
+        if let Some(max_gas_used) = config.max_block_gas_used {
+            let gas_used = txns.iter().map(|txn| txn.gas_used).sum::<u64>();
+            if gas_used > max_gas_used {
+                alerts.push(GasAlert::HighGasUsed {
+                    block_number,
+                    gas_used,
+                    max_gas_used,
+                });
+            }
+        }
+
+        if let Some(max_gas_price) = config.max_gas_price {
+            let mut gas_prices: Vec<u64> = txns.iter().map(|txn| txn.gas_price).collect();
+            gas_prices.sort_unstable();
+            let gas_price = percentile(&gas_prices, config.gas_price_percentile);
+            if gas_price > max_gas_price {
+                alerts.push(GasAlert::HighGasPrice {
+                    block_number,
+                    percentile: config.gas_price_percentile,
+                    gas_price,
+                    median_gas_price: percentile(&gas_prices, 50),
+                    max_gas_price,
+                });
+            }
+        }
+
+        alerts
+            .into_iter()
+            .filter(|alert| {
+                if !self
+                    .throttle
+                    .should_notify(alert.key(), now, config.max_notify_time_interval)
+                {
+                    return false;
+                }
+                info!("Gas exception detected: {:?}", alert);
+                true
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn txn(status: TransactionStatus, gas_used: u64, gas_price: u64) -> Transaction {
+        Transaction {
+            hash: String::new(),
+            block_number: 100,
+            timestamp: Utc::now(),
+            from_address: String::new(),
+            to_address: String::new(),
+            amount: 0,
+            token: String::new(),
+            gas_used,
+            gas_price,
+            status,
+        }
+    }
+
+    #[test]
+    fn test_percentile() {
+        let values: Vec<u64> = (1..=10).collect();
+        assert_eq!(percentile(&values, 50), 5);
+        assert_eq!(percentile(&values, 90), 9);
+        assert_eq!(percentile(&values, 100), 10);
+        assert_eq!(percentile(&[7], 90), 7);
+    }
+
+    #[test]
+    fn test_high_failure_rate() {
+        let config = GasMonitorConfig::default();
+        let mut monitor = GasMonitor::default();
+
+        // Not enough transactions to compute a failure rate
+        let txns = vec![
+            txn(TransactionStatus::Failed, 100, 1),
+            txn(TransactionStatus::Failed, 100, 1),
+        ];
+        assert!(monitor.check_block(100, &txns, 1000, &config).is_empty());
+
+        let mut txns = vec![txn(TransactionStatus::Failed, 100, 1); 3];
+        txns.extend(vec![txn(TransactionStatus::Success, 100, 1); 2]);
+        assert_eq!(
+            monitor.check_block(101, &txns, 1000, &config),
+            vec![GasAlert::HighFailureRate {
+                block_number: 101,
+                failed: 3,
+                total: 5,
+            }]
+        );
+
+        // Notified once per max_notify_time_interval
+        assert!(monitor.check_block(102, &txns, 1100, &config).is_empty());
+    }
+
+    #[test]
+    fn test_gas_bounds() {
+        let config = GasMonitorConfig {
+            max_block_gas_used: Some(1000),
+            max_gas_price: Some(10),
+            ..GasMonitorConfig::default()
+        };
+        let mut monitor = GasMonitor::default();
+
+        let txns = vec![txn(TransactionStatus::Success, 400, 1); 2];
+        assert!(monitor.check_block(100, &txns, 1000, &config).is_empty());
+
+        let mut txns = vec![txn(TransactionStatus::Success, 400, 1); 8];
+        txns.extend(vec![txn(TransactionStatus::Success, 400, 50); 2]);
+        assert_eq!(
+            monitor.check_block(101, &txns, 1000, &config),
+            vec![
+                GasAlert::HighGasUsed {
+                    block_number: 101,
+                    gas_used: 4000,
+                    max_gas_used: 1000,
+                },
+                GasAlert::HighGasPrice {
+                    block_number: 101,
+                    percentile: 90,
+                    gas_price: 50,
+                    median_gas_price: 1,
+                    max_gas_price: 10,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,155 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+/// Address of the Starcoin framework modules
+pub const CORE_ADDRESS: &str = "0x00000000000000000000000000000001";
+
+/// High-impact on-chain actions: governance, config changes and code upgrades
+#[derive(Debug, Clone, PartialEq)]
+pub enum GovernanceAction {
+    /// `0x1::Dao::ProposalCreatedEvent`
+    ProposalCreated,
+    /// `0x1::Dao::VoteChangedEvent`
+    VoteChanged,
+    /// A passed proposal was queued for execution
+    ProposalQueued { function: String },
+    /// A proposal action was executed
+    ProposalExecuted { function: String },
+    /// `0x1::Config::ConfigChangeEvent<config>`
+    ConfigChanged { config: String },
+    /// `0x1::PackageTxnManager::UpgradeEvent`
+    ModuleUpgraded,
+    /// A transaction with a package payload
+    PackagePublished {
+        package_address: String,
+        modules: usize,
+    },
+}
+
+impl GovernanceAction {
+    /// Identifies the kind of action in the alert keys
+    pub fn key(&self) -> &'static str {
+        match self {
+            GovernanceAction::ProposalCreated => "proposal_created",
+            GovernanceAction::VoteChanged => "vote_changed",
+            GovernanceAction::ProposalQueued { .. } => "proposal_queued",
+            GovernanceAction::ProposalExecuted { .. } => "proposal_executed",
+            GovernanceAction::ConfigChanged { .. } => "config_changed",
+            GovernanceAction::ModuleUpgraded => "module_upgraded",
+            GovernanceAction::PackagePublished { .. } => "package_published",
+        }
+    }
+}
+
+/// Classify an event emitted by the framework, from the module, name and type
+/// parameters of its struct tag
+pub fn classify_framework_event(
+    module: &str,
+    name: &str,
+    type_params: &[String],
+) -> Option<GovernanceAction> {
+    match (module, name) {
+        ("Dao", "ProposalCreatedEvent") => Some(GovernanceAction::ProposalCreated),
+        ("Dao", "VoteChangedEvent") => Some(GovernanceAction::VoteChanged),
+        ("Config", "ConfigChangeEvent") => Some(GovernanceAction::ConfigChanged {
+            config: type_params.first().cloned().unwrap_or_default(),
+        }),
+        ("PackageTxnManager", "UpgradeEvent") => Some(GovernanceAction::ModuleUpgraded),
+        _ => None,
+    }
+}
+
+/// Classify a script function call. Proposal execution emits no Dao event, it
+/// is detected from the framework functions queuing and executing proposals.
+pub fn classify_script_function(
+    module_name: &str,
+    function_name: &str,
+) -> Option<GovernanceAction> {
+    let module = module_name.strip_prefix(CORE_ADDRESS)?.strip_prefix("::")?;
+    let function = format!("{}::{}", module_name, function_name);
+    if function_name == "queue_proposal_action" {
+        Some(GovernanceAction::ProposalQueued { function })
+    } else if function_name.starts_with("execute")
+        && (module.ends_with("Proposal") || module == "OnChainConfigScripts")
+    {
+        Some(GovernanceAction::ProposalExecuted { function })
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_framework_event() {
+        assert_eq!(
+            classify_framework_event("Dao", "ProposalCreatedEvent", &[]),
+            Some(GovernanceAction::ProposalCreated)
+        );
+        assert_eq!(
+            classify_framework_event("Dao", "VoteChangedEvent", &[]),
+            Some(GovernanceAction::VoteChanged)
+        );
+        assert_eq!(
+            classify_framework_event(
+                "Config",
+                "ConfigChangeEvent",
+                &["0x00000000000000000000000000000001::VMConfig::VMConfig".to_string()]
+            ),
+            Some(GovernanceAction::ConfigChanged {
+                config: "0x00000000000000000000000000000001::VMConfig::VMConfig".to_string()
+            })
+        );
+        assert_eq!(
+            classify_framework_event("PackageTxnManager", "UpgradeEvent", &[]),
+            Some(GovernanceAction::ModuleUpgraded)
+        );
+        assert_eq!(
+            classify_framework_event("Account", "DepositEvent", &[]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_classify_script_function() {
+        assert_eq!(
+            classify_script_function(
+                "0x00000000000000000000000000000001::DaoVoteScripts",
+                "queue_proposal_action"
+            ),
+            Some(GovernanceAction::ProposalQueued {
+                function:
+                    "0x00000000000000000000000000000001::DaoVoteScripts::queue_proposal_action"
+                        .to_string()
+            })
+        );
+        assert!(matches!(
+            classify_script_function(
+                "0x00000000000000000000000000000001::OnChainConfigScripts",
+                "execute_on_chain_config_proposal"
+            ),
+            Some(GovernanceAction::ProposalExecuted { .. })
+        ));
+        assert!(matches!(
+            classify_script_function(
+                "0x00000000000000000000000000000001::ModifyDaoConfigProposal",
+                "execute"
+            ),
+            Some(GovernanceAction::ProposalExecuted { .. })
+        ));
+        assert_eq!(
+            classify_script_function(
+                "0x00000000000000000000000000000001::TransferScripts",
+                "peer_to_peer_v2"
+            ),
+            None
+        );
+        // Only framework modules are governance
+        assert_eq!(
+            classify_script_function("0x4a50777e0e4f67625400148b04afd572::MyProposal", "execute"),
+            None
+        );
+    }
+}
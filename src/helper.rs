@@ -1,18 +1,22 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::{
+    chain_reader::ChainReader,
+    types::{Transaction, TransactionStatus},
+};
 use anyhow::{anyhow, Result};
-use starcoin_rpc_api::types::TransactionPayloadView;
-use starcoin_rpc_api::{
-    chain::GetTransactionOption,
-    types::{BlockTransactionsView, BlockView, SignedUserTransactionView},
+use chrono::{TimeZone, Utc};
+use starcoin_rpc_api::types::{
+    BlockHeaderView, TransactionInfoView, TransactionPayloadView, TransactionStatusView,
 };
-use starcoin_rpc_client::RpcClient;
+use starcoin_rpc_api::types::{BlockTransactionsView, BlockView, SignedUserTransactionView};
+use starcoin_types::{account_address::AccountAddress, transaction::TransactionPayload};
 use std::sync::Arc;
 use tracing::info;
 
 pub async fn extract_full_txn_from_block_view(
-    rpc_client: Arc<RpcClient>,
+    chain_reader: Arc<dyn ChainReader>,
     block_views: Vec<BlockView>,
 ) -> Result<Vec<SignedUserTransactionView>> {
     if block_views.is_empty() {
@@ -48,12 +52,9 @@ pub async fn extract_full_txn_from_block_view(
 
     // Now fetch detailed transaction info for hash types
     for txn_hash in txn_hashes_to_fetch {
-        let rpc_client_clone = rpc_client.clone();
+        let chain_reader_clone = chain_reader.clone();
         let txn_result = tokio::task::spawn_blocking(move || {
-            rpc_client_clone
-                .chain_get_transaction(txn_hash, Some(GetTransactionOption { decode: true }))
-                .ok()
-                .flatten()
+            chain_reader_clone.get_transaction(txn_hash).ok().flatten()
         })
         .await?;
 
@@ -68,36 +69,151 @@ pub async fn extract_full_txn_from_block_view(
     Ok(all_transactions)
 }
 
-pub fn parse_txn_p2p_amount(txn_view: SignedUserTransactionView) -> Result<Option<u128>> {
+/// A `TransferScripts` peer to peer transfer
+#[derive(Debug, Clone, PartialEq)]
+pub struct P2pTransfer {
+    pub receiver: String,
+    pub amount: u128,
+    pub token: String,
+}
+
+/// Index of the amount argument of the `TransferScripts` peer to peer functions
+fn p2p_amount_index(module_name: &str, function_name: &str) -> Option<usize> {
+    if module_name != "0x00000000000000000000000000000001::TransferScripts" {
+        None
+    } else if function_name == "peer_to_peer_v2" {
+        Some(1)
+    } else if function_name == "peer_to_peer" {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+/// Format an address as a full length hex literal
+pub fn format_address(address: AccountAddress) -> String {
+    format!("0x{}", hex::encode(address.into_bytes()))
+}
+
+pub fn parse_txn_p2p_transfer(txn_view: &SignedUserTransactionView) -> Result<Option<P2pTransfer>> {
     let txn_payload_view = txn_view
         .raw_txn
         .decoded_payload
+        .as_ref()
         .ok_or(anyhow!("should decode txn"))?;
-    let amount = match txn_payload_view {
+    let transfer = match txn_payload_view {
         TransactionPayloadView::ScriptFunction(function_view) => {
             let module_name = function_view.module.0.to_string();
             let function_name = function_view.function.as_str();
 
             info!(
-                "parse_txn_p2p_amount | script function: {}, {}, args: {:?}",
-                function_view.module.0.to_string(),
-                function_view.function.as_str(),
-                function_view.args
+                "parse_txn_p2p_transfer | script function: {}, {}, args: {:?}",
+                module_name, function_name, function_view.args
             );
-            if module_name == "0x00000000000000000000000000000001::TransferScripts"
-                && function_name == "peer_to_peer_v2"
-            {
-                function_view.args[1].0.as_u64().map(|n| n as u128)
-            } else if module_name == "0x00000000000000000000000000000001::TransferScripts"
-                && function_name == "peer_to_peer"
-            {
-                function_view.args[2].0.as_u64().map(|n| n as u128)
-            } else {
-                None
-            }
+            p2p_amount_index(&module_name, function_name).and_then(|index| {
+                let amount = function_view.args.get(index)?.0.as_u64()? as u128;
+                Some(P2pTransfer {
+                    receiver: function_view
+                        .args
+                        .first()
+                        .and_then(|arg| arg.0.as_str())
+                        .map(|receiver| receiver.to_string())
+                        .unwrap_or_default(),
+                    amount,
+                    token: function_view
+                        .ty_args
+                        .first()
+                        .map(|ty_arg| ty_arg.0.to_string())
+                        .unwrap_or_default(),
+                })
+            })
         }
         _ => None,
     };
 
-    Ok(amount)
+    Ok(transfer)
+}
+
+/// Decode a transfer from the BCS payload of a transaction, for views without
+/// `decoded_payload` such as the ones of pending transactions
+pub fn decode_p2p_transfer_payload(payload: &[u8]) -> Result<Option<P2pTransfer>> {
+    let script_function = match bcs_ext::from_bytes::<TransactionPayload>(payload)? {
+        TransactionPayload::ScriptFunction(script_function) => script_function,
+        _ => return Ok(None),
+    };
+    let module_name = script_function.module().to_string();
+    let function_name = script_function.function().as_str();
+    let index = match p2p_amount_index(&module_name, function_name) {
+        Some(index) => index,
+        None => return Ok(None),
+    };
+
+    let args = script_function.args();
+    let amount = match args.get(index) {
+        Some(arg) => bcs_ext::from_bytes::<u128>(arg)?,
+        None => return Ok(None),
+    };
+    let receiver = args
+        .first()
+        .map(|arg| bcs_ext::from_bytes::<AccountAddress>(arg))
+        .transpose()?;
+    Ok(Some(P2pTransfer {
+        receiver: receiver.map(format_address).unwrap_or_default(),
+        amount,
+        token: script_function
+            .ty_args()
+            .first()
+            .map(|ty_arg| ty_arg.to_string())
+            .unwrap_or_default(),
+    }))
+}
+
+/// Parse the transfer of a pending transaction, decoding its raw payload when
+/// the node did not
+pub fn parse_pending_txn_p2p_transfer(
+    txn_view: &SignedUserTransactionView,
+) -> Result<Option<P2pTransfer>> {
+    if txn_view.raw_txn.decoded_payload.is_some() {
+        parse_txn_p2p_transfer(txn_view)
+    } else {
+        decode_p2p_transfer_payload(&txn_view.raw_txn.payload.0)
+    }
+}
+
+pub fn parse_txn_p2p_amount(txn_view: SignedUserTransactionView) -> Result<Option<u128>> {
+    Ok(parse_txn_p2p_transfer(&txn_view)?.map(|transfer| transfer.amount))
+}
+
+/// Build a `Transaction` from a block transaction and its execution result
+pub fn build_transaction(
+    header: &BlockHeaderView,
+    txn_view: &SignedUserTransactionView,
+    txn_info: &TransactionInfoView,
+) -> Transaction {
+    let transfer = parse_txn_p2p_transfer(txn_view).ok().flatten();
+    Transaction {
+        hash: txn_view.transaction_hash.to_hex_literal(),
+        block_number: header.number.0,
+        timestamp: Utc
+            .timestamp_millis_opt(header.timestamp.0 as i64)
+            .single()
+            .unwrap_or_default(),
+        from_address: txn_view.raw_txn.sender.to_string(),
+        to_address: transfer
+            .as_ref()
+            .map(|t| t.receiver.clone())
+            .unwrap_or_default(),
+        amount: transfer
+            .as_ref()
+            .map(|t| u64::try_from(t.amount).unwrap_or(u64::MAX))
+            .unwrap_or_default(),
+        token: transfer.map(|t| t.token).unwrap_or_default(),
+        gas_used: txn_info.gas_used.0,
+        gas_price: txn_view.raw_txn.gas_unit_price.0,
+        status: if matches!(txn_info.status, TransactionStatusView::Executed) {
+            TransactionStatus::Success
+        } else {
+            TransactionStatus::Failed
+        },
+    }
 }
@@ -1,10 +1,46 @@
+pub mod address_label;
+pub mod alert_dedup;
+pub mod alert_digest;
+pub mod alert_mute;
+pub mod alert_routing;
+pub mod alert_sender;
+pub mod alert_throttle;
+pub mod balance_check;
+pub mod balance_monitor;
+pub mod block_anomaly_check;
+pub mod bot_access;
+pub mod bot_jobs;
+pub mod chain_reader;
 pub mod config;
 pub mod daily_notification;
+pub mod event_filter;
+#[cfg(test)]
+mod fixture;
+pub mod fund_trace;
+pub mod gas_check;
+pub mod governance_check;
 pub mod helper;
+pub mod locale;
+pub mod messages;
 pub mod monitor;
 pub mod monitor_dispatcher;
+pub mod monitor_handler;
+pub mod node_health_check;
+pub mod node_health_monitor;
+pub mod pending_txn_tracker;
+pub mod price_provider;
 pub mod pubsub_client;
+pub mod rich_text;
+pub mod rpc_pool;
+pub mod send_queue;
 pub mod stcscan_monitor;
 pub mod stcscan_monitor_index;
+pub mod storage;
+pub mod stores;
+pub mod subscription;
+pub mod supply_check;
 pub mod telegram;
+pub mod templates;
+pub mod transfer_index;
 pub mod types;
+pub mod velocity_check;
@@ -0,0 +1,156 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    messages::{self, Messages},
+    rich_text::RichText,
+};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, str::FromStr};
+
+/// Language of the alerts and bot replies, chosen per chat with `/language`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Locale {
+    #[default]
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+    #[serde(rename = "en-US")]
+    EnUs,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::ZhCn, Locale::EnUs];
+    pub const NAMES: &'static str = "zh-CN, en-US";
+
+    /// The wording of this locale among the wordings of all of them
+    pub fn pick<T>(self, zh_cn: T, en_us: T) -> T {
+        match self {
+            Locale::ZhCn => zh_cn,
+            Locale::EnUs => en_us,
+        }
+    }
+
+    /// The phrases of the alerts and bot replies in this locale
+    pub fn messages(self) -> &'static Messages {
+        self.pick(&messages::ZH_CN, &messages::EN_US)
+    }
+}
+
+impl std::fmt::Display for Locale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.pick("zh-CN", "en-US"))
+    }
+}
+
+impl FromStr for Locale {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s.trim().to_lowercase().replace('_', "-").as_str() {
+            "zh-cn" | "zh" => Locale::ZhCn,
+            "en-us" | "en" => Locale::EnUs,
+            _ => bail!("Unknown locale: {}, expected one of {}", s, Locale::NAMES),
+        })
+    }
+}
+
+/// A message worded in every locale, the chats it is sent to each getting
+/// their own
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalizedText {
+    texts: Vec<(Locale, RichText)>,
+}
+
+impl LocalizedText {
+    pub fn new(text: impl Fn(Locale) -> RichText) -> Self {
+        Self {
+            texts: Locale::ALL
+                .iter()
+                .map(|locale| (*locale, text(*locale)))
+                .collect(),
+        }
+    }
+
+    pub fn get(&self, locale: Locale) -> &RichText {
+        self.texts
+            .iter()
+            .find(|(l, _)| *l == locale)
+            .map(|(_, text)| text)
+            .unwrap_or(&self.texts[0].1)
+    }
+}
+
+/// The same text in every locale
+impl From<RichText> for LocalizedText {
+    fn from(text: RichText) -> Self {
+        Self {
+            texts: vec![(Locale::default(), text)],
+        }
+    }
+}
+
+impl From<String> for LocalizedText {
+    fn from(text: String) -> Self {
+        RichText::from(text).into()
+    }
+}
+
+impl From<&str> for LocalizedText {
+    fn from(text: &str) -> Self {
+        RichText::from(text).into()
+    }
+}
+
+/// The plain text in the default locale
+impl std::fmt::Display for LocalizedText {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.get(Locale::default()).fmt(f)
+    }
+}
+
+/// The locales set with `/language`, kept in `DATA_DIR`, the other chats
+/// using `DEFAULT_LOCALE`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChatLocales {
+    chats: HashMap<i64, Locale>,
+}
+
+impl ChatLocales {
+    pub const FILE_NAME: &'static str = "chat_locales.json";
+
+    pub fn get(&self, chat_id: i64) -> Option<Locale> {
+        self.chats.get(&chat_id).copied()
+    }
+
+    pub fn set(&mut self, chat_id: i64, locale: Locale) {
+        self.chats.insert(chat_id, locale);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_localized_text() -> Result<()> {
+        let text = LocalizedText::new(|locale| {
+            RichText::new()
+                .text(locale.pick("区块: ", "Block: "))
+                .bold("1000")
+        });
+        assert_eq!(text.get(Locale::EnUs).to_string(), "Block: 1000");
+        assert_eq!(text.to_string(), "区块: 1000");
+        // Untranslated text reads the same in every locale
+        assert_eq!(
+            LocalizedText::from("lagging").get(Locale::EnUs).to_string(),
+            "lagging"
+        );
+
+        assert_eq!("en_US".parse::<Locale>()?, Locale::EnUs);
+        assert_eq!("zh".parse::<Locale>()?, Locale::ZhCn);
+        assert!("fr-FR".parse::<Locale>().is_err());
+        assert_eq!(serde_json::to_string(&Locale::EnUs)?, "\"en-US\"");
+        Ok(())
+    }
+}
@@ -1,26 +1,63 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+mod address_label;
+mod alert_dedup;
+mod alert_digest;
+mod alert_mute;
+mod alert_routing;
+mod alert_sender;
+mod alert_throttle;
+mod balance_check;
+mod balance_monitor;
+mod block_anomaly_check;
+mod bot_access;
+mod bot_jobs;
+mod chain_reader;
 mod config;
 mod daily_notification;
+mod event_filter;
+mod fund_trace;
+mod gas_check;
+mod governance_check;
 mod helper;
+mod locale;
+mod messages;
 mod monitor;
 mod monitor_dispatcher;
 mod monitor_handler;
+mod node_health_check;
+mod node_health_monitor;
+mod pending_txn_tracker;
+mod price_provider;
 mod pubsub_client;
+mod rich_text;
+mod rpc_pool;
+mod send_queue;
 mod stcscan_monitor;
 mod stcscan_monitor_index;
+mod storage;
+mod stores;
+mod subscription;
+mod supply_check;
 mod telegram;
+mod templates;
+mod transfer_index;
 mod types;
+mod velocity_check;
 
+use crate::balance_monitor::BalanceMonitor;
 use crate::daily_notification::DailyNotificationService;
-use crate::monitor_handler::default_monitor_handler::DefaultMonitorHandler;
+use crate::monitor_handler::composite_monitor_handler::CompositeMonitorHandler;
+use crate::node_health_monitor::NodeHealthMonitor;
+use crate::price_provider::price_provider_from_config;
+use crate::rpc_pool::RpcClientPool;
+use crate::stores::Stores;
 use crate::telegram::TelegramBot;
 use anyhow::{ensure, Result};
 use clap::Parser;
-use starcoin_rpc_client::RpcClient;
 use stcscan_monitor::StcScanMonitor;
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 use tracing::{info, Level};
 
 #[derive(Parser)]
@@ -46,30 +83,63 @@ fn main() -> Result<()> {
     let config = Arc::new(config::Config::load()?);
     info!("Configuration loaded successfully");
 
-    let rpc_url = &config.starcoin_rpc_url;
-    ensure!(rpc_url.starts_with("ws://") || rpc_url.starts_with("wss://"));
-    let rpc_client = Arc::new(RpcClient::connect_websocket(rpc_url)?);
+    for rpc_url in &config.starcoin_rpc_urls {
+        ensure!(
+            rpc_url.starts_with("ws://") || rpc_url.starts_with("wss://"),
+            "Starcoin RPC url must be a websocket url: {}",
+            rpc_url
+        );
+    }
+    let rpc_pool = Arc::new(RpcClientPool::connect(
+        &config.starcoin_rpc_urls,
+        config.rpc_max_head_lag,
+    )?);
+
+    let stores = Arc::new(Stores::open(&config.data_dir)?);
 
     // Init telegram bot
-    let tg_bot = Arc::new(TelegramBot::new(config.clone(), rpc_client.clone()));
-    let monitor_handler = Arc::new(DefaultMonitorHandler::new(
-        rpc_client.clone(),
-        tg_bot.clone(),
+    let tg_bot = Arc::new(TelegramBot::new(
+        config.clone(),
+        rpc_pool.clone(),
+        stores.clone(),
+    )?);
+    let price_provider = price_provider_from_config(&config)?;
+    let monitor_handler = Arc::new(CompositeMonitorHandler::from_config(
         config.clone(),
-    ));
+        rpc_pool.clone(),
+        tg_bot.clone(),
+        price_provider.clone(),
+        stores,
+    )?);
 
     // Init monitor, do some compute-heavy work or call synchronous code
-    let monitor = monitor::Monitor::new(rpc_client.clone(), monitor_handler.clone())
+    let monitor = monitor::Monitor::new(rpc_pool.clone(), monitor_handler.clone())
         .expect("Failed to create monitor.");
     let mut handles = monitor.run()?;
+    handles.push(
+        rpc_pool
+            .clone()
+            .run_health_check(Duration::from_secs(config.rpc_health_check_interval))?,
+    );
     handles.push(tg_bot.run()?);
 
     // Init stc scan monitor
     let stc_scan_monitor =
-        StcScanMonitor::new(config.clone(), monitor_handler.clone(), rpc_client.clone());
+        StcScanMonitor::new(config.clone(), monitor_handler.clone(), rpc_pool.clone());
     handles.push(stc_scan_monitor.run()?);
 
-    let daily_notification = DailyNotificationService::new(config.clone(), tg_bot.clone());
+    // Init node health monitor
+    let node_health_monitor =
+        NodeHealthMonitor::new(config.clone(), monitor_handler.clone(), rpc_pool.clone());
+    handles.push(node_health_monitor.run()?);
+
+    // Init balance monitor of the watched addresses
+    let balance_monitor =
+        BalanceMonitor::new(config.clone(), monitor_handler.clone(), rpc_pool.clone());
+    handles.push(balance_monitor.run()?);
+
+    let daily_notification =
+        DailyNotificationService::new(config.clone(), tg_bot.clone(), price_provider);
     handles.push(daily_notification.run()?);
     // Init daily notification service
     // let daily_notification_service = tokio::runtime::Runtime::new()?
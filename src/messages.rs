@@ -0,0 +1,840 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Catalog of the phrases of the alerts and bot replies, one per locale. The
+//! templates and commands only arrange them, so a locale is added here alone
+//! and none of its phrases can be left out.
+
+use crate::{
+    address_label::AddressCategory,
+    alert_mute::MuteRule,
+    alert_sender::AlertCategory,
+    balance_check::BalanceChangeAlert,
+    bot_access::Role,
+    bot_jobs::{JobId, JobLimit},
+    fund_trace::{TraceConfig, MAX_TRACE_DEPTH},
+    governance_check::GovernanceAction,
+    locale::Locale,
+    subscription::Subscription,
+    supply_check::{SupplyAlert, SupplyChangeKind},
+    telegram::ArgError,
+    velocity_check::{TransferDirection, VelocityAlert},
+};
+use chrono::{DateTime, Utc};
+use starcoin_types::block::BlockNumber;
+
+/// The phrases of a locale, those with values being formatted by a function
+pub struct Messages {
+    // Transfer alerts
+    pub large_transfer_title: &'static str,
+    pub large_transfer_digest_title: &'static str,
+    pub pending_large_transfer_title: &'static str,
+    pub block: &'static str,
+    pub transaction: &'static str,
+    pub amount: &'static str,
+    pub sender: &'static str,
+    pub receiver: &'static str,
+    pub pending_warned: &'static str,
+    pub pending_waiting: &'static str,
+    /// Number of transfers, followed by their total amount
+    pub large_transfers: fn(usize) -> String,
+    pub address_category: fn(AddressCategory) -> &'static str,
+
+    // Block production, gas and node alerts
+    pub block_anomaly_title: &'static str,
+    /// Seconds since the previous block
+    pub slow_block: fn(u64) -> String,
+    /// Difficulty, recent average difficulty and the deviation in percent
+    pub difficulty_swing: fn(u128, u128, f64) -> String,
+    /// Miner, blocks produced, blocks of the window and their share in percent
+    pub miner_dominance: fn(&str, usize, usize, f64) -> String,
+    pub failed_transactions_title: &'static str,
+    /// Failed and total transactions, failure rate in percent
+    pub failed_transactions: fn(usize, usize, f64) -> String,
+    pub gas_title: &'static str,
+    /// Gas used and its limit
+    pub high_gas_used: fn(u64, u64) -> String,
+    /// Percentile, its gas price, the limit and the median gas price
+    pub high_gas_price: fn(usize, u64, u64, u64) -> String,
+    pub index_lag_title: &'static str,
+    pub index_lag_cached_block: &'static str,
+    pub index_lag: &'static str,
+    pub index_lag_hint: &'static str,
+    pub node_health_title: &'static str,
+    /// Node URL, its head, seconds without a new block and the last error
+    pub head_stale: fn(&str, BlockNumber, u64, Option<&str>) -> String,
+    /// Node URL and its head, best node URL and its head, followed by the lag
+    pub head_lagging: fn(&str, BlockNumber, &str, BlockNumber) -> String,
+    pub hash_mismatch_block: &'static str,
+    pub hash_mismatch_heads: &'static str,
+
+    // Balance, velocity, governance and supply alerts
+    /// The alert and its described address, followed by the change
+    pub balance_change: fn(&BalanceChangeAlert, &str) -> String,
+    /// The alert, its described address, the window in seconds and blocks,
+    /// followed by the total
+    pub velocity: fn(&VelocityAlert, &str, u64, Option<u64>) -> String,
+    /// Limit, followed by the last transaction
+    pub velocity_limit: fn(&str) -> String,
+    pub governance_title: fn(&GovernanceAction) -> &'static str,
+    pub governance_action: fn(&GovernanceAction) -> String,
+    pub event_data: &'static str,
+    /// The alert, followed by its total
+    pub token_supply: fn(&SupplyAlert) -> String,
+    /// The alert and the latest change
+    pub token_supply_limit: fn(&SupplyAlert, u128) -> String,
+
+    // Daily summary and query replies
+    pub daily_summary_title: &'static str,
+    pub date: &'static str,
+    pub no_large_transfers_today: &'static str,
+    pub large_transfers_count: &'static str,
+    pub total_amount: &'static str,
+    pub value: &'static str,
+    pub from_block: &'static str,
+    pub to_block: &'static str,
+    pub no_large_transfers: &'static str,
+    pub balance_address: &'static str,
+    pub balance: &'static str,
+    pub balance_change_24h: &'static str,
+    /// Address without a balance resource, which is not a zero balance
+    pub no_balance_resource: fn(&str) -> String,
+    pub history_unreadable: &'static str,
+    pub since: &'static str,
+    pub no_history: &'static str,
+    /// Blocks fetched and to fetch
+    pub blocks_fetched: fn(u64, u64) -> String,
+    pub invalid_block_range: &'static str,
+    pub block_range_too_large: &'static str,
+    pub tracing: &'static str,
+    /// Depth, followed by the trace
+    pub fund_trace: fn(usize) -> String,
+    pub txn_not_indexed: fn(&str) -> String,
+    pub trace_no_outgoing: &'static str,
+    /// Number of transfers listed
+    pub trace_truncated: fn(usize) -> String,
+
+    // Commands
+    /// Role needed and role of the user
+    pub permission_denied: fn(Role, Role) -> String,
+    /// Role needed by the alert buttons
+    pub button_permission_denied: fn(Role) -> String,
+    pub command_failed: &'static str,
+    pub unknown_command: &'static str,
+    pub help_heading: &'static str,
+    /// Commands listed by `/help` and their descriptions
+    pub help_commands: &'static [(&'static str, &'static str)],
+    /// Usage of a command, `None` for an unknown one
+    pub command_usage: fn(&str) -> Option<String>,
+    pub unknown_help_command: fn(&str) -> String,
+    pub arg_error: fn(&ArgError) -> String,
+    pub invalid_arguments: &'static str,
+    /// Command and its cooldown in seconds
+    pub command_cooldown: fn(&str, u64) -> String,
+    pub no_label: fn(&str) -> String,
+    pub label_saved: &'static str,
+    pub subscribed: &'static str,
+    pub no_subscriptions: &'static str,
+    pub subscriptions: &'static str,
+    pub no_matching_subscription: &'static str,
+    pub subscriptions_removed: fn(usize) -> String,
+    pub chat_language: fn(Locale) -> String,
+    pub language_set: fn(Locale) -> String,
+
+    // Mutes
+    pub mute_rule: fn(&MuteRule) -> String,
+    pub ack_button: &'static str,
+    pub snooze_button: &'static str,
+    pub mute_address_button: &'static str,
+    pub no_mutes: &'static str,
+    pub mutes: &'static str,
+    pub no_matching_mute: &'static str,
+    pub alerts_resumed: fn(usize) -> String,
+
+    // Jobs
+    pub job_limit: fn(JobLimit) -> String,
+    /// Job id and description
+    pub job_started: fn(JobId, &str) -> String,
+    /// Job id, description and progress
+    pub job_running: fn(JobId, &str, &str) -> String,
+    pub job_done: fn(JobId, &str) -> String,
+    /// Job id, description and error
+    pub job_failed: fn(JobId, &str, &str) -> String,
+    pub job_cancelled: fn(JobId, &str) -> String,
+    /// Number of jobs cancelled and their ids
+    pub jobs_cancelled: fn(usize, &str) -> String,
+    pub no_jobs: &'static str,
+    pub jobs: &'static str,
+    /// Job id, description, user id and start time
+    pub job: fn(JobId, &str, u64, &str) -> String,
+    pub no_job_to_cancel: &'static str,
+}
+
+fn format_time(time: &DateTime<Utc>) -> String {
+    time.format("%Y-%m-%d %H:%M UTC").to_string()
+}
+
+pub static ZH_CN: Messages = Messages {
+    large_transfer_title: "🚨[大交易事件告警]: 区块: ",
+    large_transfer_digest_title: "🚨[大交易汇总告警]: 区块: ",
+    pending_large_transfer_title: "🚨[待确认大交易告警]: 交易: ",
+    block: "区块: ",
+    transaction: "交易: ",
+    amount: "额度: ",
+    sender: "发送方: ",
+    receiver: "接收方: ",
+    pending_warned: ", 已在交易池中预警",
+    pending_waiting: ", 交易尚在交易池中等待打包",
+    large_transfers: |count| format!(", 共 {} 笔大交易, 交易总额: ", count),
+    address_category: |category| match category {
+        AddressCategory::Exchange => "交易所",
+        AddressCategory::Bridge => "跨链桥",
+        AddressCategory::Team => "团队",
+        AddressCategory::Foundation => "基金会",
+        AddressCategory::Blacklisted => "⚠️黑名单",
+    },
+
+    block_anomaly_title: "🚨[出块异常告警]: ",
+    slow_block: |interval_secs| format!(", 距上一个区块 {} 秒", interval_secs),
+    difficulty_swing: |difficulty, average_difficulty, change_percent| {
+        format!(
+            ", 难度 {} 偏离最近平均难度 {} 达 {:.2}%",
+            difficulty, average_difficulty, change_percent
+        )
+    },
+    miner_dominance: |miner, blocks, window, percent| {
+        format!(
+            "矿工 {} 在最近 {} 个区块中出块 {} 个, 占比 {:.2}%",
+            miner, window, blocks, percent
+        )
+    },
+    failed_transactions_title: "🚨[交易失败告警]: 区块: ",
+    failed_transactions: |failed, total, percent| {
+        format!(", 失败交易 {}/{}, 失败率 {:.2}%", failed, total, percent)
+    },
+    gas_title: "🚨[Gas异常告警]: 区块: ",
+    high_gas_used: |gas_used, max_gas_used| {
+        format!(", Gas 消耗 {} 超过上限 {}", gas_used, max_gas_used)
+    },
+    high_gas_price: |percentile, gas_price, max_gas_price, median_gas_price| {
+        format!(
+            ", P{} Gas 价格 {} 超过上限 {}, 中位数: {}",
+            percentile, gas_price, max_gas_price, median_gas_price
+        )
+    },
+    index_lag_title: "🚨[索引差异过大事件告警]: 当前链上区块号: ",
+    index_lag_cached_block: ", StcScan 缓存的区块号: ",
+    index_lag: ", 差额: ",
+    index_lag_hint: ", 其差异过大可能导致StcScan索引追不上",
+    node_health_title: "🚨[节点健康告警]: ",
+    head_stale: |url, head_number, stale_secs, error| {
+        format!(
+            "节点 {} 的区块高度 {} 已 {} 秒未增长{}",
+            url,
+            head_number,
+            stale_secs,
+            error
+                .map(|e| format!(", 最近错误: {}", e))
+                .unwrap_or_default()
+        )
+    },
+    head_lagging: |url, head_number, best_url, best_number| {
+        format!(
+            "节点 {} 的区块高度 {} 落后节点 {} 的区块高度 {}, 差额: ",
+            url, head_number, best_url, best_number
+        )
+    },
+    hash_mismatch_block: "各节点在区块高度 ",
+    hash_mismatch_heads: " 的区块哈希不一致: ",
+
+    balance_change: |alert, address| {
+        format!(
+            "🚨[余额变动告警]: 地址 {} 的 {} 余额由 {:.9} 变为 {:.9}, 变动: ",
+            address,
+            alert.token,
+            alert.previous as f64 / 1e9,
+            alert.current as f64 / 1e9
+        )
+    },
+    velocity: |alert, address, window_secs, window_blocks| {
+        let action = match alert.direction {
+            TransferDirection::Sent => "转出",
+            TransferDirection::Received => "转入",
+        };
+        let window = match window_blocks {
+            Some(blocks) => format!("最近 {} 秒 (不超过 {} 个区块) 内", window_secs, blocks),
+            None => format!("最近 {} 秒内", window_secs),
+        };
+        format!(
+            "🚨[累计转账告警]: 地址 {} {}{} {} 共 {} 笔, 累计 ",
+            address, window, action, alert.token, alert.transfers
+        )
+    },
+    velocity_limit: |limit| format!(" 超过上限 {}, 最近一笔交易: ", limit),
+    governance_title: |action| match action {
+        GovernanceAction::ConfigChanged { .. } => "🚨[链上配置变更告警]: ",
+        GovernanceAction::ModuleUpgraded | GovernanceAction::PackagePublished { .. } => {
+            "🚨[合约升级告警]: "
+        }
+        _ => "🚨[链上治理告警]: ",
+    },
+    governance_action: |action| match action {
+        GovernanceAction::ProposalCreated => "新提案创建".to_string(),
+        GovernanceAction::VoteChanged => "提案投票变更".to_string(),
+        GovernanceAction::ProposalQueued { function } => {
+            format!("提案进入执行队列, 调用: {}", function)
+        }
+        GovernanceAction::ProposalExecuted { function } => format!("提案执行, 调用: {}", function),
+        GovernanceAction::ConfigChanged { config } => format!("配置 {} 已变更", config),
+        GovernanceAction::ModuleUpgraded => "合约包已升级".to_string(),
+        GovernanceAction::PackagePublished {
+            package_address,
+            modules,
+        } => format!("地址 {} 发布合约包, 模块数: {}", package_address, modules),
+    },
+    event_data: "事件数据: ",
+    token_supply: |alert| {
+        let action = match alert.kind {
+            SupplyChangeKind::Mint => "铸造",
+            SupplyChangeKind::Burn => "销毁",
+            SupplyChangeKind::TreasuryWithdraw => "从国库提取",
+        };
+        format!(
+            "🚨[代币供应告警]: {} 最近 {} 秒内{}总量 ",
+            alert.token, alert.window_secs, action
+        )
+    },
+    token_supply_limit: |alert, amount| {
+        format!(" 超过上限 {} (最小单位), 最近一笔: {}", alert.limit, amount)
+    },
+
+    daily_summary_title: "📊 【每日交易汇总】",
+    date: "日期: ",
+    no_large_transfers_today: "今日没有发现大额交易",
+    large_transfers_count: "大额交易总数: ",
+    total_amount: "交易总额: ",
+    value: "折合: ",
+    from_block: "从区块 ",
+    to_block: " 到 ",
+    no_large_transfers: ", 没有找到大交易",
+    balance_address: "💰 地址: ",
+    balance: "\n当前余额: ",
+    balance_change_24h: "\n24小时变动: ",
+    no_balance_resource: |address| {
+        format!(
+            "💰 地址 {} 没有 STC 余额资源, 账户不存在或未接收过 STC",
+            address
+        )
+    },
+    history_unreadable: "历史记录读取失败",
+    since: "自 ",
+    no_history: "无历史记录, 仅记录 WATCHED_ADDRESSES 中的地址",
+    blocks_fetched: |fetched, count| format!("已查询 {}/{} 个区块", fetched, count),
+    invalid_block_range: "❌ 起始区块不能大于结束区块",
+    block_range_too_large: "❌ 区块区间不能超过 2000 个区块",
+    tracing: "追踪中",
+    fund_trace: |depth| format!("🔎 资金追踪 (深度 {}):", depth),
+    txn_not_indexed: |txn_hash| format!("❌ 转账索引中未找到交易 {}", txn_hash),
+    trace_no_outgoing: "(未发现后续转出)",
+    trace_truncated: |transfers| format!("(仅列出前 {} 笔转账)", transfers),
+
+    permission_denied: |required_role, role| {
+        format!(
+            "⛔ 权限不足: 该命令需要 {} 角色, 当前角色为 {}",
+            required_role, role
+        )
+    },
+    button_permission_denied: |required_role| format!("⛔ 权限不足: 需要 {} 角色", required_role),
+    command_failed: "❌ 命令执行失败: ",
+    unknown_command: "❓ 未知命令, 使用 /help 查看可用的命令",
+    help_heading: "🤖 Starcoin Monitor Bot 支持以下命令, /help <command> 查看命令用法:",
+    help_commands: &[
+        ("help", "显示帮助"),
+        ("transactions", "查询两个区块之间的大交易"),
+        ("balance", "查询地址的 STC 余额及24小时变动"),
+        ("label", "查询, 添加或修改地址标签"),
+        ("trace", "追踪大额转账的资金流向"),
+        ("subscribe", "订阅告警, 不带参数时查看本群的订阅"),
+        ("unsubscribe", "取消订阅"),
+        ("snooze", "暂停告警"),
+        ("mute", "屏蔽地址的告警, 不带参数时查看已暂停和屏蔽的告警"),
+        ("unmute", "恢复已暂停或屏蔽的告警"),
+        ("language", "查看或设置本群的语言"),
+        ("jobs", "查看运行中的查询任务"),
+        ("cancel", "取消自己的查询任务"),
+    ],
+    command_usage: |command| {
+        let usage = match command {
+            "transactions" => "用法: /transactions <start_block> <end_block>\n查询两个区块之间的大交易, 区间不超过 2000 个区块\n示例: /transactions 1000 1100".to_string(),
+            "balance" => "用法: /balance <address>\n查询地址的 STC 余额及24小时变动\n示例: /balance 0x4a50777e0e4f67625400148b04afd572".to_string(),
+            "label" => format!(
+                "用法: /label <address> [<category> <name>]\n查询地址标签, 或添加/修改地址标签\n类别: {}\n示例: /label 0x4a50777e0e4f67625400148b04afd572 exchange Binance",
+                AddressCategory::NAMES
+            ),
+            "trace" => format!(
+                "用法: /trace <txn_hash> [depth]\n追踪大额转账的资金流向\n深度: 1 到 {}, 默认 {}\n示例: /trace 0x6ed3afdf412404f98fc16d9350b9a19d3258598be5f6b73215a6ab06247b6a53 3",
+                MAX_TRACE_DEPTH,
+                TraceConfig::default().depth
+            ),
+            "subscribe" => format!(
+                "用法: /subscribe [{}]\n订阅告警到本群或本话题, 可对地址和代币设置低于告警阈值的金额 (地址以 STC 计, STC 以外的代币需给出精度), 不带参数时查看本群的订阅\n类别: {}\n示例: /subscribe token 0x1::STC::STC 50000",
+                Subscription::USAGE,
+                AlertCategory::NAMES
+            ),
+            "unsubscribe" => "用法: /unsubscribe <category <category> | address <address> | token <token_code> | all>\n取消本群或本话题的订阅\n示例: /unsubscribe token 0x1::STC::STC".to_string(),
+            "snooze" => format!(
+                "用法: /snooze <category>:<severity>[:<kind or address>] [duration]\n暂停一类告警, 默认 1 小时, 时长如 30m, 2h, 1d, 更高级别的同类告警仍会发送\n类别: {}\n示例: /snooze index_lag:warning 2h",
+                AlertCategory::NAMES
+            ),
+            "mute" => "用法: /mute [<address> [duration]]\n屏蔽地址的所有告警, 不指定时长时一直屏蔽, 不带参数时查看已暂停和屏蔽的告警\n示例: /mute 0x4a50777e0e4f67625400148b04afd572 1d".to_string(),
+            "unmute" => "用法: /unmute <category>:<severity>[:<kind or address>] | <address> | all\n恢复已暂停或屏蔽的告警\n示例: /unmute index_lag:warning".to_string(),
+            "language" => format!(
+                "用法: /language [{}]\n查看或设置本群告警和回复的语言\n示例: /language en-US",
+                Locale::NAMES.replace(", ", " | ")
+            ),
+            "cancel" => "用法: /cancel [job_id]\n取消指定的查询任务, 不指定时取消自己的全部任务, 管理员可取消他人的任务\n示例: /cancel 3".to_string(),
+            _ => return None,
+        };
+        Some(usage)
+    },
+    unknown_help_command: |command| format!("❓ 未知命令: /{}, 使用 /help 查看可用的命令", command),
+    arg_error: |e| match e {
+        ArgError::TooManyArguments => "参数过多".to_string(),
+        ArgError::MissingAddress => "缺少地址".to_string(),
+        ArgError::InvalidAddress(address) => format!("无效的地址: {}", address),
+        ArgError::InvalidJobId(job_id) => format!("无效的任务编号: {}", job_id),
+        ArgError::InvalidSubscription => "无效的订阅".to_string(),
+        ArgError::MissingSubscription => "缺少要取消的订阅".to_string(),
+        ArgError::InvalidLocale(locale) => format!("不支持的语言: {}", locale),
+        ArgError::InvalidDuration(duration) => {
+            format!("无效的时长: {}, 如 30m, 2h 或 1d", duration)
+        }
+        ArgError::MissingAlertKey => "缺少告警标识".to_string(),
+        ArgError::InvalidAlertKey(key) => format!("无效的告警标识: {}", key),
+        ArgError::InvalidMuteTarget(target) => format!("无效的告警标识或地址: {}", target),
+        ArgError::MissingLabelName => "缺少标签名称".to_string(),
+        ArgError::InvalidCategory(category) => format!("无效的类别: {}", category),
+        ArgError::MissingTxnHash => "缺少交易哈希".to_string(),
+        ArgError::InvalidTxnHash(txn_hash) => format!("无效的交易哈希: {}", txn_hash),
+        ArgError::InvalidDepth => format!("无效的深度, 应为 1 到 {}", MAX_TRACE_DEPTH),
+    },
+    invalid_arguments: "参数无效",
+    command_cooldown: |command, cooldown| {
+        format!("⏳ {} 每 {} 秒只能执行一次, 请稍后再试", command, cooldown)
+    },
+    no_label: |address| format!("🏷 地址 {} 暂无标签", address),
+    label_saved: "✅ 标签已保存: ",
+    subscribed: "✅ 已订阅: ",
+    no_subscriptions: "📭 暂无订阅, 使用 /help subscribe 查看用法",
+    subscriptions: "📬 当前订阅:",
+    no_matching_subscription: "❌ 没有匹配的订阅",
+    subscriptions_removed: |removed| format!("✅ 已取消 {} 个订阅", removed),
+    chat_language: |locale| format!("🌐 本群的语言: {}", locale),
+    language_set: |locale| format!("🌐 本群的语言已设置为 {}", locale),
+
+    mute_rule: |rule| match rule {
+        MuteRule::Snooze { key, until } => format!("{} 暂停至 {}", key, format_time(until)),
+        MuteRule::Ack { key, last_fired } => {
+            format!("{} 已确认, 最近触发于 {}", key, format_time(last_fired))
+        }
+        MuteRule::Address { address, until } => match until {
+            Some(until) => format!("{} 屏蔽至 {}", address, format_time(until)),
+            None => format!("{} 已屏蔽", address),
+        },
+    },
+    ack_button: "✅ 确认",
+    snooze_button: "💤 暂停 1 小时",
+    mute_address_button: "🔕 屏蔽地址",
+    no_mutes: "🔔 暂无暂停或屏蔽的告警",
+    mutes: "🔕 已暂停或屏蔽的告警:",
+    no_matching_mute: "❌ 没有匹配的暂停或屏蔽",
+    alerts_resumed: |removed| format!("🔔 已恢复 {} 项告警", removed),
+
+    job_limit: |limit| match limit {
+        JobLimit::TooManyJobs(jobs) => format!("⏳ 已有 {} 个查询任务在运行, 请稍后再试", jobs),
+        JobLimit::TooManyUserJobs(jobs) => format!(
+            "⏳ 你已有 {} 个查询任务在运行, 请等待完成或使用 /cancel 取消",
+            jobs
+        ),
+    },
+    job_started: |id, description| {
+        format!(
+            "⏳ 任务 #{} 已开始: {}\n使用 /cancel {} 取消",
+            id, description, id
+        )
+    },
+    job_running: |id, description, progress| {
+        format!(
+            "⏳ 任务 #{} 运行中: {}\n{}\n使用 /cancel {} 取消",
+            id, description, progress, id
+        )
+    },
+    job_done: |id, description| format!("✅ 任务 #{} 已完成: {}", id, description),
+    job_failed: |id, description, error| {
+        format!("❌ 任务 #{} 失败: {}\n{}", id, description, error)
+    },
+    job_cancelled: |id, description| format!("🛑 任务 #{} 已取消: {}", id, description),
+    jobs_cancelled: |count, ids| format!("🛑 已取消 {} 个查询任务: {}", count, ids),
+    no_jobs: "📋 没有运行中的查询任务",
+    jobs: "📋 运行中的查询任务:",
+    job: |id, description, user_id, started_at| {
+        format!(
+            "#{} {} (用户 {}, 开始于 {})",
+            id, description, user_id, started_at
+        )
+    },
+    no_job_to_cancel: "❌ 没有可取消的查询任务",
+};
+
+pub static EN_US: Messages = Messages {
+    large_transfer_title: "🚨[Large Transfer Alert]: Block: ",
+    large_transfer_digest_title: "🚨[Large Transfer Digest]: Blocks: ",
+    pending_large_transfer_title: "🚨[Pending Large Transfer Alert]: Transaction: ",
+    block: "Block: ",
+    transaction: "Transaction: ",
+    amount: "Amount: ",
+    sender: "Sender: ",
+    receiver: "Receiver: ",
+    pending_warned: ", already warned while in the txpool",
+    pending_waiting: ", the transaction is still waiting in the txpool",
+    large_transfers: |count| format!(", {} large transfers, total amount: ", count),
+    address_category: |category| match category {
+        AddressCategory::Exchange => "Exchange",
+        AddressCategory::Bridge => "Bridge",
+        AddressCategory::Team => "Team",
+        AddressCategory::Foundation => "Foundation",
+        AddressCategory::Blacklisted => "⚠️Blacklisted",
+    },
+
+    block_anomaly_title: "🚨[Block Production Alert]: ",
+    slow_block: |interval_secs| format!(", {} seconds after the previous block", interval_secs),
+    difficulty_swing: |difficulty, average_difficulty, change_percent| {
+        format!(
+            ", difficulty {} deviates {:.2}% from the recent average {}",
+            difficulty, change_percent, average_difficulty
+        )
+    },
+    miner_dominance: |miner, blocks, window, percent| {
+        format!(
+            "Miner {} produced {} of the last {} blocks, {:.2}%",
+            miner, blocks, window, percent
+        )
+    },
+    failed_transactions_title: "🚨[Failed Transactions Alert]: Block: ",
+    failed_transactions: |failed, total, percent| {
+        format!(
+            ", failed transactions {}/{}, failure rate {:.2}%",
+            failed, total, percent
+        )
+    },
+    gas_title: "🚨[Gas Alert]: Block: ",
+    high_gas_used: |gas_used, max_gas_used| {
+        format!(", gas used {} exceeds the limit {}", gas_used, max_gas_used)
+    },
+    high_gas_price: |percentile, gas_price, max_gas_price, median_gas_price| {
+        format!(
+            ", P{} gas price {} exceeds the limit {}, median: {}",
+            percentile, gas_price, max_gas_price, median_gas_price
+        )
+    },
+    index_lag_title: "🚨[Index Lag Alert]: Chain head: ",
+    index_lag_cached_block: ", StcScan cached block: ",
+    index_lag: ", lag: ",
+    index_lag_hint: ", StcScan may be unable to catch up with the chain",
+    node_health_title: "🚨[Node Health Alert]: ",
+    head_stale: |url, head_number, stale_secs, error| {
+        format!(
+            "Node {} has been stuck at block {} for {} seconds{}",
+            url,
+            head_number,
+            stale_secs,
+            error
+                .map(|e| format!(", last error: {}", e))
+                .unwrap_or_default()
+        )
+    },
+    head_lagging: |url, head_number, best_url, best_number| {
+        format!(
+            "Node {} at block {} is behind node {} at block {}, lag: ",
+            url, head_number, best_url, best_number
+        )
+    },
+    hash_mismatch_block: "The nodes disagree on block ",
+    hash_mismatch_heads: ": ",
+
+    balance_change: |alert, address| {
+        format!(
+            "🚨[Balance Change Alert]: {} balance of {} went from {:.9} to {:.9}, change: ",
+            alert.token,
+            address,
+            alert.previous as f64 / 1e9,
+            alert.current as f64 / 1e9
+        )
+    },
+    velocity: |alert, address, window_secs, window_blocks| {
+        let action = match alert.direction {
+            TransferDirection::Sent => "sent",
+            TransferDirection::Received => "received",
+        };
+        let window = match window_blocks {
+            Some(blocks) => format!(
+                "the last {} seconds (at most {} blocks)",
+                window_secs, blocks
+            ),
+            None => format!("the last {} seconds", window_secs),
+        };
+        format!(
+            "🚨[Velocity Alert]: {} {} {} transfers of {} within {}, total ",
+            address, action, alert.transfers, alert.token, window
+        )
+    },
+    velocity_limit: |limit| format!(" exceeds the limit {}, last transaction: ", limit),
+    governance_title: |action| match action {
+        GovernanceAction::ConfigChanged { .. } => "🚨[On-chain Config Alert]: ",
+        GovernanceAction::ModuleUpgraded | GovernanceAction::PackagePublished { .. } => {
+            "🚨[Contract Upgrade Alert]: "
+        }
+        _ => "🚨[Governance Alert]: ",
+    },
+    governance_action: |action| match action {
+        GovernanceAction::ProposalCreated => "new proposal created".to_string(),
+        GovernanceAction::VoteChanged => "proposal votes changed".to_string(),
+        GovernanceAction::ProposalQueued { function } => {
+            format!("proposal queued for execution, calling: {}", function)
+        }
+        GovernanceAction::ProposalExecuted { function } => {
+            format!("proposal executed, calling: {}", function)
+        }
+        GovernanceAction::ConfigChanged { config } => format!("config {} changed", config),
+        GovernanceAction::ModuleUpgraded => "package upgraded".to_string(),
+        GovernanceAction::PackagePublished {
+            package_address,
+            modules,
+        } => format!(
+            "package published by {}, modules: {}",
+            package_address, modules
+        ),
+    },
+    event_data: "Event data: ",
+    token_supply: |alert| {
+        let action = match alert.kind {
+            SupplyChangeKind::Mint => "minted",
+            SupplyChangeKind::Burn => "burnt",
+            SupplyChangeKind::TreasuryWithdraw => "withdrawn from the treasury",
+        };
+        format!(
+            "🚨[Token Supply Alert]: {} {} within the last {} seconds: ",
+            alert.token, action, alert.window_secs
+        )
+    },
+    token_supply_limit: |alert, amount| {
+        format!(
+            " exceeds the limit {} (smallest unit), latest: {}",
+            alert.limit, amount
+        )
+    },
+
+    daily_summary_title: "📊 【Daily Transfer Summary】",
+    date: "Date: ",
+    no_large_transfers_today: "No large transfers found today",
+    large_transfers_count: "Large transfers: ",
+    total_amount: "Total amount: ",
+    value: "Value: ",
+    from_block: "From block ",
+    to_block: " to ",
+    no_large_transfers: ", no large transfers found",
+    balance_address: "💰 Address: ",
+    balance: "\nBalance: ",
+    balance_change_24h: "\n24h change: ",
+    no_balance_resource: |address| {
+        format!(
+            "💰 Address {} has no STC balance resource, the account does not exist or never received STC",
+            address
+        )
+    },
+    history_unreadable: "the history cannot be read",
+    since: "since ",
+    no_history: "no history, only kept for the WATCHED_ADDRESSES",
+    blocks_fetched: |fetched, count| format!("{}/{} blocks fetched", fetched, count),
+    invalid_block_range: "❌ Start block must be less than or equal to end block",
+    block_range_too_large:
+        "❌ The interval between the start and end blocks must not exceed 2,000 blocks",
+    tracing: "Tracing",
+    fund_trace: |depth| format!("🔎 Fund trace (depth {}):", depth),
+    txn_not_indexed: |txn_hash| {
+        format!(
+            "❌ Transaction {} not found in the transfer index",
+            txn_hash
+        )
+    },
+    trace_no_outgoing: "(no outgoing transfer found)",
+    trace_truncated: |transfers| format!("(only the first {} transfers listed)", transfers),
+
+    permission_denied: |required_role, role| {
+        format!(
+            "⛔ Permission denied: the command needs the {} role, yours is {}",
+            required_role, role
+        )
+    },
+    button_permission_denied: |required_role| {
+        format!("⛔ Permission denied: needs the {} role", required_role)
+    },
+    command_failed: "❌ Command failed: ",
+    unknown_command: "❓ Unknown command. Use /help to see available commands.",
+    help_heading: "🤖 Starcoin Monitor Bot supports the following commands, see /help <command> for their usage:",
+    help_commands: &[
+        ("help", "Show this help"),
+        ("transactions", "Large transactions between two blocks"),
+        ("balance", "STC balance of an address and its change over 24 hours"),
+        ("label", "Show, add or change the label of an address"),
+        ("trace", "Trace where the funds of a large transfer went"),
+        ("subscribe", "Subscribe to alerts, without arguments show the subscriptions of the chat"),
+        ("unsubscribe", "Unsubscribe from alerts"),
+        ("snooze", "Snooze alerts"),
+        ("mute", "Mute the alerts of an address, without arguments show the snoozed and muted alerts"),
+        ("unmute", "Resume snoozed or muted alerts"),
+        ("language", "Show or set the language of the chat"),
+        ("jobs", "Show the running queries"),
+        ("cancel", "Cancel your queries"),
+    ],
+    command_usage: |command| {
+        let usage = match command {
+            "transactions" => "Usage: /transactions <start_block> <end_block>\nLarge transactions between two blocks, at most 2000 blocks apart\nExample: /transactions 1000 1100".to_string(),
+            "balance" => "Usage: /balance <address>\nSTC balance of an address and its change over 24 hours\nExample: /balance 0x4a50777e0e4f67625400148b04afd572".to_string(),
+            "label" => format!(
+                "Usage: /label <address> [<category> <name>]\nShow the label of an address, or add or change it\nCategories: {}\nExample: /label 0x4a50777e0e4f67625400148b04afd572 exchange Binance",
+                AddressCategory::NAMES
+            ),
+            "trace" => format!(
+                "Usage: /trace <txn_hash> [depth]\nTrace where the funds of a large transfer went\nDepth: 1 to {}, default {}\nExample: /trace 0x6ed3afdf412404f98fc16d9350b9a19d3258598be5f6b73215a6ab06247b6a53 3",
+                MAX_TRACE_DEPTH,
+                TraceConfig::default().depth
+            ),
+            "subscribe" => format!(
+                "Usage: /subscribe [{}]\nSubscribe this chat or topic to alerts, an address or a token taking an amount below the alert thresholds (in STC for an address, with the decimals for tokens other than STC), without arguments show the subscriptions of the chat\nCategories: {}\nExample: /subscribe token 0x1::STC::STC 50000",
+                Subscription::USAGE,
+                AlertCategory::NAMES
+            ),
+            "unsubscribe" => "Usage: /unsubscribe <category <category> | address <address> | token <token_code> | all>\nUnsubscribe this chat or topic\nExample: /unsubscribe token 0x1::STC::STC".to_string(),
+            "snooze" => format!(
+                "Usage: /snooze <category>:<severity>[:<kind or address>] [duration]\nSnooze a kind of alerts, 1 hour by default, durations like 30m, 2h, 1d, the same alerts of a higher severity being still sent\nCategories: {}\nExample: /snooze index_lag:warning 2h",
+                AlertCategory::NAMES
+            ),
+            "mute" => "Usage: /mute [<address> [duration]]\nMute all the alerts of an address, until unmuted without a duration, without arguments show the snoozed and muted alerts\nExample: /mute 0x4a50777e0e4f67625400148b04afd572 1d".to_string(),
+            "unmute" => "Usage: /unmute <category>:<severity>[:<kind or address>] | <address> | all\nResume snoozed or muted alerts\nExample: /unmute index_lag:warning".to_string(),
+            "language" => format!(
+                "Usage: /language [{}]\nShow or set the language of the alerts and replies in this chat\nExample: /language en-US",
+                Locale::NAMES.replace(", ", " | ")
+            ),
+            "cancel" => "Usage: /cancel [job_id]\nCancel a query, all of yours without a job id, admins being able to cancel those of others\nExample: /cancel 3".to_string(),
+            _ => return None,
+        };
+        Some(usage)
+    },
+    unknown_help_command: |command| {
+        format!(
+            "❓ Unknown command: /{}. Use /help to see available commands.",
+            command
+        )
+    },
+    arg_error: |e| match e {
+        ArgError::TooManyArguments => "Too many arguments".to_string(),
+        ArgError::MissingAddress => "Missing address".to_string(),
+        ArgError::InvalidAddress(address) => format!("Invalid address: {}", address),
+        ArgError::InvalidJobId(job_id) => format!("Invalid job id: {}", job_id),
+        ArgError::InvalidSubscription => "Invalid subscription".to_string(),
+        ArgError::MissingSubscription => "Missing the subscription to remove".to_string(),
+        ArgError::InvalidLocale(locale) => format!("Unsupported language: {}", locale),
+        ArgError::InvalidDuration(duration) => {
+            format!("Invalid duration: {}, e.g. 30m, 2h or 1d", duration)
+        }
+        ArgError::MissingAlertKey => "Missing alert key".to_string(),
+        ArgError::InvalidAlertKey(key) => format!("Invalid alert key: {}", key),
+        ArgError::InvalidMuteTarget(target) => {
+            format!("Invalid alert key or address: {}", target)
+        }
+        ArgError::MissingLabelName => "Missing label name".to_string(),
+        ArgError::InvalidCategory(category) => format!("Invalid category: {}", category),
+        ArgError::MissingTxnHash => "Missing transaction hash".to_string(),
+        ArgError::InvalidTxnHash(txn_hash) => format!("Invalid transaction hash: {}", txn_hash),
+        ArgError::InvalidDepth => format!("Invalid depth, from 1 to {}", MAX_TRACE_DEPTH),
+    },
+    invalid_arguments: "Invalid arguments",
+    command_cooldown: |command, cooldown| {
+        format!(
+            "⏳ {} can only be run once every {} seconds, please try again later",
+            command, cooldown
+        )
+    },
+    no_label: |address| format!("🏷 Address {} has no label", address),
+    label_saved: "✅ Label saved: ",
+    subscribed: "✅ Subscribed: ",
+    no_subscriptions: "📭 No subscriptions, see /help subscribe",
+    subscriptions: "📬 Subscriptions:",
+    no_matching_subscription: "❌ No matching subscription",
+    subscriptions_removed: |removed| format!("✅ {} subscriptions removed", removed),
+    chat_language: |locale| format!("🌐 Language of this chat: {}", locale),
+    language_set: |locale| format!("🌐 The language of this chat is now {}", locale),
+
+    mute_rule: |rule| match rule {
+        MuteRule::Snooze { key, until } => {
+            format!("{} snoozed until {}", key, format_time(until))
+        }
+        MuteRule::Ack { key, last_fired } => format!(
+            "{} acknowledged, last fired at {}",
+            key,
+            format_time(last_fired)
+        ),
+        MuteRule::Address { address, until } => match until {
+            Some(until) => format!("{} muted until {}", address, format_time(until)),
+            None => format!("{} muted", address),
+        },
+    },
+    ack_button: "✅ Ack",
+    snooze_button: "💤 Snooze 1h",
+    mute_address_button: "🔕 Mute address",
+    no_mutes: "🔔 No snoozed or muted alerts",
+    mutes: "🔕 Snoozed or muted alerts:",
+    no_matching_mute: "❌ No matching snooze or mute",
+    alerts_resumed: |removed| format!("🔔 {} alerts resumed", removed),
+
+    job_limit: |limit| match limit {
+        JobLimit::TooManyJobs(jobs) => {
+            format!(
+                "⏳ {} jobs are already running, please try again later",
+                jobs
+            )
+        }
+        JobLimit::TooManyUserJobs(jobs) => format!(
+            "⏳ You already have {} jobs running, wait for them or use /cancel",
+            jobs
+        ),
+    },
+    job_started: |id, description| {
+        format!(
+            "⏳ Job #{} started: {}\nUse /cancel {} to cancel it",
+            id, description, id
+        )
+    },
+    job_running: |id, description, progress| {
+        format!(
+            "⏳ Job #{} running: {}\n{}\nUse /cancel {} to cancel it",
+            id, description, progress, id
+        )
+    },
+    job_done: |id, description| format!("✅ Job #{} done: {}", id, description),
+    job_failed: |id, description, error| {
+        format!("❌ Job #{} failed: {}\n{}", id, description, error)
+    },
+    job_cancelled: |id, description| format!("🛑 Job #{} cancelled: {}", id, description),
+    jobs_cancelled: |count, ids| format!("🛑 {} jobs cancelled: {}", count, ids),
+    no_jobs: "📋 No running jobs",
+    jobs: "📋 Running jobs:",
+    job: |id, description, user_id, started_at| {
+        format!(
+            "#{} {} (user {}, started at {})",
+            id, description, user_id, started_at
+        )
+    },
+    no_job_to_cancel: "❌ No job to cancel",
+};
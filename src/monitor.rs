@@ -1,11 +1,22 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{monitor_dispatcher::MonitorDispatcher, pubsub_client::PubSubClient};
+use crate::{
+    event_filter::{merge_interests, RecentKeys},
+    monitor_dispatcher::MonitorDispatcher,
+    pubsub_client::PubSubClient,
+    rpc_pool::RpcClientPool,
+};
 use anyhow::Result;
-use starcoin_rpc_client::RpcClient;
-use std::{sync::Arc, thread::JoinHandle};
-use tracing::info;
+use std::{
+    sync::{Arc, Mutex},
+    thread::JoinHandle,
+};
+use tracing::{error, info};
+
+/// Number of recent events remembered to drop the ones delivered by several
+/// subscriptions
+const RECENT_EVENTS_CAPACITY: usize = 4096;
 
 pub struct Monitor {
     pubsub_client: Arc<PubSubClient>,
@@ -13,27 +24,53 @@ pub struct Monitor {
 }
 
 impl Monitor {
-    pub fn new(rpc_client: Arc<RpcClient>, dispatcher: Arc<dyn MonitorDispatcher>) -> Result<Self> {
+    pub fn new(
+        rpc_pool: Arc<RpcClientPool>,
+        dispatcher: Arc<dyn MonitorDispatcher>,
+    ) -> Result<Self> {
         Ok(Self {
             dispatcher,
-            pubsub_client: Arc::new(PubSubClient::new(rpc_client)?),
+            pubsub_client: Arc::new(PubSubClient::new(rpc_pool)?),
         })
     }
 
     pub fn run(&self) -> Result<Vec<JoinHandle<()>>> {
         info!("Monitor::run | entered");
 
-        let pubsub_client1 = self.pubsub_client.clone();
-        let dispatcher1 = self.dispatcher.clone();
-        let event_watch_handle = std::thread::spawn(move || {
-            pubsub_client1
-                .subscribe_new_events(|evt| {
-                    let dispatcher = dispatcher1.clone();
-                    let evt_clone = evt.clone();
-                    tokio::spawn(async move { dispatcher.dispatch_event(&evt_clone).await });
-                })
-                .expect("should subscribe new events");
-        });
+        let interests = Arc::new(self.dispatcher.event_interests());
+        let recent_events = Arc::new(Mutex::new(RecentKeys::new(RECENT_EVENTS_CAPACITY)));
+        let mut handles = Vec::new();
+        for subscription in merge_interests(&interests) {
+            let pubsub_client = self.pubsub_client.clone();
+            let dispatcher = self.dispatcher.clone();
+            let interests = interests.clone();
+            let recent_events = recent_events.clone();
+            handles.push(std::thread::spawn(move || {
+                pubsub_client
+                    .subscribe_new_events(&subscription, |evt| {
+                        // Events matching several subscriptions are delivered once per subscription
+                        let key = (evt.event_key, evt.event_seq_number.0);
+                        if !recent_events.lock().unwrap().insert(key) {
+                            return;
+                        }
+                        let creator = evt.event_key.get_creator_address();
+                        if !interests
+                            .iter()
+                            .any(|interest| interest.matches(&creator, &evt.type_tag.0))
+                        {
+                            return;
+                        }
+                        let dispatcher = dispatcher.clone();
+                        let evt_clone = evt.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = dispatcher.dispatch_event(&evt_clone).await {
+                                error!("Monitor | Failed to dispatch event: {}", e);
+                            }
+                        });
+                    })
+                    .expect("should subscribe new events");
+            }));
+        }
 
         let pubsub_client2 = self.pubsub_client.clone();
         let dispatcher2 = self.dispatcher.clone();
@@ -42,14 +79,41 @@ impl Monitor {
                 .subscribe_new_blocks(|evt| {
                     let dispatcher = dispatcher2.clone();
                     let evt_clone = evt.clone();
-                    tokio::spawn(async move { dispatcher.dispatch_block(&evt_clone).await });
+                    tokio::spawn(async move {
+                        if let Err(e) = dispatcher.dispatch_block(&evt_clone).await {
+                            error!(
+                                "Monitor | Failed to dispatch block {}: {}",
+                                evt_clone.header.number.0, e
+                            );
+                        }
+                    });
                 })
                 .expect("should subscribe new events");
         });
 
-        let mut handles = Vec::new();
-        handles.push(event_watch_handle);
+        let pubsub_client3 = self.pubsub_client.clone();
+        let dispatcher3 = self.dispatcher.clone();
+        let pending_txn_watch_handle = std::thread::spawn(move || {
+            pubsub_client3
+                .subscribe_new_pending_txns(|txn_hashes| {
+                    for txn_hash in txn_hashes.iter().copied() {
+                        let dispatcher = dispatcher3.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = dispatcher.dispatch_pending_txn(txn_hash).await {
+                                error!(
+                                    "Monitor | Failed to dispatch pending transaction {}: {}",
+                                    txn_hash.to_hex_literal(),
+                                    e
+                                );
+                            }
+                        });
+                    }
+                })
+                .expect("should subscribe new pending transactions");
+        });
+
         handles.push(block_watch_handle);
+        handles.push(pending_txn_watch_handle);
 
         info!("Monitor::run | Exited");
         Ok(handles)
@@ -1,18 +1,61 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use starcoin_rpc_api::types::{BlockView, TransactionEventView};
+use crate::{
+    balance_check::BalanceChangeAlert, event_filter::EventInterest,
+    node_health_check::NodeHealthAlert,
+};
+use starcoin_crypto::HashValue;
+use starcoin_rpc_api::types::{BlockView, SignedUserTransactionView, TransactionEventView};
 use starcoin_types::block::BlockNumber;
 
 #[async_trait::async_trait]
 pub trait MonitorDispatcher: Send + Sync {
-    async fn dispatch_event(&self, event: &TransactionEventView) -> anyhow::Result<()>;
+    /// Events to subscribe to, only the matching events are passed to
+    /// `dispatch_event`. Defaults to none.
+    fn event_interests(&self) -> Vec<EventInterest> {
+        Vec::new()
+    }
 
-    async fn dispatch_block(&self, block: &BlockView) -> anyhow::Result<()>;
+    // Handlers only implement the notifications they are interested in, the
+    // others are ignored.
+
+    async fn dispatch_event(&self, _event: &TransactionEventView) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn dispatch_block(&self, _block: &BlockView) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// A new block along with its transactions, fetched once for all the
+    /// handlers by `CompositeMonitorHandler`
+    async fn dispatch_block_txns(
+        &self,
+        _block: &BlockView,
+        _txns: &[SignedUserTransactionView],
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// A transaction entered the txpool of the node, before being included in a block
+    async fn dispatch_pending_txn(&self, _txn_hash: HashValue) -> anyhow::Result<()> {
+        Ok(())
+    }
 
     async fn dispatch_stcscan_index_exception(
         &self,
-        curr_number: BlockNumber,
-        cached_number: BlockNumber,
-    ) -> anyhow::Result<()>;
+        _curr_number: BlockNumber,
+        _cached_number: BlockNumber,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn dispatch_node_health_exception(&self, _alert: &NodeHealthAlert) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn dispatch_balance_change(&self, _alert: &BalanceChangeAlert) -> anyhow::Result<()> {
+        Ok(())
+    }
 }
@@ -0,0 +1,333 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    alert_sender::AlertSender,
+    balance_check::BalanceChangeAlert,
+    chain_reader::ChainReader,
+    config::Config,
+    event_filter::EventInterest,
+    helper,
+    monitor_dispatcher::MonitorDispatcher,
+    monitor_handler::{
+        default_monitor_handler::DefaultMonitorHandler,
+        governance_monitor_handler::GovernanceMonitorHandler,
+        velocity_monitor_handler::VelocityMonitorHandler,
+    },
+    node_health_check::NodeHealthAlert,
+    price_provider::PriceProvider,
+    stores::Stores,
+};
+use anyhow::{bail, ensure, Result};
+use futures::{
+    future::{join_all, BoxFuture},
+    FutureExt,
+};
+use starcoin_crypto::HashValue;
+use starcoin_rpc_api::types::{BlockView, TransactionEventView};
+use starcoin_types::block::BlockNumber;
+use std::{panic::AssertUnwindSafe, sync::Arc};
+use tracing::{error, info};
+
+struct RegisteredHandler {
+    name: String,
+    handler: Arc<dyn MonitorDispatcher>,
+    interests: Vec<EventInterest>,
+}
+
+/// Dispatches every notification to all the registered handlers.
+///
+/// The handlers run concurrently and are isolated from each other: an error or
+/// a panic in one handler is logged and does not prevent the others from
+/// handling the notification. The transactions of a block are fetched once
+/// and passed to all of them.
+pub struct CompositeMonitorHandler {
+    chain_reader: Arc<dyn ChainReader>,
+    handlers: Vec<RegisteredHandler>,
+}
+
+impl CompositeMonitorHandler {
+    pub fn new(chain_reader: Arc<dyn ChainReader>) -> Self {
+        Self {
+            chain_reader,
+            handlers: Vec::new(),
+        }
+    }
+
+    /// Register the handlers named in `ENABLED_HANDLERS`
+    pub fn from_config(
+        config: Arc<Config>,
+        chain_reader: Arc<dyn ChainReader>,
+        alert_sender: Arc<dyn AlertSender>,
+        price_provider: Arc<dyn PriceProvider>,
+        stores: Arc<Stores>,
+    ) -> Result<Self> {
+        let mut composite = Self::new(chain_reader.clone());
+        for name in &config.enabled_handlers {
+            let handler: Arc<dyn MonitorDispatcher> = match name.as_str() {
+                "default" => Arc::new(DefaultMonitorHandler::new(
+                    chain_reader.clone(),
+                    alert_sender.clone(),
+                    price_provider.clone(),
+                    stores.clone(),
+                    config.clone(),
+                )),
+                "governance" => Arc::new(GovernanceMonitorHandler::new(
+                    alert_sender.clone(),
+                    config.clone(),
+                )),
+                "velocity" => Arc::new(VelocityMonitorHandler::new(
+                    alert_sender.clone(),
+                    stores.clone(),
+                    config.clone(),
+                )),
+                _ => bail!("Unknown monitor handler: {}", name),
+            };
+            composite.register(name, handler)?;
+        }
+        info!("Enabled monitor handlers: {:?}", composite.names());
+        Ok(composite)
+    }
+
+    pub fn register(&mut self, name: &str, handler: Arc<dyn MonitorDispatcher>) -> Result<()> {
+        ensure!(
+            self.handlers.iter().all(|h| h.name != name),
+            "Monitor handler {} is already registered",
+            name
+        );
+        let interests = handler.event_interests();
+        self.handlers.push(RegisteredHandler {
+            name: name.to_string(),
+            handler,
+            interests,
+        });
+        Ok(())
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.handlers.iter().map(|h| h.name.as_str()).collect()
+    }
+}
+
+/// Run `dispatch` on every handler, logging the handlers which failed or panicked
+async fn dispatch_to<'a, F>(
+    method: &str,
+    handlers: impl IntoIterator<Item = &'a RegisteredHandler>,
+    dispatch: F,
+) -> Result<()>
+where
+    F: Fn(&'a dyn MonitorDispatcher) -> BoxFuture<'a, Result<()>>,
+{
+    let results = join_all(handlers.into_iter().map(|h| {
+        let future = AssertUnwindSafe(dispatch(h.handler.as_ref())).catch_unwind();
+        async move { (h.name.as_str(), future.await) }
+    }))
+    .await;
+
+    let mut failed = Vec::new();
+    for (name, result) in results {
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => {
+                error!("{} | handler {} failed: {}", method, name, e);
+                failed.push(name);
+            }
+            Err(_) => {
+                error!("{} | handler {} panicked", method, name);
+                failed.push(name);
+            }
+        }
+    }
+    ensure!(
+        failed.is_empty(),
+        "{} failed in handlers: {}",
+        method,
+        failed.join(", ")
+    );
+    Ok(())
+}
+
+#[async_trait::async_trait]
+impl MonitorDispatcher for CompositeMonitorHandler {
+    fn event_interests(&self) -> Vec<EventInterest> {
+        self.handlers
+            .iter()
+            .flat_map(|h| h.interests.iter().cloned())
+            .collect()
+    }
+
+    /// Only the handlers interested in the event receive it
+    async fn dispatch_event(&self, event: &TransactionEventView) -> Result<()> {
+        let creator = event.event_key.get_creator_address();
+        let handlers = self.handlers.iter().filter(|h| {
+            h.interests
+                .iter()
+                .any(|interest| interest.matches(&creator, &event.type_tag.0))
+        });
+        dispatch_to("dispatch_event", handlers, |handler| {
+            handler.dispatch_event(event)
+        })
+        .await
+    }
+
+    /// The handlers are given the transactions of the block, or none if they
+    /// cannot be fetched, the checks of the block itself still being run
+    async fn dispatch_block(&self, block: &BlockView) -> Result<()> {
+        let (txns, fetch_result) = if block.body.txn_hashes().is_empty() {
+            (Vec::new(), Ok(()))
+        } else {
+            match helper::extract_full_txn_from_block_view(
+                self.chain_reader.clone(),
+                vec![block.clone()],
+            )
+            .await
+            {
+                Ok(txns) => (txns, Ok(())),
+                Err(e) => (Vec::new(), Err(e)),
+            }
+        };
+        dispatch_to("dispatch_block", &self.handlers, |handler| {
+            handler.dispatch_block_txns(block, &txns)
+        })
+        .await?;
+        fetch_result
+    }
+
+    async fn dispatch_pending_txn(&self, txn_hash: HashValue) -> Result<()> {
+        dispatch_to("dispatch_pending_txn", &self.handlers, |handler| {
+            handler.dispatch_pending_txn(txn_hash)
+        })
+        .await
+    }
+
+    async fn dispatch_stcscan_index_exception(
+        &self,
+        curr_number: BlockNumber,
+        cached_number: BlockNumber,
+    ) -> Result<()> {
+        dispatch_to(
+            "dispatch_stcscan_index_exception",
+            &self.handlers,
+            |handler| handler.dispatch_stcscan_index_exception(curr_number, cached_number),
+        )
+        .await
+    }
+
+    async fn dispatch_node_health_exception(&self, alert: &NodeHealthAlert) -> Result<()> {
+        dispatch_to(
+            "dispatch_node_health_exception",
+            &self.handlers,
+            |handler| handler.dispatch_node_health_exception(alert),
+        )
+        .await
+    }
+
+    async fn dispatch_balance_change(&self, alert: &BalanceChangeAlert) -> Result<()> {
+        dispatch_to("dispatch_balance_change", &self.handlers, |handler| {
+            handler.dispatch_balance_change(alert)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use starcoin_rpc_api::types::{
+        ChainInfoView, SignedUserTransactionView, TransactionInfoView, TransactionView,
+    };
+    use starcoin_types::account_address::AccountAddress;
+    use std::sync::Mutex;
+
+    /// The handlers under test make no chain queries
+    struct NoChainReader;
+
+    impl ChainReader for NoChainReader {
+        fn get_chain_info(&self) -> Result<ChainInfoView> {
+            bail!("no chain")
+        }
+
+        fn get_blocks_by_number(
+            &self,
+            _number: Option<BlockNumber>,
+            _count: u64,
+        ) -> Result<Vec<BlockView>> {
+            bail!("no chain")
+        }
+
+        fn get_transaction(&self, _txn_hash: HashValue) -> Result<Option<TransactionView>> {
+            bail!("no chain")
+        }
+
+        fn get_block_txn_infos(&self, _block_hash: HashValue) -> Result<Vec<TransactionInfoView>> {
+            bail!("no chain")
+        }
+
+        fn get_pending_transaction(
+            &self,
+            _txn_hash: HashValue,
+        ) -> Result<Option<SignedUserTransactionView>> {
+            bail!("no chain")
+        }
+
+        fn get_balance(&self, _address: AccountAddress) -> Result<Option<u128>> {
+            bail!("no chain")
+        }
+    }
+
+    /// Records the pending transactions it receives, failing or panicking on demand
+    #[derive(Default)]
+    struct TestHandler {
+        received: Mutex<Vec<HashValue>>,
+        fail: bool,
+        panic: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl MonitorDispatcher for TestHandler {
+        async fn dispatch_pending_txn(&self, txn_hash: HashValue) -> Result<()> {
+            if self.panic {
+                panic!("handler panicked");
+            }
+            self.received.lock().unwrap().push(txn_hash);
+            if self.fail {
+                bail!("handler failed");
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handlers_are_isolated() -> Result<()> {
+        let failing = Arc::new(TestHandler {
+            fail: true,
+            ..TestHandler::default()
+        });
+        let panicking = Arc::new(TestHandler {
+            panic: true,
+            ..TestHandler::default()
+        });
+        let healthy = Arc::new(TestHandler::default());
+
+        let mut composite = CompositeMonitorHandler::new(Arc::new(NoChainReader));
+        composite.register("failing", failing.clone())?;
+        composite.register("panicking", panicking)?;
+        composite.register("healthy", healthy.clone())?;
+        assert!(composite
+            .register("healthy", Arc::new(TestHandler::default()))
+            .is_err());
+
+        let txn_hash = HashValue::sha3_256_of(b"pending");
+        let err = composite.dispatch_pending_txn(txn_hash).await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "dispatch_pending_txn failed in handlers: failing, panicking"
+        );
+        assert_eq!(*failing.received.lock().unwrap(), vec![txn_hash]);
+        assert_eq!(*healthy.received.lock().unwrap(), vec![txn_hash]);
+
+        // The composite keeps working after a panic
+        composite.dispatch_stcscan_index_exception(10, 1).await?;
+        Ok(())
+    }
+}
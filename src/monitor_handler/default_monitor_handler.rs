@@ -1,70 +1,450 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{config::Config, helper, monitor_dispatcher::MonitorDispatcher, telegram::TelegramBot};
+use crate::{
+    address_label::LabeledAddress,
+    alert_digest::DigestBuffer,
+    alert_sender::{Alert, AlertCategory, AlertSender, AlertTransfer, Severity},
+    balance_check::BalanceChangeAlert,
+    block_anomaly_check::{BlockAnomalyConfig, BlockProductionTracker, BlockSample},
+    chain_reader::ChainReader,
+    config::Config,
+    gas_check::{GasMonitor, GasMonitorConfig},
+    helper::{self, P2pTransfer},
+    locale::LocalizedText,
+    monitor_dispatcher::MonitorDispatcher,
+    node_health_check::NodeHealthAlert,
+    pending_txn_tracker::PendingTxnTracker,
+    price_provider::{self, FiatPrice, PriceProvider},
+    stores::Stores,
+    templates,
+};
 use anyhow::Result;
-use starcoin_rpc_api::types::{BlockView, TransactionEventView};
-use starcoin_rpc_client::RpcClient;
+use chrono::Utc;
+use starcoin_crypto::HashValue;
+use starcoin_rpc_api::types::{BlockView, SignedUserTransactionView};
 use starcoin_types::block::BlockNumber;
-use std::sync::Arc;
-use tracing::info;
+use std::sync::{Arc, Mutex};
+use tracing::{debug, error, info};
 
 pub struct DefaultMonitorHandler {
     config: Arc<Config>,
-    tg_bot: Arc<TelegramBot>,
-    rpc_client: Arc<RpcClient>,
+    alert_sender: Arc<dyn AlertSender>,
+    chain_reader: Arc<dyn ChainReader>,
+    price_provider: Arc<dyn PriceProvider>,
+    stores: Arc<Stores>,
+    block_anomaly_config: BlockAnomalyConfig,
+    block_tracker: Mutex<BlockProductionTracker>,
+    gas_config: GasMonitorConfig,
+    gas_monitor: Mutex<GasMonitor>,
+    pending_txns: Mutex<PendingTxnTracker>,
+    /// Large transfers held to be grouped into a digest
+    transfer_digest: Mutex<DigestBuffer<DigestTransfer>>,
+}
+
+/// A transfer to alert, with the descriptions of its addresses
+struct DigestTransfer {
+    block_number: BlockNumber,
+    txn_hash: String,
+    sender: LabeledAddress,
+    receiver: LabeledAddress,
+    amount: u128,
+    value: Option<FiatPrice>,
+    pending_warned: bool,
+    transfer: AlertTransfer,
+}
+
+impl DigestTransfer {
+    fn transfer_alert(&self) -> templates::TransferAlert<'_> {
+        templates::TransferAlert {
+            txn_hash: &self.txn_hash,
+            sender: &self.sender,
+            receiver: &self.receiver,
+            amount: self.amount,
+            value: self.value,
+        }
+    }
+
+    fn into_alert(self) -> Alert {
+        let msg = LocalizedText::new(|locale| {
+            templates::large_transfer(
+                locale,
+                self.block_number,
+                &self.transfer_alert(),
+                self.pending_warned,
+            )
+        });
+        Alert::new(AlertCategory::LargeTransfer, Severity::Warning, msg)
+            .with_txn_hash(&self.txn_hash)
+            .with_transfer(self.transfer)
+    }
 }
 
 impl DefaultMonitorHandler {
-    pub fn new(rpc_client: Arc<RpcClient>, tg_bot: Arc<TelegramBot>, config: Arc<Config>) -> Self {
+    pub fn new(
+        chain_reader: Arc<dyn ChainReader>,
+        alert_sender: Arc<dyn AlertSender>,
+        price_provider: Arc<dyn PriceProvider>,
+        stores: Arc<Stores>,
+        config: Arc<Config>,
+    ) -> Self {
+        let block_anomaly_config = BlockAnomalyConfig {
+            window_size: config.block_anomaly_window_size,
+            max_block_interval_secs: config.max_block_interval_secs,
+            max_difficulty_change_percent: config.max_difficulty_change_percent,
+            max_miner_share_percent: config.max_miner_share_percent,
+            ..BlockAnomalyConfig::default()
+        };
+        let gas_config = GasMonitorConfig {
+            max_failure_rate_percent: config.max_failure_rate_percent,
+            min_txns_for_failure_rate: config.min_txns_for_failure_rate,
+            max_block_gas_used: config.max_block_gas_used,
+            max_gas_price: config.max_gas_price,
+            ..GasMonitorConfig::default()
+        };
+        let transfer_digest = DigestBuffer::new(config.alert_digest_window_secs);
         Self {
-            rpc_client,
-            tg_bot,
+            chain_reader,
+            alert_sender,
+            price_provider,
+            stores,
             config,
+            block_anomaly_config,
+            block_tracker: Mutex::new(BlockProductionTracker::default()),
+            gas_config,
+            gas_monitor: Mutex::new(GasMonitor::default()),
+            pending_txns: Mutex::new(PendingTxnTracker::default()),
+            transfer_digest: Mutex::new(transfer_digest),
         }
     }
-}
 
-#[async_trait::async_trait]
-impl MonitorDispatcher for DefaultMonitorHandler {
-    async fn dispatch_event(&self, _event: &TransactionEventView) -> Result<()> {
+    /// Whether a transfer is large, by amount or by fiat value, along with its
+    /// fiat value if the price is known
+    async fn check_large_transfer(&self, transfer: &P2pTransfer) -> (bool, Option<FiatPrice>) {
+        let large_amount = transfer.amount > self.config.min_transaction_amount;
+        if !large_amount && self.config.min_transaction_usd.is_none() {
+            return (false, None);
+        }
+        let value = price_provider::fiat_value(
+            self.price_provider.as_ref(),
+            &transfer.token,
+            transfer.amount,
+        )
+        .await;
+        let large_value = self
+            .config
+            .min_transaction_usd
+            .zip(value)
+            .is_some_and(|(min_usd, value)| value.usd >= min_usd);
+        (large_amount || large_value, value)
+    }
+
+    fn alert_transfer(txn: &SignedUserTransactionView, transfer: &P2pTransfer) -> AlertTransfer {
+        AlertTransfer {
+            sender: helper::format_address(txn.raw_txn.sender),
+            receiver: transfer.receiver.clone(),
+            token: transfer.token.clone(),
+            amount: transfer.amount,
+        }
+    }
+
+    /// Whether a chat subscribed to a transfer below the alert thresholds
+    fn wants_transfer(&self, transfer: &AlertTransfer) -> bool {
+        self.stores
+            .subscriptions
+            .read(|subscriptions| subscriptions.wants_transfer(transfer))
+    }
+
+    /// The sender and receiver of a transfer, with their labels
+    fn describe_transfer(
+        &self,
+        txn: &SignedUserTransactionView,
+        transfer: &P2pTransfer,
+    ) -> (LabeledAddress, LabeledAddress) {
+        self.stores.labels.read(|labels| {
+            (
+                labels.labeled(&helper::format_address(txn.raw_txn.sender)),
+                labels.labeled(&transfer.receiver),
+            )
+        })
+    }
+
+    /// Whether the transfer of a transaction was alerted within
+    /// `ALERT_DEDUP_SECS`, as when its block is replayed after a restart or a
+    /// reconnection, or is held for the next digest
+    fn is_alerted(&self, txn_hash: &str, now: u64) -> bool {
+        self.transfer_digest
+            .lock()
+            .unwrap()
+            .holds(|t| t.txn_hash == txn_hash)
+            || self
+                .stores
+                .alerted_txns
+                .read(|alerted| alerted.contains(txn_hash, now, self.config.alert_dedup_secs))
+    }
+
+    /// Record the transactions whose transfers were queued, the alerts being
+    /// sent again on a replay if they cannot be saved
+    fn record_alerted<'a>(&self, txn_hashes: impl IntoIterator<Item = &'a str>) {
+        if self.config.alert_dedup_secs == 0 {
+            return;
+        }
+        let now = Utc::now().timestamp() as u64;
+        if let Err(e) = self
+            .stores
+            .alerted_txns
+            .update(|alerted| alerted.record(txn_hashes, now, self.config.alert_dedup_secs))
+        {
+            error!("Failed to save alerted transactions: {}", e);
+        }
+    }
+
+    /// Alert the large transfers of a block, those following the last alert
+    /// within `ALERT_DIGEST_WINDOW_SECS` being held and then grouped into a
+    /// digest
+    async fn notify_large_transfers(&self, transfers: Vec<DigestTransfer>) -> Result<()> {
+        let mut transfers = self
+            .transfer_digest
+            .lock()
+            .unwrap()
+            .push(transfers, Utc::now().timestamp() as u64);
+        let txn_hashes: Vec<String> = transfers.iter().map(|t| t.txn_hash.clone()).collect();
+        let alert = match transfers.len() {
+            0 => return Ok(()),
+            1 => transfers.remove(0).into_alert(),
+            _ => {
+                let digest: Vec<_> = transfers
+                    .iter()
+                    .map(|t| (t.block_number, t.transfer_alert(), t.pending_warned))
+                    .collect();
+                let msg =
+                    LocalizedText::new(|locale| templates::large_transfer_digest(locale, &digest));
+                // Identified by its first transaction
+                let alert = Alert::new(AlertCategory::LargeTransfer, Severity::Warning, msg)
+                    .with_txn_hash(&transfers[0].txn_hash);
+                transfers
+                    .into_iter()
+                    .fold(alert, |alert, t| alert.with_transfer(t.transfer))
+            }
+        };
+        self.alert_sender.send_alert(&alert).await?;
+        self.record_alerted(txn_hashes.iter().map(String::as_str));
         Ok(())
     }
 
-    async fn dispatch_block(&self, block_view: &BlockView) -> Result<()> {
+    async fn check_block_production(&self, block_view: &BlockView) -> Result<()> {
+        let header = &block_view.header;
+        let sample = BlockSample {
+            number: header.number.0,
+            timestamp: header.timestamp.0,
+            difficulty: header.difficulty.low_u128(),
+            miner: header.author.to_string(),
+        };
+        let anomalies = self.block_tracker.lock().unwrap().observe(
+            sample,
+            Utc::now().timestamp() as u64,
+            &self.block_anomaly_config,
+        );
+
+        for anomaly in anomalies {
+            self.alert_sender
+                .send_alert(
+                    &Alert::new(
+                        AlertCategory::BlockProduction,
+                        Severity::Warning,
+                        LocalizedText::new(|locale| templates::block_anomaly(locale, &anomaly)),
+                    )
+                    .with_kind(anomaly.key()),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn check_block_gas(
+        &self,
+        block_view: &BlockView,
+        full_txns: &[SignedUserTransactionView],
+    ) -> Result<()> {
+        let chain_reader = self.chain_reader.clone();
+        let block_hash = block_view.header.block_hash;
+        let txn_infos =
+            tokio::task::spawn_blocking(move || chain_reader.get_block_txn_infos(block_hash))
+                .await??;
+
+        let txns: Vec<_> = full_txns
+            .iter()
+            .filter_map(|txn| {
+                txn_infos
+                    .iter()
+                    .find(|info| info.transaction_hash == txn.transaction_hash)
+                    .map(|info| helper::build_transaction(&block_view.header, txn, info))
+            })
+            .collect();
+        let alerts = self.gas_monitor.lock().unwrap().check_block(
+            block_view.header.number.0,
+            &txns,
+            Utc::now().timestamp() as u64,
+            &self.gas_config,
+        );
+
+        for alert in alerts {
+            self.alert_sender
+                .send_alert(
+                    &Alert::new(
+                        AlertCategory::Gas,
+                        Severity::Warning,
+                        LocalizedText::new(|locale| templates::gas_alert(locale, &alert)),
+                    )
+                    .with_kind(alert.key()),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl MonitorDispatcher for DefaultMonitorHandler {
+    async fn dispatch_block_txns(
+        &self,
+        block_view: &BlockView,
+        full_txns: &[SignedUserTransactionView],
+    ) -> Result<()> {
         let height = block_view.header.number.0;
         info!("dispatch_block | New block arrived: {}", height);
-        if block_view.body.txn_hashes().is_empty() {
-            return Ok(());
+        // Large transfer alerts do not depend on the block production checks
+        if let Err(e) = self.check_block_production(block_view).await {
+            error!(
+                "dispatch_block | Failed to check production of block {}: {}",
+                height, e
+            );
+        }
+
+        if full_txns.is_empty() {
+            // Release the transfers held for a digest
+            return self.notify_large_transfers(Vec::new()).await;
         }
 
         info!(
             "dispatch_block | The block have transactions, count: {}",
-            block_view.body.txn_hashes().len()
+            full_txns.len()
         );
-        let full_txns = helper::extract_full_txn_from_block_view(
-            self.rpc_client.clone(),
-            vec![block_view.clone()],
-        )
-        .await?;
+        // Large transfer alerts do not depend on the transaction infos
+        if let Err(e) = self.check_block_gas(block_view, full_txns).await {
+            error!(
+                "dispatch_block | Failed to check gas of block {}: {}",
+                height, e
+            );
+        }
 
+        let mut large_transfers = Vec::new();
         for txn in full_txns {
             let txn_hash = txn.transaction_hash;
-            if let Some(amount) = helper::parse_txn_p2p_amount(txn)? {
-                if amount > self.config.min_transaction_amount {
-                    let msg = format!(
-                        "🚨[大交易事件告警]: 区块: https://stcscan.io/main/blocks/height/{:?}, 交易: https://stcscan.io/main/transactions/detail/{:?}, 额度: {:.9}",
-                        height,
+            if let Some(transfer) = helper::parse_txn_p2p_transfer(txn)? {
+                let (large, value) = self.check_large_transfer(&transfer).await;
+                let alert_transfer = Self::alert_transfer(txn, &transfer);
+                // Transfers below the thresholds may still be subscribed to
+                if !large && !self.wants_transfer(&alert_transfer) {
+                    continue;
+                }
+                let now = Utc::now().timestamp() as u64;
+                if self.is_alerted(&txn_hash.to_hex_literal(), now) {
+                    continue;
+                }
+                let (sender, receiver) = self.describe_transfer(txn, &transfer);
+                let pending_secs = self.pending_txns.lock().unwrap().confirm(txn_hash, now);
+                if let Some(pending_secs) = pending_secs {
+                    info!(
+                        "dispatch_block | Transaction {} confirmed after {} seconds in txpool",
                         txn_hash.to_hex_literal(),
-                        amount as f64 / 1e9
+                        pending_secs
                     );
-                    self.tg_bot.send_message(msg.as_str()).await?;
-                    // TODO: write into db
                 }
+                let digest_transfer = DigestTransfer {
+                    block_number: height,
+                    txn_hash: txn_hash.to_hex_literal(),
+                    sender,
+                    receiver,
+                    amount: transfer.amount,
+                    value,
+                    pending_warned: pending_secs.is_some(),
+                    transfer: alert_transfer,
+                };
+                if large {
+                    large_transfers.push(digest_transfer);
+                } else {
+                    // The large transfers still alerted if it cannot be sent
+                    let alert = digest_transfer.into_alert().subscribers_only(true);
+                    match self.alert_sender.send_alert(&alert).await {
+                        Ok(()) => self.record_alerted([txn_hash.to_hex_literal().as_str()]),
+                        Err(e) => error!(
+                            "dispatch_block | Failed to alert subscribed transfer {}: {}",
+                            txn_hash.to_hex_literal(),
+                            e
+                        ),
+                    }
+                }
+                // TODO: write into db
             }
         }
 
-        Ok(())
+        self.notify_large_transfers(large_transfers).await
+    }
+
+    async fn dispatch_pending_txn(&self, txn_hash: HashValue) -> Result<()> {
+        let chain_reader = self.chain_reader.clone();
+        let txn = match tokio::task::spawn_blocking(move || {
+            chain_reader.get_pending_transaction(txn_hash)
+        })
+        .await??
+        {
+            Some(txn) => txn,
+            None => {
+                // Already included in a block or dropped from the txpool
+                debug!(
+                    "dispatch_pending_txn | Transaction {} is not pending anymore",
+                    txn_hash.to_hex_literal()
+                );
+                return Ok(());
+            }
+        };
+
+        let transfer = match helper::parse_pending_txn_p2p_transfer(&txn)? {
+            Some(transfer) => transfer,
+            None => return Ok(()),
+        };
+        let (large, value) = self.check_large_transfer(&transfer).await;
+        let alert_transfer = Self::alert_transfer(&txn, &transfer);
+        if !large && !self.wants_transfer(&alert_transfer) {
+            return Ok(());
+        }
+        if !self
+            .pending_txns
+            .lock()
+            .unwrap()
+            .track(txn_hash, Utc::now().timestamp() as u64)
+        {
+            return Ok(());
+        }
+
+        let (sender, receiver) = self.describe_transfer(&txn, &transfer);
+        let transfer_alert = templates::TransferAlert {
+            txn_hash: &txn_hash.to_hex_literal(),
+            sender: &sender,
+            receiver: &receiver,
+            amount: transfer.amount,
+            value,
+        };
+        let msg =
+            LocalizedText::new(|locale| templates::pending_large_transfer(locale, &transfer_alert));
+        let alert = Alert::new(AlertCategory::LargeTransfer, Severity::Warning, msg)
+            .with_txn_hash(&txn_hash.to_hex_literal())
+            .with_transfer(alert_transfer)
+            .subscribers_only(!large);
+        self.alert_sender.send_alert(&alert).await
     }
 
     async fn dispatch_stcscan_index_exception(
@@ -72,12 +452,46 @@ impl MonitorDispatcher for DefaultMonitorHandler {
         curr_number: BlockNumber,
         cached_number: BlockNumber,
     ) -> Result<()> {
-        let msg = format!(
-            "🚨[索引差异过大事件告警]: 当前链上区块号: {}, StcScan 缓存的区块号: {}, 差额：{} 其差异过大可能导致StcScan索引追不上 ",
-            curr_number,
-            cached_number,
-            curr_number - cached_number
-        );
-        self.tg_bot.send_message(msg.as_str()).await
+        self.alert_sender
+            .send_alert(&Alert::new(
+                AlertCategory::IndexLag,
+                Severity::Warning,
+                LocalizedText::new(|locale| {
+                    templates::index_lag(locale, curr_number, cached_number)
+                }),
+            ))
+            .await
+    }
+
+    async fn dispatch_node_health_exception(&self, alert: &NodeHealthAlert) -> Result<()> {
+        // Diverging heads mean a fork, the other alerts a single node behind
+        let severity = match alert {
+            NodeHealthAlert::HeadHashMismatch { .. } => Severity::Critical,
+            _ => Severity::Warning,
+        };
+        self.alert_sender
+            .send_alert(
+                &Alert::new(
+                    AlertCategory::NodeHealth,
+                    severity,
+                    LocalizedText::new(|locale| templates::node_health(locale, alert)),
+                )
+                .with_kind(alert.key()),
+            )
+            .await
+    }
+
+    async fn dispatch_balance_change(&self, alert: &BalanceChangeAlert) -> Result<()> {
+        let address = self
+            .stores
+            .labels
+            .read(|labels| labels.labeled(&alert.address));
+        let msg = LocalizedText::new(|locale| templates::balance_change(locale, alert, &address));
+        self.alert_sender
+            .send_alert(
+                &Alert::new(AlertCategory::Balance, Severity::Warning, msg)
+                    .with_address(alert.address.clone()),
+            )
+            .await
     }
 }
@@ -0,0 +1,275 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    alert_sender::{Alert, AlertCategory, AlertSender, Severity},
+    alert_throttle::AlertThrottle,
+    config::Config,
+    event_filter::EventInterest,
+    governance_check::{
+        classify_framework_event, classify_script_function, GovernanceAction, CORE_ADDRESS,
+    },
+    helper,
+    locale::LocalizedText,
+    monitor_dispatcher::MonitorDispatcher,
+    supply_check::{
+        decode_token_supply_event, decode_treasury_withdraw_event, SupplyChangeKind,
+        SupplyCheckConfig, SupplyTracker,
+    },
+    templates,
+};
+use anyhow::Result;
+use chrono::Utc;
+use starcoin_rpc_api::types::{
+    BlockView, SignedUserTransactionView, TransactionEventView, TransactionPayloadView,
+};
+use starcoin_types::{
+    account_address::AccountAddress,
+    block::BlockNumber,
+    language_storage::{StructTag, TypeTag},
+};
+use std::sync::{Arc, Mutex};
+use tracing::{error, info};
+
+/// How long a package transaction is remembered once alerted
+const UPGRADE_DEDUP_SECS: u64 = 3600;
+
+/// Alerts on governance, config changes, code upgrades and token supply changes
+pub struct GovernanceMonitorHandler {
+    alert_sender: Arc<dyn AlertSender>,
+    supply_config: SupplyCheckConfig,
+    supply_tracker: Mutex<SupplyTracker>,
+    /// Package transactions already alerted, an upgrade being seen both as
+    /// its transaction and as its `UpgradeEvent`
+    alerted_upgrades: Mutex<AlertThrottle>,
+}
+
+impl GovernanceMonitorHandler {
+    pub fn new(alert_sender: Arc<dyn AlertSender>, config: Arc<Config>) -> Self {
+        let supply_config = SupplyCheckConfig {
+            limits: config.token_supply_limits.clone(),
+            window_secs: config.supply_window_secs,
+            ..SupplyCheckConfig::default()
+        };
+        Self {
+            alert_sender,
+            supply_config,
+            supply_tracker: Mutex::new(SupplyTracker::default()),
+            alerted_upgrades: Mutex::new(AlertThrottle::default()),
+        }
+    }
+
+    /// Whether the upgrade of a transaction was not alerted yet, recording it
+    fn first_upgrade_alert(&self, txn_hash: Option<&str>) -> bool {
+        match txn_hash {
+            Some(txn_hash) => self.alerted_upgrades.lock().unwrap().should_notify(
+                txn_hash,
+                Utc::now().timestamp() as u64,
+                UPGRADE_DEDUP_SECS,
+            ),
+            None => true,
+        }
+    }
+
+    async fn check_governance_event(
+        &self,
+        struct_tag: &StructTag,
+        event: &TransactionEventView,
+    ) -> Result<()> {
+        let type_params: Vec<String> = struct_tag
+            .type_params
+            .iter()
+            .map(|type_param| type_param.to_string())
+            .collect();
+        let action = match classify_framework_event(
+            struct_tag.module.as_str(),
+            struct_tag.name.as_str(),
+            &type_params,
+        ) {
+            Some(action) => action,
+            None => return Ok(()),
+        };
+        info!("Governance event detected: {:?}", action);
+        let txn_hash = event.transaction_hash.map(|hash| hash.to_hex_literal());
+        if action == GovernanceAction::ModuleUpgraded
+            && !self.first_upgrade_alert(txn_hash.as_deref())
+        {
+            return Ok(());
+        }
+
+        let data = match &event.decode_event_data {
+            Some(decoded) => serde_json::to_string(decoded)?,
+            None => format!("0x{}", hex::encode(&event.data.0)),
+        };
+        let msg = LocalizedText::new(|locale| {
+            templates::governance(
+                locale,
+                &action,
+                event.block_number.as_ref().map(|number| number.0),
+                txn_hash.as_deref(),
+                Some(data.clone()),
+            )
+        });
+        self.alert_sender
+            .send_alert(
+                &Alert::new(AlertCategory::Governance, Severity::Warning, msg)
+                    .with_kind(action.key()),
+            )
+            .await
+    }
+
+    async fn check_supply_event(
+        &self,
+        struct_tag: &StructTag,
+        event: &TransactionEventView,
+    ) -> Result<()> {
+        let (kind, token, amount) = match (struct_tag.module.as_str(), struct_tag.name.as_str()) {
+            ("Token", "MintEvent") => {
+                let (token, amount) = decode_token_supply_event(&event.data.0)?;
+                (SupplyChangeKind::Mint, token, amount)
+            }
+            ("Token", "BurnEvent") => {
+                let (token, amount) = decode_token_supply_event(&event.data.0)?;
+                (SupplyChangeKind::Burn, token, amount)
+            }
+            ("Treasury", "WithdrawEvent") => {
+                // The event does not name the token, `Treasury<TokenT>` is kept
+                // under the account of the token, which emits the event
+                let creator = helper::format_address(event.event_key.get_creator_address());
+                let token = match self
+                    .supply_config
+                    .limits
+                    .keys()
+                    .find(|token| token.starts_with(&format!("{}::", creator)))
+                {
+                    Some(token) => token.clone(),
+                    None => return Ok(()),
+                };
+                let amount = decode_treasury_withdraw_event(&event.data.0)?;
+                (SupplyChangeKind::TreasuryWithdraw, token, amount)
+            }
+            _ => return Ok(()),
+        };
+
+        let alert = match self.supply_tracker.lock().unwrap().observe(
+            kind,
+            &token,
+            amount,
+            Utc::now().timestamp() as u64,
+            &self.supply_config,
+        ) {
+            Some(alert) => alert,
+            None => return Ok(()),
+        };
+        let txn_hash = event.transaction_hash.map(|hash| hash.to_hex_literal());
+        let msg = LocalizedText::new(|locale| {
+            templates::token_supply(locale, &alert, amount, txn_hash.as_deref())
+        });
+        self.alert_sender
+            .send_alert(
+                &Alert::new(AlertCategory::Governance, Severity::Critical, msg)
+                    .with_kind(alert.kind.key()),
+            )
+            .await
+    }
+
+    /// Alert on package publishing and proposal execution transactions
+    async fn check_governance_txns(
+        &self,
+        height: BlockNumber,
+        full_txns: &[SignedUserTransactionView],
+    ) -> Result<()> {
+        for txn in full_txns {
+            let action = match txn.raw_txn.decoded_payload.as_ref() {
+                Some(TransactionPayloadView::Package(package)) => {
+                    Some(GovernanceAction::PackagePublished {
+                        package_address: helper::format_address(package.package_address),
+                        modules: package.modules.len(),
+                    })
+                }
+                Some(TransactionPayloadView::ScriptFunction(function_view)) => {
+                    classify_script_function(
+                        &function_view.module.0.to_string(),
+                        function_view.function.as_str(),
+                    )
+                }
+                _ => None,
+            };
+            if let Some(action) = action {
+                info!("Governance transaction detected: {:?}", action);
+                let txn_hash = txn.transaction_hash.to_hex_literal();
+                if matches!(action, GovernanceAction::PackagePublished { .. })
+                    && !self.first_upgrade_alert(Some(&txn_hash))
+                {
+                    continue;
+                }
+                let msg = LocalizedText::new(|locale| {
+                    templates::governance(locale, &action, Some(height), Some(&txn_hash), None)
+                });
+                // The other transactions are still checked
+                if let Err(e) = self
+                    .alert_sender
+                    .send_alert(
+                        &Alert::new(AlertCategory::Governance, Severity::Warning, msg)
+                            .with_kind(action.key()),
+                    )
+                    .await
+                {
+                    error!(
+                        "Failed to send governance alert of transaction {}: {}",
+                        txn_hash, e
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl MonitorDispatcher for GovernanceMonitorHandler {
+    /// Governance and supply events are emitted by the framework, and the
+    /// supply events of other tokens by the account of the token. Type tags are
+    /// left open as `ConfigChangeEvent<T>` is only matched with its type argument.
+    fn event_interests(&self) -> Vec<EventInterest> {
+        let mut addresses = vec![CORE_ADDRESS];
+        addresses.extend(
+            self.supply_config
+                .limits
+                .keys()
+                .filter_map(|token| token.split("::").next()),
+        );
+        vec![EventInterest {
+            addresses: addresses
+                .into_iter()
+                .filter_map(|address| AccountAddress::from_hex_literal(address).ok())
+                .collect(),
+            type_tags: Vec::new(),
+        }]
+    }
+
+    async fn dispatch_event(&self, event: &TransactionEventView) -> Result<()> {
+        let struct_tag = match &event.type_tag.0 {
+            TypeTag::Struct(struct_tag) => struct_tag,
+            _ => return Ok(()),
+        };
+        if helper::format_address(struct_tag.address) != CORE_ADDRESS {
+            return Ok(());
+        }
+
+        // A failed supply alert does not hold up the governance check
+        if let Err(e) = self.check_supply_event(struct_tag, event).await {
+            error!("Failed to check supply event {}: {}", struct_tag, e);
+        }
+        self.check_governance_event(struct_tag, event).await
+    }
+
+    async fn dispatch_block_txns(
+        &self,
+        block_view: &BlockView,
+        full_txns: &[SignedUserTransactionView],
+    ) -> Result<()> {
+        self.check_governance_txns(block_view.header.number.0, full_txns)
+            .await
+    }
+}
@@ -1,4 +1,7 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod composite_monitor_handler;
 pub mod default_monitor_handler;
+pub mod governance_monitor_handler;
+pub mod velocity_monitor_handler;
@@ -0,0 +1,137 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    alert_sender::{Alert, AlertCategory, AlertSender, Severity},
+    config::Config,
+    helper,
+    locale::LocalizedText,
+    monitor_dispatcher::MonitorDispatcher,
+    stores::Stores,
+    templates,
+    velocity_check::{AddressTransfer, TransferDirection, VelocityCheckConfig, VelocityTracker},
+};
+use anyhow::Result;
+use chrono::Utc;
+use starcoin_rpc_api::types::{BlockView, SignedUserTransactionView};
+use starcoin_types::account_address::AccountAddress;
+use std::sync::{Arc, Mutex};
+use tracing::error;
+
+/// Alerts when the transfers of an address add up beyond a limit within a
+/// window, whatever the amount of each transfer
+pub struct VelocityMonitorHandler {
+    alert_sender: Arc<dyn AlertSender>,
+    stores: Arc<Stores>,
+    velocity_config: VelocityCheckConfig,
+    velocity_tracker: Mutex<VelocityTracker>,
+}
+
+impl VelocityMonitorHandler {
+    pub fn new(
+        alert_sender: Arc<dyn AlertSender>,
+        stores: Arc<Stores>,
+        config: Arc<Config>,
+    ) -> Self {
+        let velocity_config = VelocityCheckConfig {
+            max_window_amount: config.velocity_max_amount,
+            window_secs: config.velocity_window_secs,
+            window_blocks: config.velocity_window_blocks,
+            ..VelocityCheckConfig::default()
+        };
+        Self {
+            alert_sender,
+            stores,
+            velocity_config,
+            velocity_tracker: Mutex::new(VelocityTracker::default()),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl MonitorDispatcher for VelocityMonitorHandler {
+    async fn dispatch_block_txns(
+        &self,
+        block_view: &BlockView,
+        full_txns: &[SignedUserTransactionView],
+    ) -> Result<()> {
+        let height = block_view.header.number.0;
+        let now = Utc::now().timestamp() as u64;
+        self.velocity_tracker
+            .lock()
+            .unwrap()
+            .prune(height, now, &self.velocity_config);
+        for txn in full_txns {
+            // The other transactions are still checked
+            let transfer = match helper::parse_txn_p2p_transfer(txn) {
+                Ok(Some(transfer)) => transfer,
+                Ok(None) => continue,
+                Err(e) => {
+                    error!(
+                        "Failed to parse transfer of transaction {}: {}",
+                        txn.transaction_hash.to_hex_literal(),
+                        e
+                    );
+                    continue;
+                }
+            };
+            let receiver = AccountAddress::from_hex_literal(&transfer.receiver)
+                .map(helper::format_address)
+                .unwrap_or(transfer.receiver);
+            let sides = [
+                (
+                    helper::format_address(txn.raw_txn.sender),
+                    TransferDirection::Sent,
+                ),
+                (receiver, TransferDirection::Received),
+            ];
+
+            for (address, direction) in sides {
+                let alert = self.velocity_tracker.lock().unwrap().observe(
+                    AddressTransfer {
+                        address,
+                        direction,
+                        token: transfer.token.clone(),
+                        amount: transfer.amount,
+                    },
+                    height,
+                    now,
+                    &self.velocity_config,
+                );
+                let alert = match alert {
+                    Some(alert) => alert,
+                    None => continue,
+                };
+                let address = self
+                    .stores
+                    .labels
+                    .read(|labels| labels.labeled(&alert.address));
+                let txn_hash = txn.transaction_hash.to_hex_literal();
+                let msg = LocalizedText::new(|locale| {
+                    templates::velocity(
+                        locale,
+                        &alert,
+                        &address,
+                        self.velocity_config.window_secs,
+                        self.velocity_config.window_blocks,
+                        &txn_hash,
+                    )
+                });
+                if let Err(e) = self
+                    .alert_sender
+                    .send_alert(
+                        &Alert::new(AlertCategory::Velocity, Severity::Warning, msg)
+                            .with_address(alert.address.clone()),
+                    )
+                    .await
+                {
+                    error!(
+                        "Failed to send velocity alert of transaction {}: {}",
+                        txn_hash, e
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,296 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::alert_throttle::AlertThrottle;
+use starcoin_crypto::HashValue;
+use starcoin_types::block::BlockNumber;
+use std::collections::HashMap;
+use tracing::info;
+
+/// Configuration for node health checking logic
+#[derive(Debug, Clone)]
+pub struct NodeHealthConfig {
+    /// Seconds a node head may stay at the same height before alerting
+    pub max_head_stale_secs: u64,
+    /// Blocks a node may lag behind the highest head before alerting
+    pub max_head_difference: u64,
+    /// Minimal seconds between two alerts of the same kind for the same node
+    pub max_notify_time_interval: u64,
+}
+
+impl Default for NodeHealthConfig {
+    fn default() -> Self {
+        Self {
+            max_head_stale_secs: 300,
+            max_head_difference: 10,
+            max_notify_time_interval: 600,
+        }
+    }
+}
+
+/// Head reported by a node in the latest poll
+#[derive(Debug, Clone)]
+pub struct NodeHead {
+    pub url: String,
+    pub number: Option<BlockNumber>,
+    pub hash: Option<HashValue>,
+    pub error: Option<String>,
+}
+
+impl NodeHead {
+    fn is_reachable(&self) -> bool {
+        self.number.is_some() && self.error.is_none()
+    }
+}
+
+/// State kept between two checks
+#[derive(Debug, Clone, Default)]
+pub struct NodeHealthState {
+    /// Node url -> head number and the time it was first seen
+    head_seen_at: HashMap<String, (BlockNumber, u64)>,
+    throttle: AlertThrottle,
+}
+
+/// Problems found in a check
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeHealthAlert {
+    /// The node head has not advanced for `stale_secs`
+    HeadStale {
+        url: String,
+        head_number: BlockNumber,
+        stale_secs: u64,
+        error: Option<String>,
+    },
+    /// The node is more than `max_head_difference` blocks behind another node
+    HeadLagging {
+        url: String,
+        head_number: BlockNumber,
+        best_url: String,
+        best_number: BlockNumber,
+    },
+    /// Nodes report different blocks at the same height
+    HeadHashMismatch {
+        number: BlockNumber,
+        heads: Vec<(String, HashValue)>,
+    },
+}
+
+impl NodeHealthAlert {
+    /// Identifies the alert for notification throttling
+    pub fn key(&self) -> String {
+        match self {
+            NodeHealthAlert::HeadStale { url, .. } => format!("stale:{}", url),
+            NodeHealthAlert::HeadLagging { url, .. } => format!("lagging:{}", url),
+            NodeHealthAlert::HeadHashMismatch { .. } => "hash_mismatch".to_string(),
+        }
+    }
+}
+
+fn check_head_stale(
+    heads: &[NodeHead],
+    now: u64,
+    state: &mut NodeHealthState,
+    config: &NodeHealthConfig,
+) -> Vec<NodeHealthAlert> {
+    let mut alerts = Vec::new();
+    for head in heads {
+        let number = match head.number {
+            Some(number) => number,
+            None => continue,
+        };
+        let seen = state
+            .head_seen_at
+            .entry(head.url.clone())
+            .or_insert((number, now));
+        if seen.0 != number {
+            *seen = (number, now);
+        }
+
+        let stale_secs = now.saturating_sub(seen.1);
+        if stale_secs > config.max_head_stale_secs {
+            alerts.push(NodeHealthAlert::HeadStale {
+                url: head.url.clone(),
+                head_number: number,
+                stale_secs,
+                error: head.error.clone(),
+            });
+        }
+    }
+    alerts
+}
+
+fn check_head_lagging(heads: &[NodeHead], config: &NodeHealthConfig) -> Vec<NodeHealthAlert> {
+    let reachable: Vec<(&NodeHead, BlockNumber)> = heads
+        .iter()
+        .filter(|head| head.is_reachable())
+        .filter_map(|head| head.number.map(|number| (head, number)))
+        .collect();
+    let (best, best_number) = match reachable.iter().max_by_key(|(_, number)| *number) {
+        Some((head, number)) => (*head, *number),
+        None => return Vec::new(),
+    };
+
+    reachable
+        .iter()
+        .filter(|(_, number)| best_number - number > config.max_head_difference)
+        .map(|(head, number)| NodeHealthAlert::HeadLagging {
+            url: head.url.clone(),
+            head_number: *number,
+            best_url: best.url.clone(),
+            best_number,
+        })
+        .collect()
+}
+
+fn check_head_hash(heads: &[NodeHead]) -> Vec<NodeHealthAlert> {
+    let mut by_number: HashMap<BlockNumber, Vec<(String, HashValue)>> = HashMap::new();
+    for head in heads.iter().filter(|head| head.is_reachable()) {
+        if let (Some(number), Some(hash)) = (head.number, head.hash) {
+            by_number
+                .entry(number)
+                .or_default()
+                .push((head.url.clone(), hash));
+        }
+    }
+
+    let mut alerts: Vec<NodeHealthAlert> = by_number
+        .into_iter()
+        .filter(|(_, heads)| heads.iter().any(|(_, hash)| *hash != heads[0].1))
+        .map(|(number, heads)| NodeHealthAlert::HeadHashMismatch { number, heads })
+        .collect();
+    alerts.sort_by_key(|alert| match alert {
+        NodeHealthAlert::HeadHashMismatch { number, .. } => *number,
+        _ => 0,
+    });
+    alerts
+}
+
+/// Check the heads of all nodes, returning the alerts to notify.
+///
+/// Alerts of the same kind for the same node are notified at most once every
+/// `max_notify_time_interval` seconds.
+pub fn check_node_health(
+    heads: &[NodeHead],
+    now: u64,
+    state: &mut NodeHealthState,
+    config: &NodeHealthConfig,
+) -> Vec<NodeHealthAlert> {
+    let mut alerts = check_head_stale(heads, now, state, config);
+    alerts.extend(check_head_lagging(heads, config));
+    alerts.extend(check_head_hash(heads));
+
+    alerts
+        .into_iter()
+        .filter(|alert| {
+            if !state
+                .throttle
+                .should_notify(&alert.key(), now, config.max_notify_time_interval)
+            {
+                return false;
+            }
+            info!("Node health exception detected: {:?}", alert);
+            true
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn head(url: &str, number: BlockNumber, hash: HashValue) -> NodeHead {
+        NodeHead {
+            url: url.to_string(),
+            number: Some(number),
+            hash: Some(hash),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_head_stale() {
+        let config = NodeHealthConfig::default();
+        let mut state = NodeHealthState::default();
+        let hash = HashValue::random();
+
+        let heads = vec![head("ws://node1", 100, hash)];
+        assert!(check_node_health(&heads, 1000, &mut state, &config).is_empty());
+        assert!(check_node_health(&heads, 1200, &mut state, &config).is_empty());
+
+        let alerts = check_node_health(&heads, 1301, &mut state, &config);
+        assert_eq!(
+            alerts,
+            vec![NodeHealthAlert::HeadStale {
+                url: "ws://node1".to_string(),
+                head_number: 100,
+                stale_secs: 301,
+                error: None,
+            }]
+        );
+
+        // Already notified, not again before max_notify_time_interval
+        assert!(check_node_health(&heads, 1400, &mut state, &config).is_empty());
+
+        // The head advanced, the staleness restarts
+        let heads = vec![head("ws://node1", 101, hash)];
+        assert!(check_node_health(&heads, 2000, &mut state, &config).is_empty());
+    }
+
+    #[test]
+    fn test_head_lagging() {
+        let config = NodeHealthConfig::default();
+        let mut state = NodeHealthState::default();
+        let heads = vec![
+            head("ws://node1", 100, HashValue::random()),
+            head("ws://node2", 89, HashValue::random()),
+            head("ws://node3", 95, HashValue::random()),
+        ];
+
+        let alerts = check_node_health(&heads, 1000, &mut state, &config);
+        assert_eq!(
+            alerts,
+            vec![NodeHealthAlert::HeadLagging {
+                url: "ws://node2".to_string(),
+                head_number: 89,
+                best_url: "ws://node1".to_string(),
+                best_number: 100,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_unreachable_node_not_lagging() {
+        let config = NodeHealthConfig::default();
+        let mut state = NodeHealthState::default();
+        let mut unreachable = head("ws://node2", 50, HashValue::random());
+        unreachable.error = Some("connection refused".to_string());
+        let heads = vec![head("ws://node1", 100, HashValue::random()), unreachable];
+
+        assert!(check_node_health(&heads, 1000, &mut state, &config).is_empty());
+    }
+
+    #[test]
+    fn test_head_hash_mismatch() {
+        let config = NodeHealthConfig::default();
+        let mut state = NodeHealthState::default();
+        let hash1 = HashValue::random();
+        let hash2 = HashValue::random();
+        let heads = vec![
+            head("ws://node1", 100, hash1),
+            head("ws://node2", 100, hash2),
+            head("ws://node3", 99, hash1),
+        ];
+
+        let alerts = check_node_health(&heads, 1000, &mut state, &config);
+        assert_eq!(
+            alerts,
+            vec![NodeHealthAlert::HeadHashMismatch {
+                number: 100,
+                heads: vec![
+                    ("ws://node1".to_string(), hash1),
+                    ("ws://node2".to_string(), hash2),
+                ],
+            }]
+        );
+    }
+}
@@ -0,0 +1,85 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    config::Config,
+    monitor_dispatcher::MonitorDispatcher,
+    node_health_check::{check_node_health, NodeHead, NodeHealthConfig, NodeHealthState},
+    rpc_pool::RpcClientPool,
+};
+use anyhow::Result;
+use chrono::Utc;
+use std::{sync::Arc, thread::JoinHandle, time::Duration};
+use tracing::{debug, error, info};
+
+/// Watches the heads of all configured nodes.
+///
+/// The heads come from the `chain_info` polled by the health check of the
+/// `RpcClientPool`, this monitor alerts when a head stops advancing or when the
+/// nodes disagree on the head height or hash.
+pub struct NodeHealthMonitor {
+    config: Arc<Config>,
+    dispatcher: Arc<dyn MonitorDispatcher>,
+    rpc_pool: Arc<RpcClientPool>,
+}
+
+impl NodeHealthMonitor {
+    pub fn new(
+        config: Arc<Config>,
+        dispatcher: Arc<dyn MonitorDispatcher>,
+        rpc_pool: Arc<RpcClientPool>,
+    ) -> Self {
+        Self {
+            config,
+            dispatcher,
+            rpc_pool,
+        }
+    }
+
+    pub fn run(&self) -> Result<JoinHandle<()>> {
+        let rpc_pool = self.rpc_pool.clone();
+        let dispatcher = self.dispatcher.clone();
+        let config = self.config.clone();
+        Ok(std::thread::spawn(move || {
+            info!("NodeHealthMonitor::run | entered");
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_io()
+                .enable_time()
+                .build()
+                .unwrap();
+
+            let mut state = NodeHealthState::default();
+            let health_config = NodeHealthConfig {
+                max_head_stale_secs: config.node_head_stale_secs,
+                max_head_difference: config.node_max_head_difference,
+                ..NodeHealthConfig::default()
+            };
+
+            loop {
+                std::thread::sleep(Duration::from_secs(config.node_health_check_interval));
+
+                let heads: Vec<NodeHead> = rpc_pool
+                    .node_health()
+                    .into_iter()
+                    .map(|(url, health)| NodeHead {
+                        url,
+                        number: health.head_number,
+                        hash: health.head_hash,
+                        error: health.last_error,
+                    })
+                    .collect();
+                debug!("NodeHealthMonitor::run | heads: {:?}", heads);
+
+                let now = Utc::now().timestamp() as u64;
+                for alert in check_node_health(&heads, now, &mut state, &health_config) {
+                    let dispatcher = dispatcher.clone();
+                    if let Err(e) = rt.block_on(async move {
+                        dispatcher.dispatch_node_health_exception(&alert).await
+                    }) {
+                        error!("Failed to dispatch node health exception: {}", e);
+                    }
+                }
+            }
+        }))
+    }
+}
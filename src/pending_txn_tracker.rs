@@ -0,0 +1,80 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use starcoin_crypto::HashValue;
+use std::collections::HashMap;
+
+/// Remembers the pending large transfers already alerted, so the alert of the
+/// confirmed transaction can refer to the pending one
+#[derive(Debug)]
+pub struct PendingTxnTracker {
+    /// Seconds after which a pending transaction is considered dropped
+    max_pending_secs: u64,
+    /// Transaction hash -> time it was first seen in the txpool
+    pending: HashMap<HashValue, u64>,
+}
+
+impl Default for PendingTxnTracker {
+    fn default() -> Self {
+        // Transactions expire from the txpool after an hour by default
+        Self::new(3600)
+    }
+}
+
+impl PendingTxnTracker {
+    pub fn new(max_pending_secs: u64) -> Self {
+        Self {
+            max_pending_secs,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Track a pending transaction, returning false if it is already tracked
+    pub fn track(&mut self, txn_hash: HashValue, now: u64) -> bool {
+        let max_pending_secs = self.max_pending_secs;
+        self.pending
+            .retain(|_, seen_at| now.saturating_sub(*seen_at) <= max_pending_secs);
+        if self.pending.contains_key(&txn_hash) {
+            return false;
+        }
+        self.pending.insert(txn_hash, now);
+        true
+    }
+
+    /// Stop tracking a transaction included in a block, returning how many
+    /// seconds it was pending if it was tracked
+    pub fn confirm(&mut self, txn_hash: HashValue, now: u64) -> Option<u64> {
+        self.pending
+            .remove(&txn_hash)
+            .map(|seen_at| now.saturating_sub(seen_at))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_track_and_confirm() {
+        let mut tracker = PendingTxnTracker::default();
+        let txn_hash = HashValue::random();
+
+        assert!(tracker.track(txn_hash, 1000));
+        // Seen again, e.g. after resubscribing
+        assert!(!tracker.track(txn_hash, 1010));
+
+        assert_eq!(tracker.confirm(txn_hash, 1030), Some(30));
+        assert_eq!(tracker.confirm(txn_hash, 1040), None);
+        assert_eq!(tracker.confirm(HashValue::random(), 1040), None);
+    }
+
+    #[test]
+    fn test_dropped_txn_expired() {
+        let mut tracker = PendingTxnTracker::new(60);
+        let dropped = HashValue::random();
+
+        assert!(tracker.track(dropped, 1000));
+        assert!(tracker.track(HashValue::random(), 1061));
+        assert_eq!(tracker.confirm(dropped, 1100), None);
+    }
+}
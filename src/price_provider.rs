@@ -0,0 +1,211 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{balance_monitor::STC_TOKEN_CODE, config::Config, supply_check::normalize_token_code};
+use anyhow::{bail, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::{error, info};
+
+/// Decimals of STC, whose smallest unit is the nano STC
+pub const STC_DECIMALS: u32 = 9;
+
+/// A slow price API must not hold up the alerts, sent without a fiat value
+/// once it times out
+const PRICE_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Price of one whole token, or a value, in fiat currencies
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct FiatPrice {
+    pub usd: f64,
+    pub cny: f64,
+}
+
+/// Price of a token, with the decimals of its amounts
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TokenPrice {
+    #[serde(flatten)]
+    pub price: FiatPrice,
+    pub decimals: u32,
+}
+
+impl TokenPrice {
+    /// Value of an amount in the smallest unit of the token
+    pub fn value_of(&self, amount: u128) -> FiatPrice {
+        let tokens = amount as f64 / 10f64.powi(self.decimals as i32);
+        FiatPrice {
+            usd: self.price.usd * tokens,
+            cny: self.price.cny * tokens,
+        }
+    }
+}
+
+impl std::fmt::Display for FiatPrice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "≈ ${:.2} / ¥{:.2}", self.usd, self.cny)
+    }
+}
+
+#[async_trait::async_trait]
+pub trait PriceProvider: Send + Sync {
+    /// Price of one whole token, `None` if the token has no known price
+    async fn get_price(&self, token: &str) -> Result<Option<TokenPrice>>;
+}
+
+/// Value of an amount of a token, `None` if the price is unknown. Failures are
+/// only logged, the value is an enrichment of the alerts.
+pub async fn fiat_value(
+    price_provider: &dyn PriceProvider,
+    token: &str,
+    amount: u128,
+) -> Option<FiatPrice> {
+    match price_provider.get_price(token).await {
+        Ok(price) => price.map(|price| price.value_of(amount)),
+        Err(e) => {
+            error!("Failed to get the price of {}: {}", token, e);
+            None
+        }
+    }
+}
+
+/// Fixed prices, from a JSON file mapping token codes to prices and decimals
+#[derive(Debug, Clone, Default)]
+pub struct StaticPriceProvider {
+    prices: HashMap<String, TokenPrice>,
+}
+
+impl StaticPriceProvider {
+    pub fn new(prices: HashMap<String, TokenPrice>) -> Result<Self> {
+        Ok(Self {
+            prices: prices
+                .into_iter()
+                .map(|(token, price)| Ok((normalize_token_code(&token)?, price)))
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let content = std::fs::read_to_string(path.as_ref())?;
+        Self::new(serde_json::from_str(&content)?)
+    }
+}
+
+#[async_trait::async_trait]
+impl PriceProvider for StaticPriceProvider {
+    async fn get_price(&self, token: &str) -> Result<Option<TokenPrice>> {
+        Ok(self.prices.get(token).copied())
+    }
+}
+
+/// Prices from the CoinGecko `simple/price` API, cached for a while
+pub struct HttpPriceProvider {
+    client: Client,
+    api_url: String,
+    /// Token code -> CoinGecko coin id and decimals of the token
+    coins: HashMap<String, (String, u32)>,
+    cache_duration: Duration,
+    cache: Mutex<HashMap<String, (Instant, TokenPrice)>>,
+}
+
+impl HttpPriceProvider {
+    pub fn new(api_url: &str, cache_duration: Duration) -> Result<Self> {
+        Ok(Self {
+            client: Client::builder().timeout(PRICE_REQUEST_TIMEOUT).build()?,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            coins: HashMap::from([(
+                STC_TOKEN_CODE.to_string(),
+                ("starcoin".to_string(), STC_DECIMALS),
+            )]),
+            cache_duration,
+            cache: Mutex::new(HashMap::new()),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl PriceProvider for HttpPriceProvider {
+    async fn get_price(&self, token: &str) -> Result<Option<TokenPrice>> {
+        let (coin_id, decimals) = match self.coins.get(token) {
+            Some(coin) => coin,
+            None => return Ok(None),
+        };
+        if let Some((fetched_at, price)) = self.cache.lock().unwrap().get(token) {
+            if fetched_at.elapsed() < self.cache_duration {
+                return Ok(Some(*price));
+            }
+        }
+
+        let url = format!(
+            "{}/simple/price?ids={}&vs_currencies=usd,cny",
+            self.api_url, coin_id
+        );
+        let prices: HashMap<String, FiatPrice> = self
+            .client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let price = prices.get(coin_id).map(|price| TokenPrice {
+            price: *price,
+            decimals: *decimals,
+        });
+        if let Some(price) = price {
+            info!("Price of {} updated: {:?}", token, price);
+            self.cache
+                .lock()
+                .unwrap()
+                .insert(token.to_string(), (Instant::now(), price));
+        }
+        Ok(price)
+    }
+}
+
+/// Create the provider selected by `PRICE_SOURCE`
+pub fn price_provider_from_config(config: &Config) -> Result<Arc<dyn PriceProvider>> {
+    Ok(match config.price_source.as_str() {
+        "coingecko" => Arc::new(HttpPriceProvider::new(
+            &config.price_api_url,
+            Duration::from_secs(config.price_cache_secs),
+        )?),
+        "file" => Arc::new(StaticPriceProvider::load(&config.price_file)?),
+        "none" => Arc::new(StaticPriceProvider::default()),
+        source => bail!("Unknown price source: {}", source),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_static_price_provider() -> Result<()> {
+        let provider: StaticPriceProvider = StaticPriceProvider::new(serde_json::from_str(
+            r#"{
+                "0x1::STC::STC": {"usd": 0.05, "cny": 0.35, "decimals": 9},
+                "0x1::XUSDT::XUSDT": {"usd": 1.0, "cny": 7.0, "decimals": 6}
+            }"#,
+        )?)?;
+        let value = fiat_value(&provider, STC_TOKEN_CODE, 2_000_000_000_000)
+            .await
+            .unwrap();
+        assert_eq!(value.to_string(), "≈ $100.00 / ¥700.00");
+        let value = fiat_value(
+            &provider,
+            &normalize_token_code("0x1::XUSDT::XUSDT")?,
+            2_000_000,
+        )
+        .await
+        .unwrap();
+        assert_eq!(value.to_string(), "≈ $2.00 / ¥14.00");
+        assert!(fiat_value(&provider, "0x1::BTC::BTC", 1).await.is_none());
+        Ok(())
+    }
+}
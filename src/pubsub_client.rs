@@ -1,45 +1,74 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::{chain_reader::ChainReader, event_filter::EventInterest, rpc_pool::RpcClientPool};
 use anyhow::Result;
 use futures::{TryStream, TryStreamExt};
-use starcoin_rpc_api::types::{pubsub::EventFilter, BlockView, TransactionEventView};
+use starcoin_crypto::HashValue;
+use starcoin_rpc_api::types::{BlockView, TransactionEventView};
 use starcoin_rpc_client::RpcClient;
-use std::sync::Arc;
+use starcoin_types::block::BlockNumber;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
-use tokio::io::AsyncBufReadExt;
-use tracing::info;
+use tokio::{io::AsyncBufReadExt, runtime::Runtime};
+use tracing::{error, info, warn};
+
+/// How often a subscription checks whether the primary node was switched
+const PRIMARY_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// Delay before subscribing again after a subscription ended or failed
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(3);
+/// Most blocks fetched after subscribing again, the older ones being skipped
+const MAX_BACKFILL_BLOCKS: u64 = 1000;
+const BACKFILL_BATCH_BLOCKS: u64 = 50;
 
 pub struct PubSubClient {
-    rpc_client: Arc<RpcClient>,
+    rpc_pool: Arc<RpcClientPool>,
+}
+
+/// Why `blocking_handle_notification` returned
+enum NotificationExit {
+    /// `q` was typed on stdin
+    Quit,
+    /// The stream ended, e.g. the connection was closed
+    StreamEnded,
+    /// The primary node was switched
+    PrimarySwitched,
 }
 
-fn blocking_handle_notification<T, F>(
+fn blocking_handle_notification<T, F, S>(
+    rt: &Runtime,
     mut event_stream: impl TryStream<Ok = T, Error = anyhow::Error> + Unpin,
     handle: F,
-) where
+    primary_switched: S,
+) -> NotificationExit
+where
     F: Fn(&T),
+    S: Fn() -> bool,
 {
-    let rt = tokio::runtime::Builder::new_multi_thread()
-        .enable_io()
-        .enable_time()
-        .build()
-        .expect("should able to create tokio runtime");
     let stdin = tokio::io::stdin();
     let mut lines = tokio::io::BufReader::new(stdin).lines();
     rt.block_on(async move {
+        let mut primary_check = tokio::time::interval(PRIMARY_CHECK_INTERVAL);
         loop {
             tokio::select! {
                maybe_quit = lines.next_line()  => {
                    if let Ok(Some(q)) = maybe_quit {
                        if q.as_str() == "q" {
-                           break;
+                           return NotificationExit::Quit;
                        }
                    }
                }
+               _ = primary_check.tick() => {
+                   if primary_switched() {
+                       return NotificationExit::PrimarySwitched;
+                   }
+               }
                try_event = event_stream.try_next() => {
                    match try_event {
-                        Ok(None) => break,
+                        Ok(None) => return NotificationExit::StreamEnded,
                         Ok(Some(evt)) => {
                             handle(&evt);
                             // println!("{}", display(&evt))
@@ -51,43 +80,196 @@ fn blocking_handle_notification<T, F>(
                }
             }
         }
-    });
+    })
 }
+
 impl PubSubClient {
-    pub fn new(rpc_client: Arc<RpcClient>) -> Result<Self> {
-        Ok(Self { rpc_client })
+    pub fn new(rpc_pool: Arc<RpcClientPool>) -> Result<Self> {
+        Ok(Self { rpc_pool })
+    }
+
+    /// Keep a subscription on the primary node, subscribing again on the new
+    /// primary whenever it is switched or the subscription ends. `missed` is
+    /// called once subscribed, what it returns being handled before the
+    /// notifications.
+    fn subscribe_with_failover<T, Sub, St, M, F>(
+        &self,
+        name: &str,
+        subscribe: Sub,
+        missed: M,
+        fun: F,
+    ) where
+        Sub: Fn(&RpcClient) -> Result<St>,
+        St: TryStream<Ok = T, Error = anyhow::Error> + Unpin,
+        M: Fn() -> Vec<T>,
+        F: Fn(&T),
+    {
+        let rt = tokio::runtime::Builder::new_multi_thread()
+            .enable_io()
+            .enable_time()
+            .build()
+            .expect("should able to create tokio runtime");
+
+        loop {
+            let generation = self.rpc_pool.generation();
+            // Keep the client alive as long as its subscription is used
+            let subscription = self.rpc_pool.primary().and_then(|client| {
+                let subscription = subscribe(client.as_ref())?;
+                Ok((client, subscription))
+            });
+
+            match subscription {
+                Ok((_client, subscription)) => {
+                    info!("{} | subscribed on {}", name, self.rpc_pool.primary_url());
+                    // Within the runtime, the handler spawning tasks
+                    let missed = missed();
+                    rt.block_on(async { missed.iter().for_each(&fun) });
+                    let exit = blocking_handle_notification(&rt, subscription, &fun, || {
+                        self.rpc_pool.generation() != generation
+                    });
+                    match exit {
+                        NotificationExit::Quit => return,
+                        NotificationExit::PrimarySwitched => {
+                            info!("{} | primary switched, subscribing again", name);
+                            continue;
+                        }
+                        NotificationExit::StreamEnded => {
+                            warn!(
+                                "{} | subscription on {} ended",
+                                name,
+                                self.rpc_pool.primary_url()
+                            );
+                            self.rpc_pool.report_primary_failure(&anyhow::anyhow!(
+                                "{} subscription ended",
+                                name
+                            ));
+                        }
+                    }
+                }
+                Err(e) => {
+                    error!(
+                        "{} | failed to subscribe on {}: {}",
+                        name,
+                        self.rpc_pool.primary_url(),
+                        e
+                    );
+                    self.rpc_pool.report_primary_failure(&e);
+                }
+            }
+
+            std::thread::sleep(RESUBSCRIBE_DELAY);
+        }
+    }
+
+    /// The blocks after `last_block` up to the head, produced while no
+    /// subscription was running. Those the new subscription also pushes are
+    /// not alerted twice, the alerted transfers being deduplicated.
+    fn missed_blocks(&self, last_block: BlockNumber) -> Vec<BlockView> {
+        let head = match self.rpc_pool.get_chain_info() {
+            Ok(chain_info) => chain_info.head.number.0,
+            Err(e) => {
+                error!("subscribe_new_blocks | failed to get the head: {}", e);
+                return Vec::new();
+            }
+        };
+        if head <= last_block {
+            return Vec::new();
+        }
+        let start = last_block + 1;
+        let end = head.min(last_block + MAX_BACKFILL_BLOCKS);
+        if end < head {
+            warn!(
+                "subscribe_new_blocks | {} blocks missed, only blocks {} to {} fetched",
+                head - last_block,
+                start,
+                end
+            );
+        }
+        info!(
+            "subscribe_new_blocks | fetching blocks {} to {} missed",
+            start, end
+        );
+
+        let mut blocks = Vec::new();
+        let mut number = start;
+        while number <= end {
+            let count = BACKFILL_BATCH_BLOCKS.min(end - number + 1);
+            match self.rpc_pool.get_blocks_by_number(Some(number), count) {
+                Ok(batch) => blocks.extend(batch),
+                Err(e) => {
+                    error!(
+                        "subscribe_new_blocks | failed to get blocks from {}: {}",
+                        number, e
+                    );
+                    break;
+                }
+            }
+            number += count;
+        }
+        blocks.retain(|block| (start..=end).contains(&block.header.number.0));
+        blocks.sort_by_key(|block| block.header.number.0);
+        blocks.dedup_by_key(|block| block.header.number.0);
+        blocks
     }
 
     pub fn subscribe_new_blocks<F: Fn(&BlockView)>(&self, fun: F) -> Result<()> {
         info!("subscribe_new_blocks | Entered");
 
-        let subscription = self.rpc_client.subscribe_new_blocks()?;
-
-        blocking_handle_notification(subscription, |bv| {
-            fun(bv);
-        });
+        // Number of the latest block handled, the blocks after it being
+        // fetched once subscribed again
+        let last_block: Mutex<Option<BlockNumber>> = Mutex::new(None);
+        self.subscribe_with_failover(
+            "subscribe_new_blocks",
+            |client| client.subscribe_new_blocks(),
+            || {
+                let last_block = *last_block.lock().unwrap();
+                last_block
+                    .map(|last_block| self.missed_blocks(last_block))
+                    .unwrap_or_default()
+            },
+            |block: &BlockView| {
+                let number = block.header.number.0;
+                let mut last_block = last_block.lock().unwrap();
+                *last_block = Some(last_block.map_or(number, |last| last.max(number)));
+                drop(last_block);
+                fun(block)
+            },
+        );
 
         info!("subscribe_new_blocks | Exited");
 
         Ok(())
     }
 
-    pub fn subscribe_new_events<F: Fn(&TransactionEventView)>(&self, fun: F) -> Result<()> {
-        info!("subscribe_new_events | Entered");
+    /// Subscribe to the hashes of the transactions entering the txpool
+    pub fn subscribe_new_pending_txns<F: Fn(&Vec<HashValue>)>(&self, fun: F) -> Result<()> {
+        info!("subscribe_new_pending_txns | Entered");
 
-        let event_filter = EventFilter {
-            from_block: None,
-            to_block: None,
-            event_keys: None,
-            addrs: None,
-            type_tags: None,
-            limit: None,
-        };
-        let subscription = self.rpc_client.subscribe_events(event_filter, true)?;
+        self.subscribe_with_failover(
+            "subscribe_new_pending_txns",
+            |client| client.subscribe_new_transactions(),
+            Vec::new,
+            fun,
+        );
+
+        info!("subscribe_new_pending_txns | Exited");
+        Ok(())
+    }
+
+    /// Subscribe to the events matching `interest`, filtered by the node
+    pub fn subscribe_new_events<F: Fn(&TransactionEventView)>(
+        &self,
+        interest: &EventInterest,
+        fun: F,
+    ) -> Result<()> {
+        info!("subscribe_new_events | Entered, interest: {:?}", interest);
 
-        blocking_handle_notification(subscription, |evt| {
-            fun(evt);
-        });
+        self.subscribe_with_failover(
+            "subscribe_new_events",
+            |client| client.subscribe_events(interest.to_event_filter(), true),
+            Vec::new,
+            fun,
+        );
 
         info!("subscribe_new_events | Exited");
         Ok(())
@@ -0,0 +1,349 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// How a message is formatted when sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextFormat {
+    /// No formatting, the links being written as their URL
+    Plain,
+    MarkdownV2,
+    Html,
+}
+
+impl FromStr for TextFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s.trim().to_lowercase().as_str() {
+            "plain" => TextFormat::Plain,
+            "markdownv2" => TextFormat::MarkdownV2,
+            "html" => TextFormat::Html,
+            _ => bail!("Unknown text format: {}", s),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Span {
+    Text(String),
+    Bold(String),
+    Code(String),
+    Link { text: String, url: String },
+}
+
+impl Span {
+    fn content(&self) -> &str {
+        match self {
+            Span::Text(text) | Span::Bold(text) | Span::Code(text) => text,
+            Span::Link { text, .. } => text,
+        }
+    }
+
+    /// The same kind of span with another content
+    fn with_content(&self, content: String) -> Span {
+        match self {
+            Span::Text(_) => Span::Text(content),
+            Span::Bold(_) => Span::Bold(content),
+            Span::Code(_) => Span::Code(content),
+            Span::Link { url, .. } => Span::Link {
+                text: content,
+                url: url.clone(),
+            },
+        }
+    }
+
+    /// Length once rendered, in the UTF-16 code units Telegram counts
+    fn rendered_len(&self, format: TextFormat) -> usize {
+        RichText {
+            spans: vec![self.clone()],
+        }
+        .render(format)
+        .encode_utf16()
+        .count()
+    }
+}
+
+/// A message made of spans, each escaped for the format it is rendered in
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RichText {
+    spans: Vec<Span>,
+}
+
+/// Characters escaped anywhere in MarkdownV2 text
+const MARKDOWN_V2_SPECIAL_CHARS: &[char] = &[
+    '_', '*', '[', ']', '(', ')', '~', '`', '>', '#', '+', '-', '=', '|', '{', '}', '.', '!', '\\',
+];
+
+fn escape_markdown_v2(text: &str, special_chars: &[char]) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if special_chars.contains(&ch) {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl RichText {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.spans.push(Span::Text(text.into()));
+        self
+    }
+
+    pub fn bold(mut self, text: impl Into<String>) -> Self {
+        self.spans.push(Span::Bold(text.into()));
+        self
+    }
+
+    pub fn code(mut self, text: impl Into<String>) -> Self {
+        self.spans.push(Span::Code(text.into()));
+        self
+    }
+
+    pub fn link(mut self, text: impl Into<String>, url: impl Into<String>) -> Self {
+        self.spans.push(Span::Link {
+            text: text.into(),
+            url: url.into(),
+        });
+        self
+    }
+
+    pub fn append(mut self, other: RichText) -> Self {
+        self.spans.extend(other.spans);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// Split into messages rendering to at most `limit` characters, cut
+    /// after a line where possible and inside a span only if it is longer
+    /// than `limit` by itself
+    pub fn split(&self, format: TextFormat, limit: usize) -> Vec<RichText> {
+        // Text spans are cut at their line ends, where the messages are split
+        let mut pieces = Vec::new();
+        for span in &self.spans {
+            let lines: Vec<Span> = match span {
+                Span::Text(text) => text
+                    .split_inclusive('\n')
+                    .map(|line| Span::Text(line.to_string()))
+                    .collect(),
+                span => vec![span.clone()],
+            };
+            for line in lines {
+                if line.rendered_len(format) > limit {
+                    pieces.extend(Self::split_span(&line, format, limit));
+                } else {
+                    pieces.push(line);
+                }
+            }
+        }
+
+        let mut parts = Vec::new();
+        let mut current: Vec<Span> = Vec::new();
+        let mut current_len = 0;
+        // Number of spans of `current` up to its last line end
+        let mut line_end = 0;
+        for piece in pieces {
+            let len = piece.rendered_len(format);
+            while current_len + len > limit && !current.is_empty() {
+                let rest = if line_end > 0 {
+                    current.split_off(line_end)
+                } else {
+                    Vec::new()
+                };
+                parts.push(RichText {
+                    spans: std::mem::replace(&mut current, rest),
+                });
+                current_len = current.iter().map(|span| span.rendered_len(format)).sum();
+                line_end = 0;
+            }
+            current_len += len;
+            current.push(piece);
+            if current
+                .last()
+                .is_some_and(|span| span.content().ends_with('\n'))
+            {
+                line_end = current.len();
+            }
+        }
+        if !current.is_empty() || parts.is_empty() {
+            parts.push(RichText { spans: current });
+        }
+        parts
+    }
+
+    /// Cut a span into spans of the same kind rendering to at most `limit`
+    /// characters each
+    fn split_span(span: &Span, format: TextFormat, limit: usize) -> Vec<Span> {
+        let overhead = span.with_content(String::new()).rendered_len(format);
+        let mut chunks = Vec::new();
+        let mut chunk = String::new();
+        let mut chunk_len = overhead;
+        for ch in span.content().chars() {
+            let len = span.with_content(ch.to_string()).rendered_len(format) - overhead;
+            if chunk_len + len > limit && !chunk.is_empty() {
+                chunks.push(span.with_content(std::mem::take(&mut chunk)));
+                chunk_len = overhead;
+            }
+            chunk.push(ch);
+            chunk_len += len;
+        }
+        if !chunk.is_empty() {
+            chunks.push(span.with_content(chunk));
+        }
+        chunks
+    }
+
+    pub fn render(&self, format: TextFormat) -> String {
+        self.spans
+            .iter()
+            .map(|span| match (format, span) {
+                (TextFormat::Plain, Span::Text(text))
+                | (TextFormat::Plain, Span::Bold(text))
+                | (TextFormat::Plain, Span::Code(text)) => text.clone(),
+                // Telegram links the URLs of plain messages by itself
+                (TextFormat::Plain, Span::Link { url, .. }) => url.clone(),
+                (TextFormat::MarkdownV2, Span::Text(text)) => {
+                    escape_markdown_v2(text, MARKDOWN_V2_SPECIAL_CHARS)
+                }
+                (TextFormat::MarkdownV2, Span::Bold(text)) => {
+                    format!("*{}*", escape_markdown_v2(text, MARKDOWN_V2_SPECIAL_CHARS))
+                }
+                (TextFormat::MarkdownV2, Span::Code(text)) => {
+                    format!("`{}`", escape_markdown_v2(text, &['`', '\\']))
+                }
+                (TextFormat::MarkdownV2, Span::Link { text, url }) => format!(
+                    "[{}]({})",
+                    escape_markdown_v2(text, MARKDOWN_V2_SPECIAL_CHARS),
+                    escape_markdown_v2(url, &[')', '\\'])
+                ),
+                (TextFormat::Html, Span::Text(text)) => escape_html(text),
+                (TextFormat::Html, Span::Bold(text)) => format!("<b>{}</b>", escape_html(text)),
+                (TextFormat::Html, Span::Code(text)) => {
+                    format!("<code>{}</code>", escape_html(text))
+                }
+                (TextFormat::Html, Span::Link { text, url }) => {
+                    format!("<a href=\"{}\">{}</a>", escape_html(url), escape_html(text))
+                }
+            })
+            .collect()
+    }
+}
+
+impl From<String> for RichText {
+    fn from(text: String) -> Self {
+        RichText::new().text(text)
+    }
+}
+
+impl From<&str> for RichText {
+    fn from(text: &str) -> Self {
+        RichText::new().text(text)
+    }
+}
+
+/// The plain text
+impl std::fmt::Display for RichText {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.render(TextFormat::Plain))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() -> Result<()> {
+        let text = RichText::new()
+            .text("🚨[大交易事件告警]: 区块: ")
+            .link("1000", "https://stcscan.io/main/blocks/height/1000")
+            .text(", 额度: ")
+            .bold("2000.5")
+            .text(" (≈ $1_000) <b>")
+            .code("0x1::STC::STC`");
+
+        assert_eq!(
+            text.render(TextFormat::MarkdownV2),
+            "🚨\\[大交易事件告警\\]: 区块: [1000](https://stcscan.io/main/blocks/height/1000), 额度: *2000\\.5* \\(≈ $1\\_000\\) <b\\>`0x1::STC::STC\\``"
+        );
+        assert_eq!(
+            text.render(TextFormat::Html),
+            "🚨[大交易事件告警]: 区块: <a href=\"https://stcscan.io/main/blocks/height/1000\">1000</a>, 额度: <b>2000.5</b> (≈ $1_000) &lt;b&gt;<code>0x1::STC::STC`</code>"
+        );
+        assert_eq!(
+            text.to_string(),
+            "🚨[大交易事件告警]: 区块: https://stcscan.io/main/blocks/height/1000, 额度: 2000.5 (≈ $1_000) <b>0x1::STC::STC`"
+        );
+
+        // Backslashes are escaped too
+        assert_eq!(
+            RichText::from("a\\b").render(TextFormat::MarkdownV2),
+            "a\\\\b"
+        );
+        assert_eq!("HTML".parse::<TextFormat>()?, TextFormat::Html);
+        assert!("markdown".parse::<TextFormat>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_split() {
+        let render = |parts: Vec<RichText>, format| {
+            parts
+                .iter()
+                .map(|part| part.render(format))
+                .collect::<Vec<_>>()
+        };
+        let text = RichText::new()
+            .text("line one\nline two\n")
+            .bold("bold")
+            .text(" end");
+        assert_eq!(
+            render(
+                text.split(TextFormat::MarkdownV2, 12),
+                TextFormat::MarkdownV2
+            ),
+            vec!["line one\n", "line two\n", "*bold* end"]
+        );
+        assert_eq!(
+            render(text.split(TextFormat::Plain, 100), TextFormat::Plain),
+            vec![text.to_string()]
+        );
+
+        // The end of a line is carried over to the next message with it
+        let text = RichText::new().text("ab\ncd").text(" ef");
+        assert_eq!(
+            render(text.split(TextFormat::Plain, 5), TextFormat::Plain),
+            vec!["ab\n", "cd ef"]
+        );
+
+        // A span longer than a message is cut, keeping its formatting
+        let text = RichText::new().code("a".repeat(10));
+        assert_eq!(
+            render(
+                text.split(TextFormat::MarkdownV2, 6),
+                TextFormat::MarkdownV2
+            ),
+            vec!["`aaaa`", "`aaaa`", "`aa`"]
+        );
+    }
+}
@@ -0,0 +1,458 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::chain_reader::ChainReader;
+use anyhow::{anyhow, Result};
+use starcoin_crypto::HashValue;
+use starcoin_rpc_api::types::{
+    BlockView, ChainInfoView, SignedUserTransactionView, TransactionInfoView, TransactionView,
+};
+use starcoin_rpc_client::RpcClient;
+use starcoin_types::{account_address::AccountAddress, block::BlockNumber};
+use std::{
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, RwLock,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+use tracing::{debug, error, info, warn};
+
+/// How long connecting to a node may take before it is deemed unreachable
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Messages of the RPC client errors caused by the connection, its errors
+/// reaching us as plain messages
+const TRANSPORT_ERROR_MESSAGES: [&str; 7] = [
+    "timed out",
+    "timeout",
+    "connection",
+    "channel closed",
+    "broken pipe",
+    "transport",
+    "websocket",
+];
+
+/// A query which failed on the way to or from the node, rather than being
+/// refused by the node
+#[derive(Debug)]
+struct TransportError(String);
+
+impl std::fmt::Display for TransportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for TransportError {}
+
+/// Whether an error comes from the connection to the node, so that another
+/// node may answer, unlike the errors returned by the node itself such as an
+/// invalid parameter
+fn is_transport_error(e: &anyhow::Error) -> bool {
+    if e.downcast_ref::<TransportError>().is_some()
+        || e.chain().any(|cause| cause.is::<std::io::Error>())
+    {
+        return true;
+    }
+    let message = e.to_string().to_lowercase();
+    TRANSPORT_ERROR_MESSAGES
+        .iter()
+        .any(|transport| message.contains(transport))
+}
+
+/// Run the query on each node in turn, from the primary on, moving on to the
+/// next one only on transport errors. Returns the index of the node which
+/// answered along with its answer.
+fn run_with_failover<T>(
+    node_count: usize,
+    primary: usize,
+    mut query: impl FnMut(usize) -> Result<T>,
+) -> Result<(usize, T)> {
+    let mut last_error = None;
+    for index in (0..node_count).map(|i| (primary + i) % node_count) {
+        match query(index) {
+            Ok(result) => return Ok((index, result)),
+            Err(e) if is_transport_error(&e) => last_error = Some(e),
+            Err(e) => return Err(e),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| anyhow!("No Starcoin RPC node available")))
+}
+
+/// Connect to a node, giving up after `CONNECT_TIMEOUT`
+fn connect(url: &str) -> Result<RpcClient> {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let connect_url = url.to_string();
+    std::thread::spawn(move || {
+        // The receiver is gone if the connection timed out
+        let _ = sender.send(RpcClient::connect_websocket(&connect_url));
+    });
+    match receiver.recv_timeout(CONNECT_TIMEOUT) {
+        Ok(result) => result.map_err(|e| TransportError(e.to_string()).into()),
+        Err(_) => Err(TransportError(format!(
+            "Connecting to {} timed out after {:?}",
+            url, CONNECT_TIMEOUT
+        ))
+        .into()),
+    }
+}
+
+/// Result of the latest health check of a node
+#[derive(Debug, Clone, Default)]
+pub struct NodeHealth {
+    pub head_number: Option<BlockNumber>,
+    pub head_hash: Option<HashValue>,
+    pub latency: Option<Duration>,
+    pub last_error: Option<String>,
+}
+
+impl NodeHealth {
+    pub fn is_healthy(&self) -> bool {
+        self.head_number.is_some() && self.last_error.is_none()
+    }
+}
+
+/// Pick the node to use as primary.
+///
+/// The current primary is kept as long as it is healthy and at most
+/// `max_head_lag` blocks behind the best head, to avoid flapping between nodes.
+/// Otherwise the fastest healthy node within `max_head_lag` of the best head is
+/// chosen. If no node is healthy the current primary is kept.
+pub fn select_primary(health: &[NodeHealth], current: usize, max_head_lag: u64) -> usize {
+    let best_head = match health
+        .iter()
+        .filter(|h| h.is_healthy())
+        .filter_map(|h| h.head_number)
+        .max()
+    {
+        Some(head) => head,
+        None => return current,
+    };
+    let within_lag =
+        |h: &NodeHealth| h.is_healthy() && best_head - h.head_number.unwrap_or(0) <= max_head_lag;
+
+    if health.get(current).is_some_and(within_lag) {
+        return current;
+    }
+
+    health
+        .iter()
+        .enumerate()
+        .filter(|(_, h)| within_lag(*h))
+        .min_by_key(|(_, h)| h.latency.unwrap_or(Duration::MAX))
+        .map(|(index, _)| index)
+        .unwrap_or(current)
+}
+
+struct RpcNode {
+    url: String,
+    client: RwLock<Option<Arc<RpcClient>>>,
+    health: RwLock<NodeHealth>,
+}
+
+impl RpcNode {
+    fn new(url: String) -> Self {
+        Self {
+            url,
+            client: RwLock::new(None),
+            health: RwLock::new(NodeHealth::default()),
+        }
+    }
+
+    /// Get the connected client, connecting first if needed
+    fn client(&self) -> Result<Arc<RpcClient>> {
+        if let Some(client) = self.client.read().unwrap().as_ref() {
+            return Ok(client.clone());
+        }
+
+        info!("RpcNode::client | connecting to {}", self.url);
+        let client = Arc::new(connect(&self.url)?);
+        *self.client.write().unwrap() = Some(client.clone());
+        Ok(client)
+    }
+
+    fn health(&self) -> NodeHealth {
+        self.health.read().unwrap().clone()
+    }
+
+    fn mark_failed(&self, e: &anyhow::Error) {
+        self.health.write().unwrap().last_error = Some(e.to_string());
+    }
+
+    fn check_health(&self) {
+        let started_at = Instant::now();
+        let result = self.client().and_then(|client| client.chain_info());
+
+        let mut health = self.health.write().unwrap();
+        match result {
+            Ok(chain_info) => {
+                health.head_number = Some(chain_info.head.number.0);
+                health.head_hash = Some(chain_info.head.block_hash);
+                health.latency = Some(started_at.elapsed());
+                health.last_error = None;
+                debug!(
+                    "RpcNode::check_health | {} head: {}, latency: {:?}",
+                    self.url, chain_info.head.number.0, health.latency
+                );
+            }
+            Err(e) => {
+                warn!("RpcNode::check_health | {} is unhealthy: {}", self.url, e);
+                health.latency = None;
+                health.last_error = Some(e.to_string());
+                // Drop the connection, it is re-established on the next check
+                *self.client.write().unwrap() = None;
+            }
+        }
+    }
+}
+
+/// A set of Starcoin nodes with one primary serving subscriptions and queries.
+///
+/// Nodes are health-checked by head height and latency, and the primary is
+/// switched when it disconnects or lags behind the others. Queries failing on
+/// the primary are retried on the other nodes.
+pub struct RpcClientPool {
+    nodes: Vec<RpcNode>,
+    primary: AtomicUsize,
+    generation: AtomicU64,
+    max_head_lag: u64,
+}
+
+impl RpcClientPool {
+    /// Connect to the nodes, failing only if none of them is reachable
+    pub fn connect(urls: &[String], max_head_lag: u64) -> Result<Self> {
+        if urls.is_empty() {
+            anyhow::bail!("No Starcoin RPC url configured");
+        }
+
+        let pool = Self {
+            nodes: urls.iter().cloned().map(RpcNode::new).collect(),
+            primary: AtomicUsize::new(0),
+            generation: AtomicU64::new(0),
+            max_head_lag,
+        };
+        pool.check_health();
+        if !pool.nodes.iter().any(|node| node.health().is_healthy()) {
+            anyhow::bail!("None of the Starcoin RPC nodes is reachable: {:?}", urls);
+        }
+        info!("RpcClientPool::connect | primary: {}", pool.primary_url());
+        Ok(pool)
+    }
+
+    pub fn primary_url(&self) -> &str {
+        &self.nodes[self.primary.load(Ordering::SeqCst)].url
+    }
+
+    /// Client of the primary node
+    pub fn primary(&self) -> Result<Arc<RpcClient>> {
+        self.nodes[self.primary.load(Ordering::SeqCst)].client()
+    }
+
+    /// Changes every time the primary is switched, subscribers use it to know
+    /// when to resubscribe
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    /// Url and latest health of every node
+    pub fn node_health(&self) -> Vec<(String, NodeHealth)> {
+        self.nodes
+            .iter()
+            .map(|node| (node.url.clone(), node.health()))
+            .collect()
+    }
+
+    /// Report that the primary failed outside of a query, e.g. its subscription broke
+    pub fn report_primary_failure(&self, e: &anyhow::Error) {
+        let current = self.primary.load(Ordering::SeqCst);
+        self.nodes[current].mark_failed(e);
+        self.reselect_primary();
+    }
+
+    /// Check all the nodes at once, so an unreachable node does not delay the
+    /// others
+    pub fn check_health(&self) {
+        std::thread::scope(|scope| {
+            for node in &self.nodes {
+                scope.spawn(|| node.check_health());
+            }
+        });
+        self.reselect_primary();
+    }
+
+    pub fn run_health_check(self: Arc<Self>, interval: Duration) -> Result<JoinHandle<()>> {
+        Ok(std::thread::spawn(move || {
+            info!("RpcClientPool::run_health_check | entered");
+            loop {
+                std::thread::sleep(interval);
+                self.check_health();
+            }
+        }))
+    }
+
+    fn reselect_primary(&self) {
+        let health: Vec<NodeHealth> = self.nodes.iter().map(|node| node.health()).collect();
+        let current = self.primary.load(Ordering::SeqCst);
+        let selected = select_primary(&health, current, self.max_head_lag);
+        if selected != current {
+            self.switch_primary(current, selected);
+        }
+    }
+
+    fn switch_primary(&self, from: usize, to: usize) {
+        if self
+            .primary
+            .compare_exchange(from, to, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok()
+        {
+            self.generation.fetch_add(1, Ordering::SeqCst);
+            warn!(
+                "RpcClientPool | primary switched from {} to {}",
+                self.nodes[from].url, self.nodes[to].url
+            );
+        }
+    }
+
+    /// Run the query on the primary, falling over to the other nodes when the
+    /// primary cannot be reached
+    fn call<T>(&self, query: impl Fn(&RpcClient) -> Result<T>) -> Result<T> {
+        let primary = self.primary.load(Ordering::SeqCst);
+        let (index, result) = run_with_failover(self.nodes.len(), primary, |index| {
+            let node = &self.nodes[index];
+            let result = node.client().and_then(|client| query(client.as_ref()));
+            if let Err(e) = &result {
+                error!("RpcClientPool::call | query on {} failed: {}", node.url, e);
+                if is_transport_error(e) {
+                    node.mark_failed(e);
+                }
+            }
+            result
+        })?;
+        if index != primary {
+            self.switch_primary(primary, index);
+        }
+        Ok(result)
+    }
+}
+
+impl ChainReader for RpcClientPool {
+    fn get_chain_info(&self) -> Result<ChainInfoView> {
+        self.call(|client| client.get_chain_info())
+    }
+
+    fn get_blocks_by_number(
+        &self,
+        number: Option<BlockNumber>,
+        count: u64,
+    ) -> Result<Vec<BlockView>> {
+        self.call(|client| client.get_blocks_by_number(number, count))
+    }
+
+    fn get_transaction(&self, txn_hash: HashValue) -> Result<Option<TransactionView>> {
+        self.call(|client| client.get_transaction(txn_hash))
+    }
+
+    fn get_block_txn_infos(&self, block_hash: HashValue) -> Result<Vec<TransactionInfoView>> {
+        self.call(|client| client.get_block_txn_infos(block_hash))
+    }
+
+    fn get_pending_transaction(
+        &self,
+        txn_hash: HashValue,
+    ) -> Result<Option<SignedUserTransactionView>> {
+        self.call(|client| client.get_pending_transaction(txn_hash))
+    }
+
+    fn get_balance(&self, address: AccountAddress) -> Result<Option<u128>> {
+        self.call(|client| client.get_balance(address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn healthy(head_number: BlockNumber, latency_ms: u64) -> NodeHealth {
+        NodeHealth {
+            head_number: Some(head_number),
+            head_hash: Some(HashValue::zero()),
+            latency: Some(Duration::from_millis(latency_ms)),
+            last_error: None,
+        }
+    }
+
+    fn unhealthy() -> NodeHealth {
+        NodeHealth {
+            last_error: Some("connection refused".to_string()),
+            ..NodeHealth::default()
+        }
+    }
+
+    #[test]
+    fn test_select_primary_keeps_current_within_lag() {
+        let health = vec![healthy(100, 500), healthy(103, 10)];
+        assert_eq!(select_primary(&health, 0, 5), 0);
+    }
+
+    #[test]
+    fn test_select_primary_switches_when_lagging() {
+        let health = vec![healthy(90, 10), healthy(100, 50), healthy(99, 20)];
+        assert_eq!(select_primary(&health, 0, 5), 2);
+    }
+
+    #[test]
+    fn test_select_primary_switches_when_disconnected() {
+        let health = vec![unhealthy(), healthy(100, 50)];
+        assert_eq!(select_primary(&health, 0, 5), 1);
+    }
+
+    #[test]
+    fn test_select_primary_keeps_current_when_all_unhealthy() {
+        let health = vec![unhealthy(), unhealthy()];
+        assert_eq!(select_primary(&health, 1, 5), 1);
+    }
+
+    #[test]
+    fn test_failover_on_transport_error() -> Result<()> {
+        let mut tried = Vec::new();
+        let (index, head) = run_with_failover(3, 1, |index| {
+            tried.push(index);
+            match index {
+                1 => Err(TransportError("Connecting to node 1 timed out".to_string()).into()),
+                2 => Err(std::io::Error::from(std::io::ErrorKind::ConnectionReset).into()),
+                _ => Ok(100),
+            }
+        })?;
+        assert_eq!((index, head), (0, 100));
+        assert_eq!(tried, vec![1, 2, 0]);
+
+        let err = run_with_failover(2, 0, |_| -> Result<u64> {
+            Err(anyhow!("Request timed out"))
+        })
+        .unwrap_err();
+        assert_eq!(err.to_string(), "Request timed out");
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_failover_on_rpc_error() {
+        let mut tried = Vec::new();
+        let err = run_with_failover(2, 0, |index| -> Result<u64> {
+            tried.push(index);
+            Err(anyhow!("Server returned rpc error Invalid params"))
+        })
+        .unwrap_err();
+        assert_eq!(err.to_string(), "Server returned rpc error Invalid params");
+        assert_eq!(tried, vec![0]);
+    }
+
+    #[test]
+    fn test_select_primary_ignores_unhealthy_best_head() {
+        let mut stale = healthy(200, 10);
+        stale.last_error = Some("timeout".to_string());
+        let health = vec![healthy(100, 50), stale];
+        assert_eq!(select_primary(&health, 0, 5), 0);
+    }
+}
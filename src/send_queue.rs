@@ -0,0 +1,480 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The outbound queue of the bot messages, sent within Telegram's rate
+//! limits. Messages longer than Telegram accepts are split, and the messages
+//! piling up for a chat while it is rate limited are merged.
+
+use crate::{
+    config::Config,
+    rich_text::{RichText, TextFormat},
+};
+use anyhow::{anyhow, Result};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use teloxide::{
+    prelude::*,
+    types::{InlineKeyboardMarkup, MessageId, ParseMode},
+    Bot, RequestError,
+};
+use tokio::sync::{mpsc, oneshot};
+use tracing::{error, info, warn};
+
+/// Longest message text Telegram accepts
+pub const MAX_MESSAGE_LEN: usize = 4096;
+
+/// Shortest time between two messages to the same chat
+const CHAT_MESSAGE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Attempts to send a message with its formatting, before a last one as
+/// plain text in case the formatting is what Telegram rejects
+const MAX_ATTEMPTS: u32 = 3;
+
+/// A message to a chat, or to a topic thread of a forum chat
+#[derive(Debug, Clone)]
+pub struct Outgoing {
+    pub chat_id: ChatId,
+    pub thread_id: Option<i32>,
+    pub text: RichText,
+    pub buttons: Option<InlineKeyboardMarkup>,
+    pub reply_to: Option<MessageId>,
+}
+
+impl Outgoing {
+    pub fn new(chat_id: ChatId, text: RichText) -> Self {
+        Self {
+            chat_id,
+            thread_id: None,
+            text,
+            buttons: None,
+            reply_to: None,
+        }
+    }
+
+    pub fn in_thread(mut self, thread_id: Option<i32>) -> Self {
+        self.thread_id = thread_id;
+        self
+    }
+
+    pub fn with_buttons(mut self, buttons: Option<InlineKeyboardMarkup>) -> Self {
+        self.buttons = buttons;
+        self
+    }
+
+    pub fn reply_to(mut self, message_id: MessageId) -> Self {
+        self.reply_to = Some(message_id);
+        self
+    }
+}
+
+struct Queued {
+    message: Outgoing,
+    attempts: u32,
+    /// Told the id of the message once sent, the message being kept apart
+    /// from the others
+    sent: Option<oneshot::Sender<Result<MessageId>>>,
+}
+
+impl Queued {
+    fn mergeable(&self) -> bool {
+        self.sent.is_none() && self.message.reply_to.is_none()
+    }
+}
+
+fn rendered_len(text: &RichText, format: TextFormat) -> usize {
+    text.render(format).encode_utf16().count()
+}
+
+/// Merge into `first` the messages queued after it to the same destination
+/// with the same buttons, in order and as long as they fit in one message
+fn coalesce(first: &mut Queued, queue: &mut VecDeque<Queued>, format: TextFormat) {
+    if !first.mergeable() {
+        return;
+    }
+    let separator = RichText::from("\n\n");
+    let mut len = rendered_len(&first.message.text, format);
+    let mut index = 0;
+    while index < queue.len() {
+        let next = &queue[index];
+        if next.message.chat_id != first.message.chat_id
+            || next.message.thread_id != first.message.thread_id
+        {
+            index += 1;
+            continue;
+        }
+        let next_len = rendered_len(&separator, format) + rendered_len(&next.message.text, format);
+        if !next.mergeable()
+            || next.message.buttons != first.message.buttons
+            || len + next_len > MAX_MESSAGE_LEN
+        {
+            break;
+        }
+        let next = queue.remove(index).unwrap();
+        first.message.text = std::mem::take(&mut first.message.text)
+            .append(separator.clone())
+            .append(next.message.text);
+        len += next_len;
+    }
+}
+
+/// Telegram's limits on the messages a bot sends
+#[derive(Debug, Clone, Copy)]
+pub struct SendRateLimits {
+    pub max_per_sec: usize,
+    /// To each group chat, the private chats only being limited by
+    /// `CHAT_MESSAGE_INTERVAL`
+    pub max_chat_per_min: usize,
+}
+
+impl SendRateLimits {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_per_sec: config.telegram_max_messages_per_sec.max(1),
+            max_chat_per_min: config.telegram_max_chat_messages_per_min.max(1),
+        }
+    }
+}
+
+/// When the next message may be sent, in all and to each chat
+#[derive(Debug)]
+struct RateLimiter {
+    limits: SendRateLimits,
+    /// Times of the latest messages, at most `max_per_sec` of them
+    sent: VecDeque<Instant>,
+    /// Times of the latest messages to each chat, at most
+    /// `max_chat_per_min` of them
+    chat_sent: HashMap<i64, VecDeque<Instant>>,
+    /// Until when Telegram asked not to send to a chat, or failed to
+    retry_after: HashMap<i64, Instant>,
+}
+
+impl RateLimiter {
+    fn new(limits: SendRateLimits) -> Self {
+        Self {
+            limits,
+            sent: VecDeque::new(),
+            chat_sent: HashMap::new(),
+            retry_after: HashMap::new(),
+        }
+    }
+
+    fn ready_at(&self, chat_id: i64, now: Instant) -> Instant {
+        let mut ready_at = now;
+        if self.sent.len() >= self.limits.max_per_sec {
+            ready_at = ready_at.max(self.sent[0] + Duration::from_secs(1));
+        }
+        if let Some(chat_sent) = self.chat_sent.get(&chat_id) {
+            if let Some(latest) = chat_sent.back() {
+                ready_at = ready_at.max(*latest + CHAT_MESSAGE_INTERVAL);
+            }
+            // Group chats have negative ids
+            if chat_id < 0 && chat_sent.len() >= self.limits.max_chat_per_min {
+                ready_at = ready_at.max(chat_sent[0] + Duration::from_secs(60));
+            }
+        }
+        if let Some(retry_after) = self.retry_after.get(&chat_id) {
+            ready_at = ready_at.max(*retry_after);
+        }
+        ready_at
+    }
+
+    fn record(&mut self, chat_id: i64, now: Instant) {
+        self.sent.push_back(now);
+        if self.sent.len() > self.limits.max_per_sec {
+            self.sent.pop_front();
+        }
+        let chat_sent = self.chat_sent.entry(chat_id).or_default();
+        chat_sent.push_back(now);
+        if chat_sent.len() > self.limits.max_chat_per_min {
+            chat_sent.pop_front();
+        }
+        self.retry_after.remove(&chat_id);
+    }
+
+    fn retry_after(&mut self, chat_id: i64, until: Instant) {
+        self.retry_after.insert(chat_id, until);
+    }
+}
+
+/// Queues the messages for `SendWorker`, which all the bot messages go
+/// through
+#[derive(Clone)]
+pub struct SendQueue {
+    sender: mpsc::UnboundedSender<Queued>,
+    format: TextFormat,
+}
+
+impl SendQueue {
+    pub fn new(bot: Arc<Bot>, config: &Config) -> (Self, SendWorker) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let worker = SendWorker {
+            bot,
+            format: config.telegram_text_format,
+            receiver,
+            queue: VecDeque::new(),
+            limiter: RateLimiter::new(SendRateLimits::from_config(config)),
+        };
+        (
+            Self {
+                sender,
+                format: config.telegram_text_format,
+            },
+            worker,
+        )
+    }
+
+    /// Queue a message, failures to send it being logged
+    pub fn push(&self, message: Outgoing) -> Result<()> {
+        self.enqueue(message, None)
+    }
+
+    /// Queue a message and wait until it is sent, for the id of its last
+    /// part
+    pub async fn send(&self, message: Outgoing) -> Result<MessageId> {
+        let (sent, receiver) = oneshot::channel();
+        self.enqueue(message, Some(sent))?;
+        receiver.await?
+    }
+
+    /// Queue the parts of a message, the buttons going with the last one
+    /// and the reply with the first one
+    fn enqueue(
+        &self,
+        message: Outgoing,
+        mut sent: Option<oneshot::Sender<Result<MessageId>>>,
+    ) -> Result<()> {
+        let parts = message.text.split(self.format, MAX_MESSAGE_LEN);
+        let count = parts.len();
+        for (index, text) in parts.into_iter().enumerate() {
+            let last = index + 1 == count;
+            let part = Outgoing {
+                chat_id: message.chat_id,
+                thread_id: message.thread_id,
+                text,
+                buttons: if last { message.buttons.clone() } else { None },
+                reply_to: if index == 0 { message.reply_to } else { None },
+            };
+            self.sender
+                .send(Queued {
+                    message: part,
+                    attempts: 0,
+                    sent: if last { sent.take() } else { None },
+                })
+                .map_err(|_| anyhow!("The send queue is closed"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Sends the queued messages one at a time, each as soon as the rate limits
+/// of its chat allow
+pub struct SendWorker {
+    bot: Arc<Bot>,
+    format: TextFormat,
+    receiver: mpsc::UnboundedReceiver<Queued>,
+    queue: VecDeque<Queued>,
+    limiter: RateLimiter,
+}
+
+impl SendWorker {
+    pub async fn run(mut self) {
+        loop {
+            while let Ok(queued) = self.receiver.try_recv() {
+                self.queue.push_back(queued);
+            }
+            let now = Instant::now();
+            // The earliest message of the first chat ready
+            let next = self
+                .queue
+                .iter()
+                .enumerate()
+                .map(|(index, queued)| {
+                    (self.limiter.ready_at(queued.message.chat_id.0, now), index)
+                })
+                .min();
+            match next {
+                None => match self.receiver.recv().await {
+                    Some(queued) => self.queue.push_back(queued),
+                    None => return,
+                },
+                Some((ready_at, _)) if ready_at > now => {
+                    tokio::select! {
+                        Some(queued) = self.receiver.recv() => self.queue.push_back(queued),
+                        _ = tokio::time::sleep_until(ready_at.into()) => {}
+                    }
+                }
+                Some((_, index)) => {
+                    let mut queued = self.queue.remove(index).unwrap();
+                    coalesce(&mut queued, &mut self.queue, self.format);
+                    self.send(queued).await;
+                }
+            }
+        }
+    }
+
+    async fn send(&mut self, mut queued: Queued) {
+        let chat_id = queued.message.chat_id;
+        self.limiter.record(chat_id.0, Instant::now());
+        let format = if queued.attempts < MAX_ATTEMPTS {
+            self.format
+        } else {
+            TextFormat::Plain
+        };
+        match self.request(&queued.message, format).await {
+            Ok(message_id) => {
+                info!("Message sent to chat {}", chat_id);
+                if let Some(sent) = queued.sent {
+                    let _ = sent.send(Ok(message_id));
+                }
+            }
+            Err(RequestError::RetryAfter(duration)) => {
+                warn!(
+                    "Rate limited by Telegram in chat {}, retrying after {:?}",
+                    chat_id, duration
+                );
+                self.limiter
+                    .retry_after(chat_id.0, Instant::now() + duration);
+                self.queue.push_front(queued);
+            }
+            Err(e) => {
+                queued.attempts += 1;
+                error!(
+                    "Failed to send message to chat {} (attempt {}/{}): {}",
+                    chat_id,
+                    queued.attempts,
+                    MAX_ATTEMPTS + 1,
+                    e
+                );
+                if queued.attempts > MAX_ATTEMPTS {
+                    if let Some(sent) = queued.sent {
+                        let _ = sent.send(Err(anyhow!(
+                            "Failed to send message after {} attempts: {}",
+                            queued.attempts,
+                            e
+                        )));
+                    }
+                    return;
+                }
+                // Wait before retrying
+                let backoff = Duration::from_secs(2 * queued.attempts as u64);
+                self.limiter
+                    .retry_after(chat_id.0, Instant::now() + backoff);
+                self.queue.push_front(queued);
+            }
+        }
+    }
+
+    async fn request(
+        &self,
+        message: &Outgoing,
+        format: TextFormat,
+    ) -> std::result::Result<MessageId, RequestError> {
+        let mut request = self
+            .bot
+            .send_message(message.chat_id, message.text.render(format));
+        request = match format {
+            TextFormat::Plain => request,
+            TextFormat::MarkdownV2 => request.parse_mode(ParseMode::MarkdownV2),
+            TextFormat::Html => request.parse_mode(ParseMode::Html),
+        };
+        if let Some(thread_id) = message.thread_id {
+            request = request.message_thread_id(thread_id);
+        }
+        if let Some(buttons) = message.buttons.clone() {
+            request = request.reply_markup(buttons);
+        }
+        if let Some(reply_to) = message.reply_to {
+            request = request.reply_to_message_id(reply_to);
+        }
+        Ok(request.await?.id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queued(chat_id: i64, text: &str) -> Queued {
+        Queued {
+            message: Outgoing::new(ChatId(chat_id), RichText::from(text)),
+            attempts: 0,
+            sent: None,
+        }
+    }
+
+    #[test]
+    fn test_rate_limiter() {
+        let mut limiter = RateLimiter::new(SendRateLimits {
+            max_per_sec: 2,
+            max_chat_per_min: 3,
+        });
+        let now = Instant::now();
+        assert_eq!(limiter.ready_at(-1, now), now);
+
+        // One message per second to a chat
+        limiter.record(-1, now);
+        assert_eq!(limiter.ready_at(-1, now), now + CHAT_MESSAGE_INTERVAL);
+        assert_eq!(limiter.ready_at(-2, now), now);
+        // And `max_per_sec` in all
+        limiter.record(-2, now);
+        assert_eq!(limiter.ready_at(-3, now), now + Duration::from_secs(1));
+
+        // `max_chat_per_min` to a group
+        let later = now + Duration::from_secs(10);
+        limiter.record(-1, later);
+        limiter.record(-1, later + Duration::from_secs(1));
+        assert_eq!(
+            limiter.ready_at(-1, later + Duration::from_secs(5)),
+            now + Duration::from_secs(60)
+        );
+        limiter.record(1, later);
+        limiter.record(1, later + Duration::from_secs(1));
+        limiter.record(1, later + Duration::from_secs(2));
+        assert_eq!(
+            limiter.ready_at(1, later + Duration::from_secs(5)),
+            later + Duration::from_secs(5)
+        );
+
+        // Until when Telegram asked to wait
+        let until = later + Duration::from_secs(30);
+        limiter.retry_after(1, until);
+        assert_eq!(limiter.ready_at(1, later + Duration::from_secs(5)), until);
+        limiter.record(1, until);
+        assert_eq!(limiter.ready_at(1, until), until + CHAT_MESSAGE_INTERVAL);
+    }
+
+    #[test]
+    fn test_coalesce() {
+        let mut queue = VecDeque::from(vec![
+            queued(-1, "b"),
+            queued(-2, "other chat"),
+            queued(-1, "c"),
+            Queued {
+                message: Outgoing::new(ChatId(-1), RichText::from("reply")).reply_to(MessageId(7)),
+                attempts: 0,
+                sent: None,
+            },
+            queued(-1, "after the reply"),
+        ]);
+        let mut first = queued(-1, "a");
+        coalesce(&mut first, &mut queue, TextFormat::MarkdownV2);
+        assert_eq!(first.message.text.to_string(), "a\n\nb\n\nc");
+        // The reply and what follows it stay in order
+        let texts: Vec<String> = queue
+            .iter()
+            .map(|queued| queued.message.text.to_string())
+            .collect();
+        assert_eq!(texts, vec!["other chat", "reply", "after the reply"]);
+
+        // Never beyond the length of a message
+        let long = "x".repeat(MAX_MESSAGE_LEN - 1);
+        let mut queue = VecDeque::from(vec![queued(-1, "y")]);
+        let mut first = queued(-1, &long);
+        coalesce(&mut first, &mut queue, TextFormat::Plain);
+        assert_eq!(first.message.text.to_string(), long);
+        assert_eq!(queue.len(), 1);
+    }
+}
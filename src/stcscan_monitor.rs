@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    chain_reader::ChainReader,
     config::Config,
     monitor_dispatcher::MonitorDispatcher,
     stcscan_monitor_index::{
@@ -14,7 +15,6 @@ use base64::Engine;
 
 use reqwest::Client;
 use serde_json::Value;
-use starcoin_rpc_client::RpcClient;
 use starcoin_types::block::BlockNumber;
 use std::{sync::Arc, thread::JoinHandle};
 use tracing::{debug, error, info};
@@ -22,7 +22,7 @@ use tracing::{debug, error, info};
 pub struct StcScanMonitor {
     config: Arc<Config>,
     dispatcher: Arc<dyn MonitorDispatcher>,
-    rpc_client: Arc<RpcClient>,
+    chain_reader: Arc<dyn ChainReader>,
 }
 
 /// get the cached index block number from elastic search,
@@ -117,17 +117,17 @@ impl StcScanMonitor {
     pub fn new(
         config: Arc<Config>,
         dispatcher: Arc<dyn MonitorDispatcher>,
-        rpc_client: Arc<RpcClient>,
+        chain_reader: Arc<dyn ChainReader>,
     ) -> Self {
         Self {
             config,
             dispatcher,
-            rpc_client,
+            chain_reader,
         }
     }
 
     pub fn run(&self) -> Result<JoinHandle<()>> {
-        let rpc_client = self.rpc_client.clone();
+        let chain_reader = self.chain_reader.clone();
         let dispatcher = self.dispatcher.clone();
         let config = self.config.clone();
         Ok(std::thread::spawn(move || {
@@ -142,7 +142,7 @@ impl StcScanMonitor {
 
             loop {
                 // Get current block number
-                let current_block_number = match rpc_client.chain_info() {
+                let current_block_number = match chain_reader.get_chain_info() {
                     Ok(chain_info) => chain_info.head.number.0,
                     Err(e) => {
                         error!("Failed to get current block number from RPC: {}", e);
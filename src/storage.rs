@@ -0,0 +1,119 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// Load a JSON file, or the default value if it does not exist yet
+pub fn load_json<T: DeserializeOwned + Default>(path: impl AsRef<Path>) -> Result<T> {
+    match std::fs::read_to_string(path.as_ref()) {
+        Ok(content) => Ok(serde_json::from_str(&content)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(T::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Save a value as JSON, replacing the file atomically so readers never see
+/// a partially written file
+pub fn save_json<T: Serialize>(path: impl AsRef<Path>, value: &T) -> Result<()> {
+    let path = path.as_ref();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp_path = path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, serde_json::to_string_pretty(value)?)?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// A JSON file kept in memory, loaded once and saved from the locked value,
+/// so that concurrent updates are never lost and readers do not hit the disk
+#[derive(Debug)]
+pub struct JsonStore<T> {
+    path: PathBuf,
+    value: Mutex<T>,
+}
+
+impl<T> JsonStore<T>
+where
+    T: Serialize + DeserializeOwned + Default + Clone + PartialEq,
+{
+    /// Load the file, starting from the default value if it does not exist yet
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let value = load_json(&path)?;
+        Ok(Self {
+            path,
+            value: Mutex::new(value),
+        })
+    }
+
+    pub fn read<R>(&self, read: impl FnOnce(&T) -> R) -> R {
+        read(&self.value.lock().unwrap())
+    }
+
+    /// Change the value, saving it if it changed. The change is dropped if the
+    /// file cannot be written.
+    pub fn update<R>(&self, update: impl FnOnce(&mut T) -> R) -> Result<R> {
+        let mut value = self.value.lock().unwrap();
+        let mut updated = value.clone();
+        let result = update(&mut updated);
+        if updated != *value {
+            save_json(&self.path, &updated)?;
+            *value = updated;
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_save_and_load_json() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("starcoin-monitor-{}", std::process::id()));
+        let path = dir.join("storage_test.json");
+
+        let missing: HashMap<String, u64> = load_json(&path)?;
+        assert!(missing.is_empty());
+
+        let value = HashMap::from([("a".to_string(), 1u64)]);
+        save_json(&path, &value)?;
+        let loaded: HashMap<String, u64> = load_json(&path)?;
+        assert_eq!(loaded, value);
+
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_store() -> Result<()> {
+        let dir =
+            std::env::temp_dir().join(format!("starcoin-monitor-store-{}", std::process::id()));
+        let path = dir.join("store_test.json");
+
+        let store: JsonStore<HashMap<String, u64>> = JsonStore::open(&path)?;
+        assert_eq!(
+            store.update(|value| value.insert("a".to_string(), 1))?,
+            None
+        );
+        assert_eq!(store.read(|value| value.get("a").copied()), Some(1));
+        // Unchanged values are not written
+        std::fs::remove_file(&path)?;
+        store.update(|value| value.len())?;
+        assert!(!path.exists());
+
+        store.update(|value| value.insert("b".to_string(), 2))?;
+        let reopened: JsonStore<HashMap<String, u64>> = JsonStore::open(&path)?;
+        assert_eq!(reopened.read(|value| value.len()), 2);
+
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
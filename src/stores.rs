@@ -0,0 +1,32 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    address_label::AddressLabels, alert_dedup::AlertedTxns, alert_mute::AlertMutes,
+    locale::ChatLocales, storage::JsonStore, subscription::Subscriptions,
+};
+use anyhow::Result;
+use std::path::Path;
+
+/// The files of `DATA_DIR` edited from the bot and read by the monitor
+/// handlers, loaded once at startup and shared by all of them
+pub struct Stores {
+    pub alerted_txns: JsonStore<AlertedTxns>,
+    pub labels: JsonStore<AddressLabels>,
+    pub locales: JsonStore<ChatLocales>,
+    pub mutes: JsonStore<AlertMutes>,
+    pub subscriptions: JsonStore<Subscriptions>,
+}
+
+impl Stores {
+    pub fn open(data_dir: &str) -> Result<Self> {
+        let path = |file_name: &str| Path::new(data_dir).join(file_name);
+        Ok(Self {
+            alerted_txns: JsonStore::open(path(AlertedTxns::FILE_NAME))?,
+            labels: JsonStore::open(path(AddressLabels::FILE_NAME))?,
+            locales: JsonStore::open(path(ChatLocales::FILE_NAME))?,
+            mutes: JsonStore::open(path(AlertMutes::FILE_NAME))?,
+            subscriptions: JsonStore::open(path(Subscriptions::FILE_NAME))?,
+        })
+    }
+}
@@ -0,0 +1,418 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    alert_routing::AlertDestination,
+    alert_sender::{Alert, AlertCategory, AlertTransfer, Severity},
+    balance_monitor::STC_TOKEN_CODE,
+    helper,
+    price_provider::STC_DECIMALS,
+    supply_check::normalize_token_code,
+};
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use starcoin_types::account_address::AccountAddress;
+
+/// What a chat subscribed to, with its own threshold
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Subscription {
+    /// The alerts of a category, from a severity up
+    Category {
+        category: AlertCategory,
+        min_severity: Severity,
+    },
+    /// The alerts about an address, including its STC transfers of at least
+    /// `min_amount` nano STC even if below the alert thresholds. Without an
+    /// amount, only the transfers above the thresholds.
+    Address {
+        address: String,
+        min_amount: Option<u128>,
+    },
+    /// The transfers of a token of at least `min_amount`, in the smallest unit
+    /// of the token, or above the alert thresholds without an amount
+    Token {
+        token: String,
+        min_amount: Option<u128>,
+        #[serde(default = "stc_decimals")]
+        decimals: u32,
+    },
+}
+
+fn stc_decimals() -> u32 {
+    STC_DECIMALS
+}
+
+fn normalize_address(address: &str) -> String {
+    AccountAddress::from_hex_literal(address)
+        .map(helper::format_address)
+        .unwrap_or_else(|_| address.to_string())
+}
+
+fn normalize_token(token: &str) -> String {
+    normalize_token_code(token).unwrap_or_else(|_| token.to_string())
+}
+
+/// Amount in whole tokens to the smallest unit of a token with `decimals`
+fn parse_amount(amount: &str, decimals: u32) -> Result<u128> {
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if fraction.len() > decimals as usize || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        bail!("Invalid amount: {}", amount);
+    }
+    let unit = 10u128
+        .checked_pow(decimals)
+        .ok_or_else(|| anyhow!("Invalid decimals: {}", decimals))?;
+    let whole: u128 = whole.parse()?;
+    let fraction: u128 = match decimals {
+        0 => 0,
+        _ => format!("{:0<1$}", fraction, decimals as usize).parse()?,
+    };
+    whole
+        .checked_mul(unit)
+        .and_then(|whole| whole.checked_add(fraction))
+        .ok_or_else(|| anyhow!("Invalid amount: {}", amount))
+}
+
+/// Amount in the smallest unit of a token with `decimals` to whole tokens
+fn format_amount(amount: u128, decimals: u32) -> String {
+    format!(
+        "{:.*}",
+        decimals as usize,
+        amount as f64 / 10f64.powi(decimals as i32)
+    )
+}
+
+impl Subscription {
+    pub const USAGE: &'static str = "category <category> [severity] | address <address> [min_amount] | token <token_code> [min_amount [decimals]]";
+
+    /// Parse the arguments of `/subscribe`, amounts being in whole tokens: STC
+    /// for an address, the token itself, whose decimals are needed unless it
+    /// is STC, for a token
+    pub fn parse(args: &[&str]) -> Result<Self> {
+        match args {
+            ["category", category] | ["category", category, _] => Ok(Subscription::Category {
+                category: category.parse()?,
+                min_severity: args.get(2).map_or(Ok(Severity::Info), |s| s.parse())?,
+            }),
+            ["address", address] | ["address", address, _] => Ok(Subscription::Address {
+                address: AccountAddress::from_hex_literal(address)
+                    .map(helper::format_address)
+                    .map_err(|_| anyhow!("Invalid address: {}", address))?,
+                min_amount: args
+                    .get(2)
+                    .map(|amount| parse_amount(amount, STC_DECIMALS))
+                    .transpose()?,
+            }),
+            ["token", token] | ["token", token, _] | ["token", token, _, _] => {
+                let token = normalize_token_code(token)?;
+                let decimals = match args.get(3) {
+                    Some(decimals) => decimals
+                        .parse()
+                        .map_err(|_| anyhow!("Invalid decimals: {}", decimals))?,
+                    None if token == STC_TOKEN_CODE || args.len() == 2 => STC_DECIMALS,
+                    None => bail!("The decimals of {} are needed for an amount", token),
+                };
+                Ok(Subscription::Token {
+                    min_amount: args
+                        .get(2)
+                        .map(|amount| parse_amount(amount, decimals))
+                        .transpose()?,
+                    token,
+                    decimals,
+                })
+            }
+            _ => bail!("Usage: /subscribe {}", Self::USAGE),
+        }
+    }
+
+    /// Whether both are on the same category, address or token, whatever
+    /// their thresholds
+    pub fn same_target(&self, other: &Subscription) -> bool {
+        match (self, other) {
+            (
+                Subscription::Category { category, .. },
+                Subscription::Category {
+                    category: other, ..
+                },
+            ) => category == other,
+            (
+                Subscription::Address { address, .. },
+                Subscription::Address { address: other, .. },
+            ) => address == other,
+            (Subscription::Token { token, .. }, Subscription::Token { token: other, .. }) => {
+                token == other
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether a transfer is of the address or token subscribed to, whatever
+    /// its amount
+    fn targets_transfer(&self, transfer: &AlertTransfer) -> bool {
+        match self {
+            Subscription::Category { .. } => false,
+            Subscription::Address { address, .. } => {
+                normalize_address(&transfer.sender) == *address
+                    || normalize_address(&transfer.receiver) == *address
+            }
+            Subscription::Token { token, .. } => normalize_token(&transfer.token) == *token,
+        }
+    }
+
+    /// Whether a transfer is of interest, even below the alert thresholds
+    pub fn matches_transfer(&self, transfer: &AlertTransfer) -> bool {
+        let min_amount = match self {
+            Subscription::Category { .. } => None,
+            // The amounts of the other tokens cannot be compared to STC
+            Subscription::Address { min_amount, .. } => {
+                min_amount.filter(|_| normalize_token(&transfer.token) == STC_TOKEN_CODE)
+            }
+            Subscription::Token { min_amount, .. } => *min_amount,
+        };
+        min_amount.is_some_and(|min_amount| transfer.amount >= min_amount)
+            && self.targets_transfer(transfer)
+    }
+
+    pub fn matches(&self, alert: &Alert) -> bool {
+        // The transfers of an alert not only for subscribers are above the
+        // alert thresholds
+        if alert.transfers.iter().any(|transfer| {
+            self.matches_transfer(transfer)
+                || (!alert.subscribers_only && self.targets_transfer(transfer))
+        }) {
+            return true;
+        }
+        match self {
+            Subscription::Category {
+                category,
+                min_severity,
+            } => alert.category == *category && alert.severity >= *min_severity,
+            // Alerts about an address without a transfer, its balance for example
+            Subscription::Address { address, .. } => {
+                alert.transfers.is_empty()
+                    && alert
+                        .addresses
+                        .iter()
+                        .any(|a| normalize_address(a) == *address)
+            }
+            Subscription::Token { .. } => false,
+        }
+    }
+}
+
+impl std::fmt::Display for Subscription {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Subscription::Category {
+                category,
+                min_severity,
+            } => write!(f, "category {} {}", category, min_severity),
+            Subscription::Address {
+                address,
+                min_amount: None,
+            } => write!(f, "address {}", address),
+            Subscription::Address {
+                address,
+                min_amount: Some(min_amount),
+            } => write!(
+                f,
+                "address {} {}",
+                address,
+                format_amount(*min_amount, STC_DECIMALS)
+            ),
+            Subscription::Token {
+                token,
+                min_amount: None,
+                ..
+            } => write!(f, "token {}", token),
+            Subscription::Token {
+                token,
+                min_amount: Some(min_amount),
+                decimals,
+            } => write!(
+                f,
+                "token {} {}",
+                token,
+                format_amount(*min_amount, *decimals)
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatSubscription {
+    pub destination: AlertDestination,
+    pub subscription: Subscription,
+}
+
+/// Subscriptions of the chats, kept in `DATA_DIR` and edited with
+/// `/subscribe` and `/unsubscribe`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Subscriptions {
+    subscriptions: Vec<ChatSubscription>,
+}
+
+impl Subscriptions {
+    pub const FILE_NAME: &'static str = "subscriptions.json";
+
+    /// Add a subscription, replacing the one of the chat on the same target
+    pub fn subscribe(&mut self, destination: AlertDestination, subscription: Subscription) {
+        self.subscriptions
+            .retain(|s| s.destination != destination || !s.subscription.same_target(&subscription));
+        self.subscriptions.push(ChatSubscription {
+            destination,
+            subscription,
+        });
+    }
+
+    /// Remove the subscription of the chat on the same target, or all of them
+    /// if `None`, returning how many were removed
+    pub fn unsubscribe(
+        &mut self,
+        destination: AlertDestination,
+        subscription: Option<&Subscription>,
+    ) -> usize {
+        let before = self.subscriptions.len();
+        self.subscriptions.retain(|s| {
+            s.destination != destination
+                || subscription
+                    .is_some_and(|subscription| !s.subscription.same_target(subscription))
+        });
+        before - self.subscriptions.len()
+    }
+
+    pub fn of_chat(&self, destination: AlertDestination) -> Vec<&Subscription> {
+        self.subscriptions
+            .iter()
+            .filter(|s| s.destination == destination)
+            .map(|s| &s.subscription)
+            .collect()
+    }
+
+    /// The chats subscribed to an alert
+    pub fn destinations(&self, alert: &Alert) -> Vec<AlertDestination> {
+        let mut destinations: Vec<AlertDestination> = Vec::new();
+        for s in &self.subscriptions {
+            if s.subscription.matches(alert) && !destinations.contains(&s.destination) {
+                destinations.push(s.destination);
+            }
+        }
+        destinations
+    }
+
+    /// Whether a chat subscribed to a transfer, for the transfers below the
+    /// alert thresholds
+    pub fn wants_transfer(&self, transfer: &AlertTransfer) -> bool {
+        self.subscriptions
+            .iter()
+            .any(|s| s.subscription.matches_transfer(transfer))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscriptions() -> Result<()> {
+        let finance: AlertDestination = "-100".parse()?;
+        let security: AlertDestination = "-200:5".parse()?;
+        let mut subscriptions = Subscriptions::default();
+        subscriptions.subscribe(
+            finance,
+            Subscription::parse(&["token", "0x1::STC::STC", "100000"])?,
+        );
+        subscriptions.subscribe(
+            security,
+            Subscription::parse(&["address", "0x4a50777e0e4f67625400148b04afd572", "1"])?,
+        );
+        subscriptions.subscribe(
+            security,
+            Subscription::parse(&["category", "node_health", "critical"])?,
+        );
+        assert!(Subscription::parse(&["token", "STC"]).is_err());
+        // The amounts of a token other than STC need its decimals
+        assert!(Subscription::parse(&["token", "0x1::XUSDT::XUSDT", "100"]).is_err());
+        assert_eq!(
+            Subscription::parse(&["token", "0x1::XUSDT::XUSDT", "100.5", "6"])?,
+            Subscription::Token {
+                token: normalize_token_code("0x1::XUSDT::XUSDT")?,
+                min_amount: Some(100_500_000),
+                decimals: 6,
+            }
+        );
+        assert!(Subscription::parse(&[
+            "address",
+            "0x4a50777e0e4f67625400148b04afd572",
+            "1.0000000001"
+        ])
+        .is_err());
+        assert!(Subscription::parse(&["block"]).is_err());
+
+        // A small transfer of the watched address, below the token threshold
+        let transfer = AlertTransfer {
+            sender: "0x4a50777e0e4f67625400148b04afd572".to_string(),
+            receiver: "0x1".to_string(),
+            token: "0x00000000000000000000000000000001::STC::STC".to_string(),
+            amount: 1_000_000_000,
+        };
+        assert!(subscriptions.wants_transfer(&transfer));
+        let alert = Alert::new(AlertCategory::LargeTransfer, Severity::Warning, "transfer")
+            .with_transfer(transfer.clone())
+            .subscribers_only(true);
+        assert_eq!(subscriptions.destinations(&alert), vec![security]);
+
+        let large_transfer = AlertTransfer {
+            sender: "0x2".to_string(),
+            amount: 200_000_000_000_000,
+            ..transfer.clone()
+        };
+        let large = Alert::new(AlertCategory::LargeTransfer, Severity::Warning, "transfer")
+            .with_transfer(large_transfer.clone());
+        assert_eq!(subscriptions.destinations(&large), vec![finance]);
+        // A digest goes to the chats subscribed to any of its transfers
+        let digest = Alert::new(AlertCategory::LargeTransfer, Severity::Warning, "digest")
+            .with_transfer(large_transfer.clone())
+            .with_transfer(transfer);
+        assert_eq!(subscriptions.destinations(&digest), vec![finance, security]);
+
+        // Without an amount, only the transfers above the alert thresholds
+        let watched = Subscription::parse(&["address", "0x1"])?;
+        assert!(!watched.matches_transfer(&large_transfer));
+        assert!(watched.matches(&large));
+        assert!(!watched.matches(&alert));
+
+        let lagging = Alert::new(AlertCategory::NodeHealth, Severity::Warning, "lagging");
+        assert!(subscriptions.destinations(&lagging).is_empty());
+        let balance = Alert::new(AlertCategory::Balance, Severity::Warning, "balance")
+            .with_address("0x4a50777e0e4f67625400148b04afd572");
+        assert_eq!(subscriptions.destinations(&balance), vec![security]);
+
+        // A new threshold replaces the previous one
+        subscriptions.subscribe(
+            finance,
+            Subscription::parse(&["token", "0x1::STC::STC", "500000.5"])?,
+        );
+        assert_eq!(subscriptions.of_chat(finance).len(), 1);
+        assert_eq!(
+            subscriptions.of_chat(finance)[0].to_string(),
+            "token 0x00000000000000000000000000000001::STC::STC 500000.500000000"
+        );
+
+        let restored: Subscriptions =
+            serde_json::from_str(&serde_json::to_string(&subscriptions)?)?;
+        assert_eq!(restored.of_chat(security).len(), 2);
+
+        assert_eq!(
+            subscriptions.unsubscribe(
+                security,
+                Some(&Subscription::parse(&["category", "node_health"])?)
+            ),
+            1
+        );
+        assert_eq!(subscriptions.unsubscribe(security, None), 1);
+        assert!(subscriptions.of_chat(security).is_empty());
+        assert_eq!(subscriptions.of_chat(finance).len(), 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,244 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{alert_throttle::AlertThrottle, helper};
+use anyhow::{anyhow, ensure, Result};
+use serde::Deserialize;
+use starcoin_types::account_address::AccountAddress;
+use std::collections::{HashMap, VecDeque};
+use tracing::info;
+
+/// Configuration for token supply change detection
+#[derive(Debug, Clone)]
+pub struct SupplyCheckConfig {
+    /// Token code -> amount, in the smallest unit of the token, that may be
+    /// minted, burned or withdrawn from the treasury within `window_secs`
+    pub limits: HashMap<String, u128>,
+    pub window_secs: u64,
+    /// Minimal seconds between two alerts of the same kind for the same token
+    pub max_notify_time_interval: u64,
+}
+
+impl Default for SupplyCheckConfig {
+    fn default() -> Self {
+        Self {
+            limits: HashMap::new(),
+            window_secs: 3600,
+            max_notify_time_interval: 600,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SupplyChangeKind {
+    /// `0x1::Token::MintEvent`
+    Mint,
+    /// `0x1::Token::BurnEvent`
+    Burn,
+    /// `0x1::Treasury::WithdrawEvent`
+    TreasuryWithdraw,
+}
+
+impl SupplyChangeKind {
+    /// Identifies the kind of change in the alert keys
+    pub fn key(&self) -> &'static str {
+        match self {
+            SupplyChangeKind::Mint => "mint",
+            SupplyChangeKind::Burn => "burn",
+            SupplyChangeKind::TreasuryWithdraw => "treasury_withdraw",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SupplyAlert {
+    pub kind: SupplyChangeKind,
+    pub token: String,
+    /// Amount changed within the window, including the latest change
+    pub total: u128,
+    pub limit: u128,
+    pub window_secs: u64,
+}
+
+/// Normalize a token code such as `0x1::STC::STC` to its full length address form
+pub fn normalize_token_code(token: &str) -> Result<String> {
+    let parts: Vec<&str> = token.trim().split("::").collect();
+    ensure!(parts.len() == 3, "Invalid token code: {}", token);
+    let address = AccountAddress::from_hex_literal(parts[0])
+        .map_err(|e| anyhow!("Invalid token code {}: {:?}", token, e))?;
+    Ok(format!(
+        "{}::{}::{}",
+        helper::format_address(address),
+        parts[1],
+        parts[2]
+    ))
+}
+
+/// Parse limits in the `<token code>=<amount>,...` form
+pub fn parse_supply_limits(limits: &str) -> Result<HashMap<String, u128>> {
+    limits
+        .split(',')
+        .map(|limit| limit.trim())
+        .filter(|limit| !limit.is_empty())
+        .map(|limit| {
+            let (token, amount) = limit
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Invalid token supply limit: {}", limit))?;
+            Ok((normalize_token_code(token)?, amount.trim().parse()?))
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct TokenCodeData {
+    address: AccountAddress,
+    module: Vec<u8>,
+    name: Vec<u8>,
+}
+
+/// BCS layout of `MintEvent` and `BurnEvent`
+#[derive(Deserialize)]
+struct TokenSupplyEventData {
+    amount: u128,
+    token_code: TokenCodeData,
+}
+
+/// Decode the token and amount of a `MintEvent` or `BurnEvent`
+pub fn decode_token_supply_event(data: &[u8]) -> Result<(String, u128)> {
+    let event: TokenSupplyEventData = bcs_ext::from_bytes(data)?;
+    let token = format!(
+        "{}::{}::{}",
+        helper::format_address(event.token_code.address),
+        String::from_utf8(event.token_code.module)?,
+        String::from_utf8(event.token_code.name)?
+    );
+    Ok((token, event.amount))
+}
+
+/// Decode the amount of a `Treasury::WithdrawEvent`
+pub fn decode_treasury_withdraw_event(data: &[u8]) -> Result<u128> {
+    Ok(bcs_ext::from_bytes(data)?)
+}
+
+/// Sums the supply changes of the limited tokens over a sliding window
+#[derive(Debug, Default)]
+pub struct SupplyTracker {
+    /// (token, kind) -> (time, amount) of the changes within the window
+    changes: HashMap<(String, SupplyChangeKind), VecDeque<(u64, u128)>>,
+    throttle: AlertThrottle,
+}
+
+impl SupplyTracker {
+    /// Record a supply change, returning an alert if the total within the
+    /// window exceeds the limit of the token
+    pub fn observe(
+        &mut self,
+        kind: SupplyChangeKind,
+        token: &str,
+        amount: u128,
+        now: u64,
+        config: &SupplyCheckConfig,
+    ) -> Option<SupplyAlert> {
+        let limit = *config.limits.get(token)?;
+        let changes = self.changes.entry((token.to_string(), kind)).or_default();
+        changes.push_back((now, amount));
+        while changes
+            .front()
+            .is_some_and(|(time, _)| now.saturating_sub(*time) > config.window_secs)
+        {
+            changes.pop_front();
+        }
+
+        let total = changes.iter().map(|(_, amount)| *amount).sum::<u128>();
+        if total <= limit {
+            return None;
+        }
+        let key = format!("supply:{:?}:{}", kind, token);
+        if !self
+            .throttle
+            .should_notify(&key, now, config.max_notify_time_interval)
+        {
+            return None;
+        }
+        let alert = SupplyAlert {
+            kind,
+            token: token.to_string(),
+            total,
+            limit,
+            window_secs: config.window_secs,
+        };
+        info!("Token supply exception detected: {:?}", alert);
+        Some(alert)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STC: &str = "0x00000000000000000000000000000001::STC::STC";
+
+    #[test]
+    fn test_parse_supply_limits() -> Result<()> {
+        let limits = parse_supply_limits(
+            "0x1::STC::STC=1000, 0x4a50777e0e4f67625400148b04afd572::USDT::USDT=5",
+        )?;
+        assert_eq!(limits.get(STC), Some(&1000));
+        assert_eq!(
+            limits.get("0x4a50777e0e4f67625400148b04afd572::USDT::USDT"),
+            Some(&5)
+        );
+        assert!(parse_supply_limits("")?.is_empty());
+        assert!(parse_supply_limits("0x1::STC=1000").is_err());
+        assert!(parse_supply_limits("0x1::STC::STC").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_token_supply_event() -> Result<()> {
+        let mut data = 2000u128.to_le_bytes().to_vec();
+        data.extend_from_slice(&[0u8; 15]);
+        data.push(1);
+        data.extend_from_slice(&[3, b'S', b'T', b'C', 3, b'S', b'T', b'C']);
+        assert_eq!(decode_token_supply_event(&data)?, (STC.to_string(), 2000));
+        assert_eq!(decode_treasury_withdraw_event(&7u128.to_le_bytes())?, 7);
+        Ok(())
+    }
+
+    #[test]
+    fn test_supply_window() {
+        let config = SupplyCheckConfig {
+            limits: HashMap::from([(STC.to_string(), 1000)]),
+            ..SupplyCheckConfig::default()
+        };
+        let mut tracker = SupplyTracker::default();
+
+        // Tokens without a limit are ignored
+        assert!(tracker
+            .observe(SupplyChangeKind::Mint, "0x1::X::X", 5000, 0, &config)
+            .is_none());
+
+        assert!(tracker
+            .observe(SupplyChangeKind::Mint, STC, 600, 0, &config)
+            .is_none());
+        // Mints and burns are summed separately
+        assert!(tracker
+            .observe(SupplyChangeKind::Burn, STC, 600, 10, &config)
+            .is_none());
+        assert_eq!(
+            tracker.observe(SupplyChangeKind::Mint, STC, 600, 100, &config),
+            Some(SupplyAlert {
+                kind: SupplyChangeKind::Mint,
+                token: STC.to_string(),
+                total: 1200,
+                limit: 1000,
+                window_secs: 3600,
+            })
+        );
+
+        // The first mint left the window
+        assert!(tracker
+            .observe(SupplyChangeKind::Mint, STC, 300, 3700, &config)
+            .is_none());
+    }
+}
@@ -1,92 +1,463 @@
 // Copyright (c) The Starcoin Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::address_label::{AddressCategory, AddressLabel};
+use crate::alert_mute::{self, MuteAction, MuteRule};
+use crate::alert_routing::{AlertDestination, AlertRouter};
+use crate::alert_sender::{Alert, AlertSender, Severity};
+use crate::alert_throttle::AlertThrottle;
+use crate::balance_check::BalanceHistory;
+use crate::bot_access::{AccessControl, AuditEntry, AuditLog, Role};
+use crate::bot_jobs::{JobId, JobProgress, JobQueue};
+use crate::chain_reader::ChainReader;
 use crate::config::Config;
+use crate::fund_trace::{render_trace, trace_funds, TraceConfig, MAX_TRACE_DEPTH};
 use crate::helper;
+use crate::locale::Locale;
+use crate::rich_text::RichText;
+use crate::send_queue::{Outgoing, SendQueue, SendWorker};
+use crate::storage;
+use crate::stores::Stores;
+use crate::subscription::Subscription;
+use crate::templates;
+use crate::transfer_index::EsTransferIndex;
 use anyhow::Result;
-use starcoin_rpc_client::RpcClient;
-use starcoin_types::block::BlockNumber;
-use std::{sync::Arc, thread::JoinHandle};
-use teloxide::{prelude::*, types::Message, Bot};
+use chrono::{Duration, Utc};
+use starcoin_crypto::HashValue;
+use starcoin_types::{account_address::AccountAddress, block::BlockNumber};
+use std::{
+    future::Future,
+    path::Path,
+    sync::{Arc, Mutex},
+    thread::JoinHandle,
+    time::Instant,
+};
+use teloxide::{
+    prelude::*,
+    types::{
+        CallbackQuery, InlineKeyboardButton, InlineKeyboardMarkup, Me, Message, MessageId, User,
+    },
+    utils::command::{BotCommands, ParseError},
+    Bot,
+};
 use tracing::{error, info};
 
+/// Blocks fetched per RPC call of `/transactions`, the progress of the job
+/// being reported after each batch
+const TRANSACTIONS_BATCH_BLOCKS: u64 = 100;
+
+/// Shortest time between two edits of the status message of a job, Telegram
+/// limiting how often a message can be edited
+const JOB_PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3);
+
 async fn do_handle_blocks(
-    rpc_client: Arc<RpcClient>,
+    chain_reader: Arc<dyn ChainReader>,
     config: Arc<Config>,
+    locale: Locale,
     start_num: BlockNumber,
     end_num: BlockNumber,
-) -> Result<Option<String>> {
-    let rpc_client1 = rpc_client.clone();
-    let block_views = tokio::task::spawn_blocking(move || {
-        rpc_client1.chain_get_blocks_by_number(Some(start_num), end_num - start_num, None)
-    })
-    .await??;
-
-    if block_views.is_empty() {
-        return Ok(Some(format!(
-            "从区块 {} 到 {}， 没有找到大交易",
-            start_num, end_num
-        )));
-    }
-
-    let all_transactions =
-        helper::extract_full_txn_from_block_view(rpc_client.clone(), block_views).await?;
-    if all_transactions.is_empty() {
-        return Ok(Some(format!(
-            "从区块 {} 到 {}， 没有找到大交易",
-            start_num, end_num
-        )));
+    progress: JobProgress,
+) -> Result<RichText> {
+    let count = end_num - start_num;
+    let mut all_transactions = Vec::new();
+    let mut fetched = 0;
+    while fetched < count {
+        let batch = TRANSACTIONS_BATCH_BLOCKS.min(count - fetched);
+        let number = start_num + fetched;
+        let chain_reader1 = chain_reader.clone();
+        let block_views = tokio::task::spawn_blocking(move || {
+            chain_reader1.get_blocks_by_number(Some(number), batch)
+        })
+        .await??;
+        all_transactions.extend(
+            helper::extract_full_txn_from_block_view(chain_reader.clone(), block_views).await?,
+        );
+        fetched += batch;
+        progress.set((locale.messages().blocks_fetched)(fetched, count));
     }
 
     // Process all collected transactions
     let mut matched_txn = Vec::new();
 
     for tx in all_transactions {
-        let amount = helper::parse_txn_p2p_amount(tx.clone())?;
-        if config.min_transaction_amount < amount.unwrap_or(0) {
-            matched_txn.push((tx.transaction_hash.clone(), amount));
+        let amount = helper::parse_txn_p2p_amount(tx.clone())?.unwrap_or(0);
+        if config.min_transaction_amount < amount {
+            matched_txn.push((tx.transaction_hash.to_hex_literal(), amount));
         }
     }
 
-    if matched_txn.is_empty() {
-        return Ok(Some(format!(
-            "从区块 {} 到 {}， 没有找到大交易",
-            start_num, end_num
-        )));
+    Ok(templates::block_transfers(
+        locale,
+        start_num,
+        end_num,
+        &matched_txn,
+    ))
+}
+
+type ParseResult<T> = std::result::Result<T, ParseError>;
+
+/// The commands of the bot, their descriptions only making its registered
+/// command menu, `/help` being worded in the chat locale
+#[derive(BotCommands, Clone, Debug, PartialEq)]
+#[command(rename_rule = "lowercase")]
+pub enum Command {
+    #[command(description = "off")]
+    Start,
+    #[command(description = "显示帮助", parse_with = parse_help_args)]
+    Help { command: Option<String> },
+    #[command(description = "查询两个区块之间的大交易", parse_with = "split")]
+    Transactions {
+        start_block: BlockNumber,
+        end_block: BlockNumber,
+    },
+    #[command(description = "查询地址的 STC 余额及24小时变动", parse_with = parse_balance_args)]
+    Balance { address: AccountAddress },
+    #[command(description = "查询, 添加或修改地址标签", parse_with = parse_label_args)]
+    Label {
+        address: AccountAddress,
+        label: Option<AddressLabel>,
+    },
+    #[command(description = "追踪大额转账的资金流向", parse_with = parse_trace_args)]
+    Trace { txn_hash: HashValue, depth: usize },
+    #[command(description = "订阅告警, 不带参数时查看本群的订阅", parse_with = parse_subscribe_args)]
+    Subscribe { subscription: Option<Subscription> },
+    #[command(description = "取消订阅", parse_with = parse_unsubscribe_args)]
+    Unsubscribe { subscription: Option<Subscription> },
+    #[command(description = "暂停告警", parse_with = parse_snooze_args)]
+    Snooze { key: String, duration: Duration },
+    #[command(description = "屏蔽地址的告警, 不带参数时查看已暂停和屏蔽的告警", parse_with = parse_mute_args)]
+    Mute {
+        address: Option<String>,
+        duration: Option<Duration>,
+    },
+    #[command(description = "恢复已暂停或屏蔽的告警", parse_with = parse_unmute_args)]
+    Unmute { target: Option<String> },
+    #[command(description = "查看或设置本群的语言 / Show or set the language of the chat", parse_with = parse_language_args)]
+    Language { locale: Option<Locale> },
+    #[command(description = "查看运行中的查询任务")]
+    Jobs,
+    #[command(description = "取消自己的查询任务", parse_with = parse_cancel_args)]
+    Cancel { job_id: Option<JobId> },
+}
+
+impl Command {
+    /// The lowest role allowed to run the command
+    pub fn required_role(&self) -> Role {
+        match self {
+            Command::Start
+            | Command::Help { .. }
+            | Command::Balance { .. }
+            | Command::Label { label: None, .. }
+            | Command::Subscribe { subscription: None }
+            | Command::Mute { address: None, .. }
+            | Command::Language { locale: None }
+            | Command::Jobs
+            | Command::Cancel { .. } => Role::Viewer,
+            Command::Transactions { .. }
+            | Command::Trace { .. }
+            | Command::Subscribe {
+                subscription: Some(_),
+            }
+            | Command::Unsubscribe { .. }
+            | Command::Snooze { .. }
+            | Command::Mute {
+                address: Some(_), ..
+            }
+            | Command::Unmute { .. }
+            | Command::Language { locale: Some(_) } => Role::Operator,
+            Command::Label { label: Some(_), .. } => Role::Admin,
+        }
     }
+}
 
-    let total_amount = matched_txn
-        .iter()
-        .map(|pair| pair.1.unwrap_or(0))
-        .sum::<u128>();
+/// Invalid arguments of a command, told to the user in the chat locale
+/// before the usage of the command
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgError {
+    TooManyArguments,
+    MissingAddress,
+    InvalidAddress(String),
+    InvalidJobId(String),
+    InvalidSubscription,
+    MissingSubscription,
+    InvalidLocale(String),
+    InvalidDuration(String),
+    MissingAlertKey,
+    InvalidAlertKey(String),
+    /// Neither an alert key nor an address
+    InvalidMuteTarget(String),
+    MissingLabelName,
+    InvalidCategory(String),
+    MissingTxnHash,
+    InvalidTxnHash(String),
+    InvalidDepth,
+}
 
-    Ok(Some(format!(
-        "查询区块区间: https://stcscan.io/main/blocks/height/{}, https://stcscan.io/main/blocks/height/{}
-         \n交易总额  {:.9} STC,
-         \n交易列表: {:?}",
-        start_num,
-        end_num,
-        (total_amount as f64) / (1e9f64),
-        matched_txn
-    )))
+impl std::fmt::Display for ArgError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgError::TooManyArguments => write!(f, "Too many arguments"),
+            ArgError::MissingAddress => write!(f, "Missing address"),
+            ArgError::InvalidAddress(address) => write!(f, "Invalid address: {}", address),
+            ArgError::InvalidJobId(job_id) => write!(f, "Invalid job id: {}", job_id),
+            ArgError::InvalidSubscription => write!(f, "Invalid subscription"),
+            ArgError::MissingSubscription => write!(f, "Missing subscription"),
+            ArgError::InvalidLocale(locale) => write!(f, "Unknown locale: {}", locale),
+            ArgError::InvalidDuration(duration) => write!(f, "Invalid duration: {}", duration),
+            ArgError::MissingAlertKey => write!(f, "Missing alert key"),
+            ArgError::InvalidAlertKey(key) => write!(f, "Invalid alert key: {}", key),
+            ArgError::InvalidMuteTarget(target) => {
+                write!(f, "Invalid alert key or address: {}", target)
+            }
+            ArgError::MissingLabelName => write!(f, "Missing label name"),
+            ArgError::InvalidCategory(category) => write!(f, "Invalid category: {}", category),
+            ArgError::MissingTxnHash => write!(f, "Missing transaction hash"),
+            ArgError::InvalidTxnHash(txn_hash) => {
+                write!(f, "Invalid transaction hash: {}", txn_hash)
+            }
+            ArgError::InvalidDepth => write!(f, "Invalid depth, from 1 to {}", MAX_TRACE_DEPTH),
+        }
+    }
+}
+
+impl std::error::Error for ArgError {}
+
+impl From<ArgError> for ParseError {
+    fn from(e: ArgError) -> Self {
+        ParseError::Custom(Box::new(e))
+    }
+}
+
+fn parse_help_args(input: String) -> ParseResult<(Option<String>,)> {
+    let command = input.trim().trim_start_matches('/').to_lowercase();
+    Ok(((!command.is_empty()).then_some(command),))
+}
+
+fn parse_cancel_args(input: String) -> ParseResult<(Option<JobId>,)> {
+    let job_id = input.trim().trim_start_matches('#');
+    if job_id.is_empty() {
+        return Ok((None,));
+    }
+    let job_id = job_id
+        .parse()
+        .map_err(|_| ArgError::InvalidJobId(input.trim().to_string()))?;
+    Ok((Some(job_id),))
+}
+
+fn parse_subscription(args: &[&str]) -> ParseResult<Subscription> {
+    Subscription::parse(args).map_err(|_| ArgError::InvalidSubscription.into())
+}
+
+fn parse_subscribe_args(input: String) -> ParseResult<(Option<Subscription>,)> {
+    let args: Vec<&str> = input.split_whitespace().collect();
+    if args.is_empty() {
+        return Ok((None,));
+    }
+    Ok((Some(parse_subscription(&args)?),))
+}
+
+fn parse_language_args(input: String) -> ParseResult<(Option<Locale>,)> {
+    let locale = input.trim();
+    if locale.is_empty() {
+        return Ok((None,));
+    }
+    let locale = locale
+        .parse()
+        .map_err(|_| ArgError::InvalidLocale(locale.to_string()))?;
+    Ok((Some(locale),))
+}
+
+/// `all` for all the subscriptions of the chat
+fn parse_unsubscribe_args(input: String) -> ParseResult<(Option<Subscription>,)> {
+    match input.split_whitespace().collect::<Vec<_>>().as_slice() {
+        [] => Err(ArgError::MissingSubscription.into()),
+        ["all"] => Ok((None,)),
+        args => Ok((Some(parse_subscription(args)?),)),
+    }
+}
+
+fn parse_mute_duration(duration: Option<&&str>) -> ParseResult<Option<Duration>> {
+    duration
+        .map(|duration| {
+            alert_mute::parse_duration(duration)
+                .map_err(|_| ArgError::InvalidDuration(duration.to_string()).into())
+        })
+        .transpose()
+}
+
+fn parse_snooze_args(input: String) -> ParseResult<(String, Duration)> {
+    let args: Vec<&str> = input.split_whitespace().collect();
+    let (key, duration) = match args.as_slice() {
+        [] => return Err(ArgError::MissingAlertKey.into()),
+        [key] => (key, None),
+        [key, duration] => (key, Some(duration)),
+        _ => return Err(ArgError::TooManyArguments.into()),
+    };
+    let key =
+        alert_mute::parse_alert_key(key).map_err(|_| ArgError::InvalidAlertKey(key.to_string()))?;
+    let duration = parse_mute_duration(duration)?.unwrap_or_else(|| Duration::hours(1));
+    Ok((key, duration))
+}
+
+fn parse_mute_args(input: String) -> ParseResult<(Option<String>, Option<Duration>)> {
+    let args: Vec<&str> = input.split_whitespace().collect();
+    let (address, duration) = match args.as_slice() {
+        [] => return Ok((None, None)),
+        [address] => (address, None),
+        [address, duration] => (address, Some(duration)),
+        _ => return Err(ArgError::TooManyArguments.into()),
+    };
+    let address = alert_mute::parse_address(address)
+        .map_err(|_| ArgError::InvalidAddress(address.to_string()))?;
+    Ok((Some(address), parse_mute_duration(duration)?))
+}
+
+/// An alert key or an address, `all` for all of them
+fn parse_unmute_args(input: String) -> ParseResult<(Option<String>,)> {
+    match input.split_whitespace().collect::<Vec<_>>().as_slice() {
+        [] => Err(ArgError::MissingAlertKey.into()),
+        ["all"] => Ok((None,)),
+        [target] => {
+            let target = alert_mute::parse_address(target)
+                .or_else(|_| alert_mute::parse_alert_key(target))
+                .map_err(|_| ArgError::InvalidMuteTarget(target.to_string()))?;
+            Ok((Some(target),))
+        }
+        _ => Err(ArgError::TooManyArguments.into()),
+    }
+}
+
+fn parse_address(input: &str) -> ParseResult<AccountAddress> {
+    AccountAddress::from_hex_literal(input)
+        .map_err(|_| ArgError::InvalidAddress(input.to_string()).into())
+}
+
+fn parse_balance_args(input: String) -> ParseResult<(AccountAddress,)> {
+    match input.split_whitespace().collect::<Vec<_>>().as_slice() {
+        [] => Err(ArgError::MissingAddress.into()),
+        [address] => Ok((parse_address(address)?,)),
+        _ => Err(ArgError::TooManyArguments.into()),
+    }
+}
+
+fn parse_label_args(input: String) -> ParseResult<(AccountAddress, Option<AddressLabel>)> {
+    let args: Vec<&str> = input.split_whitespace().collect();
+    match args.as_slice() {
+        [] => Err(ArgError::MissingAddress.into()),
+        [address] => Ok((parse_address(address)?, None)),
+        [_, _] => Err(ArgError::MissingLabelName.into()),
+        [address, category, name @ ..] => {
+            let category = category
+                .parse::<AddressCategory>()
+                .map_err(|_| ArgError::InvalidCategory(category.to_string()))?;
+            let label = AddressLabel {
+                category,
+                name: name.join(" "),
+            };
+            Ok((parse_address(address)?, Some(label)))
+        }
+    }
+}
+
+fn parse_trace_args(input: String) -> ParseResult<(HashValue, usize)> {
+    let args: Vec<&str> = input.split_whitespace().collect();
+    let (txn_hash, depth) = match args.as_slice() {
+        [] => return Err(ArgError::MissingTxnHash.into()),
+        [txn_hash] => (txn_hash, None),
+        [txn_hash, depth] => (txn_hash, Some(depth)),
+        _ => return Err(ArgError::TooManyArguments.into()),
+    };
+    let txn_hash = HashValue::from_hex_literal(txn_hash)
+        .map_err(|_| ArgError::InvalidTxnHash(txn_hash.to_string()))?;
+    let depth = match depth.map(|depth| depth.parse::<usize>()) {
+        None => TraceConfig::default().depth,
+        Some(Ok(depth)) if (1..=MAX_TRACE_DEPTH).contains(&depth) => depth,
+        Some(_) => return Err(ArgError::InvalidDepth.into()),
+    };
+    Ok((txn_hash, depth))
+}
+
+/// How a user is shown next to what they did
+fn user_name(user: Option<&User>) -> String {
+    match user {
+        Some(User {
+            username: Some(username),
+            ..
+        }) => format!("@{}", username),
+        Some(user) => user.full_name(),
+        None => "unknown".to_string(),
+    }
+}
+
+/// The command of a message, without the `/` and the bot name
+fn command_name(text: &str) -> &str {
+    let command = text.split_whitespace().next().unwrap_or_default();
+    let command = command.split('@').next().unwrap_or_default();
+    command.trim_start_matches('/')
+}
+
+/// How to use a command, for `/help <command>` and the invalid arguments
+fn command_usage(locale: Locale, command: &str) -> String {
+    let m = locale.messages();
+    (m.command_usage)(command).unwrap_or_else(|| (m.unknown_help_command)(command))
+}
+
+/// Why the arguments of a command were rejected, those parsed by teloxide
+/// itself only being told invalid
+fn arg_error(locale: Locale, e: &ParseError) -> String {
+    let m = locale.messages();
+    match e {
+        ParseError::Custom(e) => match e.downcast_ref::<ArgError>() {
+            Some(e) => (m.arg_error)(e),
+            None => m.invalid_arguments.to_string(),
+        },
+        _ => m.invalid_arguments.to_string(),
+    }
 }
 
 #[derive(Clone)]
 pub struct TelegramBot {
     config: Arc<Config>,
     bot: Arc<Bot>,
-    rpc_client: Arc<RpcClient>,
+    chain_reader: Arc<dyn ChainReader>,
+    access_control: AccessControl,
+    audit_log: AuditLog,
+    jobs: Arc<JobQueue>,
+    command_throttle: Arc<Mutex<AlertThrottle>>,
+    alert_router: AlertRouter,
+    send_queue: SendQueue,
+    /// Started by `run`, the messages queued before waiting for it
+    send_worker: Arc<Mutex<Option<SendWorker>>>,
+    stores: Arc<Stores>,
 }
 
 impl TelegramBot {
-    pub fn new(config: Arc<Config>, rpc_client: Arc<RpcClient>) -> Self {
-        let bot =
-            Self::create_bot_with_proxy(&config.telegram_bot_token, config.telegram_proxy.clone());
-        Self {
+    pub fn new(
+        config: Arc<Config>,
+        chain_reader: Arc<dyn ChainReader>,
+        stores: Arc<Stores>,
+    ) -> Result<Self> {
+        let bot = Arc::new(Self::create_bot_with_proxy(
+            &config.telegram_bot_token,
+            config.telegram_proxy.clone(),
+        ));
+        let (send_queue, send_worker) = SendQueue::new(bot.clone(), &config);
+        Ok(Self {
             config: config.clone(),
-            bot: Arc::new(bot),
-            rpc_client,
-        }
+            bot,
+            chain_reader,
+            access_control: AccessControl::from_config(&config)?,
+            audit_log: AuditLog::new(&config.data_dir),
+            jobs: Arc::new(JobQueue::new(
+                config.bot_max_jobs,
+                config.bot_max_jobs_per_user,
+            )),
+            command_throttle: Arc::new(Mutex::new(AlertThrottle::default())),
+            alert_router: AlertRouter::from_config(&config)?,
+            send_queue,
+            send_worker: Arc::new(Mutex::new(Some(send_worker))),
+            stores,
+        })
     }
 
     fn create_bot_with_proxy(token: &str, proxy: Option<String>) -> Bot {
@@ -121,9 +492,9 @@ impl TelegramBot {
     }
 
     pub fn run(&self) -> Result<JoinHandle<()>> {
-        let bot = self.bot.clone();
-        let config = self.config.clone();
-        let rpc_client = self.rpc_client.clone();
+        // One instance shared by all the updates, the bot and its HTTP client
+        // are reused instead of being created per message
+        let telegram_bot = Arc::new(self.clone());
         Ok(std::thread::spawn(move || {
             info!("TelegramBot::run | entered");
             let rt = tokio::runtime::Builder::new_current_thread()
@@ -134,28 +505,37 @@ impl TelegramBot {
 
             info!("TelegramBot::run | Tokio builded");
 
-            // let db = self.db.clone();
-            let handler = Update::filter_message().branch(
-                dptree::filter(|msg: Message| msg.text().is_some()).endpoint(
-                    move |msg: Message| {
-                        let config = config.clone();
-                        let rpc_client = rpc_client.clone();
-
-                        async move {
-                            let text = msg.text().unwrap();
-                            let chat_id = msg.chat.id;
-                            let _user_id =
-                                msg.from().map(|u| u.id.0.to_string()).unwrap_or_default();
-
-                            let telegram_bot = TelegramBot::new(config.clone(), rpc_client.clone());
-                            telegram_bot.handle_command(text, chat_id, _user_id).await
-                        }
+            let handler = dptree::entry()
+                .branch(Update::filter_message().branch(
+                    dptree::filter(|msg: Message| msg.text().is_some()).endpoint(
+                        |msg: Message, me: Me, telegram_bot: Arc<TelegramBot>| async move {
+                            telegram_bot.handle_message(&msg, me.username()).await
+                        },
+                    ),
+                ))
+                // The buttons of the alerts
+                .branch(Update::filter_callback_query().endpoint(
+                    |query: CallbackQuery, telegram_bot: Arc<TelegramBot>| async move {
+                        telegram_bot.handle_callback_query(&query).await
                     },
-                ),
-            );
+                ));
 
+            let send_worker = telegram_bot.send_worker.lock().unwrap().take();
             rt.block_on(async {
-                Dispatcher::builder(bot.clone(), handler)
+                if let Some(send_worker) = send_worker {
+                    tokio::spawn(send_worker.run());
+                }
+                // The menu shown by the clients when typing `/`
+                if let Err(e) = telegram_bot
+                    .bot
+                    .set_my_commands(Command::bot_commands())
+                    .await
+                {
+                    error!("Failed to register the bot commands: {}", e);
+                }
+
+                Dispatcher::builder(telegram_bot.bot.clone(), handler)
+                    .dependencies(dptree::deps![telegram_bot.clone()])
                     .build()
                     .dispatch()
                     .await
@@ -164,101 +544,720 @@ impl TelegramBot {
         }))
     }
 
-    async fn handle_command(&self, text: &str, chat_id: ChatId, _user_id: String) -> Result<()> {
-        let parts: Vec<&str> = text.split_whitespace().collect();
-        if parts.is_empty() {
+    async fn handle_message(&self, msg: &Message, bot_username: &str) -> Result<()> {
+        let text = msg.text().unwrap_or_default();
+        let chat_id = msg.chat.id;
+        let command = Command::parse(text, bot_username);
+        // Commands of other bots in a group
+        if let Err(ParseError::WrongBotName(_)) = command {
             return Ok(());
         }
 
-        let command = parts[0].to_lowercase();
-        let args = parts[1..].to_vec();
+        let user = msg.from();
+        let user_id = user.map(|user| user.id.0);
+        let role = self.access_control.role_of(chat_id.0, user_id);
+        let required_role = command
+            .as_ref()
+            .map_or(Role::Viewer, |command| command.required_role());
+        let allowed = role.is_some_and(|role| role >= required_role);
+        if text.starts_with('/') {
+            self.audit(AuditEntry {
+                time: Utc::now(),
+                chat_id: chat_id.0,
+                user_id,
+                username: user.and_then(|user| user.username.clone()),
+                command: text.to_string(),
+                role,
+                allowed,
+            });
+        }
+        let role = match role {
+            // Unknown chats and users get no reply at all
+            None => return Ok(()),
+            Some(role) => role,
+        };
+        let locale = self.chat_locale(chat_id);
+        if !allowed {
+            let message = (locale.messages().permission_denied)(required_role, role);
+            return self.send_message_to_chat(chat_id, &message).await;
+        }
 
-        match command.as_str() {
-            "/start" => {
-                let message = self.get_help_message();
-                self.send_message_to_chat(chat_id, &message).await?;
-            }
-            "/help" => {
-                let message = self.get_help_message();
-                self.send_message_to_chat(chat_id, &message).await?;
+        match command {
+            Ok(command) => {
+                // A failed command is told to the user instead of going unanswered
+                if let Err(e) = self
+                    .handle_command(command, chat_id, msg.thread_id, user, role, locale)
+                    .await
+                {
+                    error!("Failed to handle command {}: {}", text, e);
+                    let message = format!("{}{}", locale.messages().command_failed, e);
+                    return self.send_message_to_chat(chat_id, &message).await;
+                }
+                Ok(())
             }
-            "/transactions" => {
-                self.handle_transactions_command(chat_id, args).await?;
+            Err(ParseError::UnknownCommand(_)) => {
+                self.send_message_to_chat(chat_id, locale.messages().unknown_command)
+                    .await
             }
-            _ => {
-                let message = "❓ Unknown command. Use /help to see available commands.";
-                self.send_message_to_chat(chat_id, message).await?;
+            Err(e) => {
+                let message = format!(
+                    "❌ {}\n{}",
+                    arg_error(locale, &e),
+                    command_usage(locale, command_name(text))
+                );
+                self.send_message_to_chat(chat_id, &message).await
             }
         }
+    }
+
+    /// Ack, snooze or mute an alert from its buttons, the alert being edited
+    /// to tell who did it
+    async fn handle_callback_query(&self, query: &CallbackQuery) -> Result<()> {
+        let (message, data) = match (&query.message, &query.data) {
+            (Some(message), Some(data)) => (message, data),
+            _ => return Ok(()),
+        };
+        let chat_id = message.chat.id;
+        let user_id = query.from.id.0;
+        let role = self.access_control.role_of(chat_id.0, Some(user_id));
+        let allowed = role.is_some_and(|role| role >= Role::Operator);
+        let locale = self.chat_locale(chat_id);
+        self.audit(AuditEntry {
+            time: Utc::now(),
+            chat_id: chat_id.0,
+            user_id: Some(user_id),
+            username: query.from.username.clone(),
+            command: format!("button {}", data),
+            role,
+            allowed,
+        });
+        if !allowed {
+            self.bot
+                .answer_callback_query(query.id.clone())
+                .text((locale.messages().button_permission_denied)(Role::Operator))
+                .await?;
+            return Ok(());
+        }
 
+        let now = Utc::now();
+        let rule = match data.parse::<MuteAction>()? {
+            MuteAction::Ack { key } => MuteRule::Ack {
+                key,
+                last_fired: now,
+            },
+            MuteAction::Snooze { key } => MuteRule::Snooze {
+                key,
+                until: now + Duration::hours(1),
+            },
+            MuteAction::MuteAddress { address } => MuteRule::Address {
+                address,
+                until: None,
+            },
+        };
+        let by = user_name(Some(&query.from));
+        let status = format!("🔕 {} ({})", rule.describe(locale), by);
+        self.mute(rule, &by)?;
+        self.bot
+            .answer_callback_query(query.id.clone())
+            .text(&status)
+            .await?;
+        // The alert keeps its formatting, only its buttons are removed and
+        // who silenced it is replied to it
+        if let Err(e) = self
+            .bot
+            .edit_message_reply_markup(chat_id, message.id)
+            .await
+        {
+            error!(
+                "Failed to remove the buttons of alert {} in chat {}: {}",
+                message.id, chat_id, e
+            );
+        }
+        if let Err(e) = self
+            .send_queue
+            .push(Outgoing::new(chat_id, RichText::from(status)).reply_to(message.id))
+        {
+            error!(
+                "Failed to reply to alert {} in chat {}: {}",
+                message.id, chat_id, e
+            );
+        }
         Ok(())
     }
 
-    async fn handle_transactions_command(&self, chat_id: ChatId, args: Vec<&str>) -> Result<()> {
-        if args.len() < 2 {
-            let message = "❌ Usage: /transactions <start_block> <end_block>\nExample: /transactions 1000 1100";
-            self.send_message_to_chat(chat_id, message).await?;
-            return Ok(());
+    /// The locale set for the chat with `/language`, or `DEFAULT_LOCALE`
+    fn chat_locale(&self, chat_id: ChatId) -> Locale {
+        self.stores
+            .locales
+            .read(|locales| locales.get(chat_id.0))
+            .unwrap_or(self.config.default_locale)
+    }
+
+    /// Whether an alert is silenced, keeping track of the acknowledged alerts
+    /// still firing
+    fn is_muted(&self, alert: &Alert) -> bool {
+        let now = Utc::now();
+        let ack_reset = Duration::seconds(self.config.alert_ack_reset_secs as i64);
+        self.stores
+            .mutes
+            .update(|mutes| mutes.check(alert, now, ack_reset))
+            .unwrap_or_else(|e| {
+                // Still silenced, the acks being tracked again on the next alert
+                error!("Failed to save alert mutes: {}", e);
+                self.stores
+                    .mutes
+                    .read(|mutes| mutes.clone().check(alert, now, ack_reset))
+            })
+    }
+
+    fn mute(&self, rule: MuteRule, by: &str) -> Result<()> {
+        info!(
+            "Alerts muted by {}: {}",
+            by,
+            rule.describe(self.config.default_locale)
+        );
+        self.stores.mutes.update(|mutes| mutes.mute(rule, by))
+    }
+
+    async fn handle_mute_rule(
+        &self,
+        chat_id: ChatId,
+        locale: Locale,
+        rule: MuteRule,
+        by: &str,
+    ) -> Result<()> {
+        let message = format!("🔕 {}", rule.describe(locale));
+        self.mute(rule, by)?;
+        self.send_message_to_chat(chat_id, &message).await
+    }
+
+    async fn handle_mutes_command(&self, chat_id: ChatId, locale: Locale) -> Result<()> {
+        let lines: Vec<String> = self.stores.mutes.read(|mutes| {
+            mutes
+                .mutes()
+                .iter()
+                .map(|mute| format!("- {} ({})", mute.rule.describe(locale), mute.by))
+                .collect()
+        });
+        let m = locale.messages();
+        let message = if lines.is_empty() {
+            m.no_mutes.to_string()
+        } else {
+            format!("{}\n{}", m.mutes, lines.join("\n"))
+        };
+        self.send_message_to_chat(chat_id, &message).await
+    }
+
+    async fn handle_unmute_command(
+        &self,
+        chat_id: ChatId,
+        locale: Locale,
+        target: Option<String>,
+    ) -> Result<()> {
+        let removed = self
+            .stores
+            .mutes
+            .update(|mutes| mutes.unmute(target.as_deref()))?;
+        let message = if removed == 0 {
+            locale.messages().no_matching_mute.to_string()
+        } else {
+            info!("{} alert mutes removed: {:?}", removed, target);
+            (locale.messages().alerts_resumed)(removed)
+        };
+        self.send_message_to_chat(chat_id, &message).await
+    }
+
+    /// Keep track of who ran what, the command being run even if the audit
+    /// log cannot be written
+    fn audit(&self, entry: AuditEntry) {
+        info!(
+            "Bot command {:?} from user {:?} ({:?}) in chat {}, role {:?}, allowed: {}",
+            entry.command, entry.user_id, entry.username, entry.chat_id, entry.role, entry.allowed
+        );
+        if let Err(e) = self.audit_log.record(&entry) {
+            error!("Failed to write the audit log: {}", e);
         }
+    }
 
-        let start_block = match args[0].parse::<u64>() {
-            Ok(n) => n,
-            Err(_) => {
-                let message = "❌ Invalid start block number";
-                self.send_message_to_chat(chat_id, message).await?;
-                return Ok(());
+    async fn handle_command(
+        &self,
+        command: Command,
+        chat_id: ChatId,
+        thread_id: Option<i32>,
+        user: Option<&User>,
+        role: Role,
+        locale: Locale,
+    ) -> Result<()> {
+        let user_id = user.map(|user| user.id.0);
+        match command {
+            Command::Start | Command::Help { command: None } => {
+                let message = templates::help(locale);
+                self.send_rich_text_to_chat(chat_id, &message).await
             }
-        };
+            Command::Help {
+                command: Some(command),
+            } => {
+                self.send_message_to_chat(chat_id, &command_usage(locale, &command))
+                    .await
+            }
+            Command::Transactions {
+                start_block,
+                end_block,
+            } => {
+                self.handle_transactions_command(chat_id, user_id, locale, start_block, end_block)
+                    .await
+            }
+            Command::Balance { address } => {
+                self.handle_balance_command(chat_id, locale, address).await
+            }
+            Command::Label { address, label } => {
+                self.handle_label_command(chat_id, locale, address, label)
+                    .await
+            }
+            Command::Trace { txn_hash, depth } => {
+                self.handle_trace_command(chat_id, user_id, locale, txn_hash, depth)
+                    .await
+            }
+            Command::Subscribe { subscription } => {
+                let destination = AlertDestination {
+                    chat_id: chat_id.0,
+                    thread_id,
+                };
+                self.handle_subscribe_command(destination, locale, subscription)
+                    .await
+            }
+            Command::Unsubscribe { subscription } => {
+                let destination = AlertDestination {
+                    chat_id: chat_id.0,
+                    thread_id,
+                };
+                self.handle_unsubscribe_command(destination, locale, subscription)
+                    .await
+            }
+            Command::Snooze { key, duration } => {
+                let rule = MuteRule::Snooze {
+                    key,
+                    until: Utc::now() + duration,
+                };
+                self.handle_mute_rule(chat_id, locale, rule, &user_name(user))
+                    .await
+            }
+            Command::Mute {
+                address: Some(address),
+                duration,
+            } => {
+                let rule = MuteRule::Address {
+                    address,
+                    until: duration.map(|duration| Utc::now() + duration),
+                };
+                self.handle_mute_rule(chat_id, locale, rule, &user_name(user))
+                    .await
+            }
+            Command::Mute { address: None, .. } => self.handle_mutes_command(chat_id, locale).await,
+            Command::Unmute { target } => self.handle_unmute_command(chat_id, locale, target).await,
+            Command::Language { locale: None } => {
+                let message = (locale.messages().chat_language)(locale);
+                self.send_message_to_chat(chat_id, &message).await
+            }
+            Command::Language {
+                locale: Some(locale),
+            } => self.handle_language_command(chat_id, locale).await,
+            Command::Jobs => self.handle_jobs_command(chat_id, locale).await,
+            Command::Cancel { job_id } => {
+                self.handle_cancel_command(chat_id, user_id, role, locale, job_id)
+                    .await
+            }
+        }
+    }
 
-        let end_block = match args[1].parse::<u64>() {
-            Ok(n) => n,
-            Err(_) => {
-                let message = "❌ Invalid end block number";
-                self.send_message_to_chat(chat_id, message).await?;
-                return Ok(());
+    /// Whether the user may run an expensive command again, at most once per
+    /// `BOT_COMMAND_COOLDOWN_SECS`
+    fn check_cooldown(&self, user_id: Option<u64>, command: &str) -> bool {
+        let key = format!("{}:{}", user_id.unwrap_or_default(), command);
+        self.command_throttle.lock().unwrap().should_notify(
+            &key,
+            Utc::now().timestamp() as u64,
+            self.config.bot_command_cooldown_secs,
+        )
+    }
+
+    /// Run an expensive query in the background, its progress shown by
+    /// editing a status message and its result sent once done
+    async fn spawn_job<F, Fut>(
+        &self,
+        chat_id: ChatId,
+        user_id: Option<u64>,
+        locale: Locale,
+        description: String,
+        job: F,
+    ) -> Result<()>
+    where
+        F: FnOnce(JobProgress) -> Fut + Send,
+        Fut: Future<Output = Result<RichText>> + Send + 'static,
+    {
+        let id = match self.jobs.start(user_id, chat_id.0, &description) {
+            Ok(id) => id,
+            Err(limit) => {
+                return self
+                    .send_message_to_chat(chat_id, &(locale.messages().job_limit)(limit))
+                    .await
+            }
+        };
+        // Checked once the job is accepted, a rejected job not counting
+        let command = description.split_whitespace().next().unwrap_or_default();
+        if !self.check_cooldown(user_id, command) {
+            self.jobs.finish(id);
+            let cooldown = self.config.bot_command_cooldown_secs;
+            let message = (locale.messages().command_cooldown)(command, cooldown);
+            return self.send_message_to_chat(chat_id, &message).await;
+        }
+        let started = (locale.messages().job_started)(id, &description);
+        // Its id is needed to edit it, so it is waited for
+        let status = match self
+            .send_queue
+            .send(Outgoing::new(chat_id, RichText::from(started)))
+            .await
+        {
+            Ok(status) => status,
+            Err(e) => {
+                self.jobs.finish(id);
+                return Err(e);
             }
         };
+        self.jobs.set_status_message(id, status.0);
+        info!("Job #{} started: {}", id, description);
+
+        let (progress, mut progress_receiver) = JobProgress::new();
+        let job = job(progress);
+        let telegram_bot = self.clone();
+        let task = tokio::spawn(async move {
+            tokio::pin!(job);
+            let mut last_edit = Instant::now();
+            // A progress changed since the last edit, shown once the interval
+            // is over
+            let mut pending = false;
+            let result = loop {
+                let next_edit = last_edit + JOB_PROGRESS_INTERVAL;
+                tokio::select! {
+                    result = &mut job => break result,
+                    Ok(()) = progress_receiver.changed(), if !pending => pending = true,
+                    _ = tokio::time::sleep_until(next_edit.into()), if pending => {
+                        pending = false;
+                        last_edit = Instant::now();
+                        let progress = progress_receiver
+                            .borrow_and_update()
+                            .clone()
+                            .unwrap_or_default();
+                        let text = (locale.messages().job_running)(id, &description, &progress);
+                        telegram_bot.edit_status(chat_id, status, text).await;
+                    }
+                }
+            };
+            telegram_bot.jobs.finish(id);
+
+            match result {
+                Ok(message) => {
+                    info!("Job #{} done: {}", id, description);
+                    let text = (locale.messages().job_done)(id, &description);
+                    telegram_bot.edit_status(chat_id, status, text).await;
+                    if let Err(e) = telegram_bot.send_rich_text_to_chat(chat_id, &message).await {
+                        error!("Failed to send the result of job #{}: {}", id, e);
+                    }
+                }
+                Err(e) => {
+                    error!("Job #{} failed: {}: {}", id, description, e);
+                    let text = (locale.messages().job_failed)(id, &description, &e.to_string());
+                    telegram_bot.edit_status(chat_id, status, text).await;
+                }
+            }
+        });
+        self.jobs.attach(id, task.abort_handle());
+        Ok(())
+    }
 
+    async fn edit_status(&self, chat_id: ChatId, message_id: MessageId, text: String) {
+        if let Err(e) = self.bot.edit_message_text(chat_id, message_id, text).await {
+            error!("Failed to edit the job status message: {}", e);
+        }
+    }
+
+    async fn handle_transactions_command(
+        &self,
+        chat_id: ChatId,
+        user_id: Option<u64>,
+        locale: Locale,
+        start_block: BlockNumber,
+        end_block: BlockNumber,
+    ) -> Result<()> {
         if start_block > end_block {
-            self.send_message_to_chat(
-                chat_id,
-                "❌ Start block must be less than or equal to end block",
-            )
-            .await?;
-            return Ok(());
+            return self
+                .send_message_to_chat(chat_id, locale.messages().invalid_block_range)
+                .await;
         }
 
         if end_block - start_block > 2000 {
-            self.send_message_to_chat(
-                chat_id,
-                "❌ The interval between the start and end points does not exceed 2,000 blocks.",
-            )
-            .await?;
-            return Ok(());
+            return self
+                .send_message_to_chat(chat_id, locale.messages().block_range_too_large)
+                .await;
         }
 
-        let respon_message = do_handle_blocks(
-            self.rpc_client.clone(),
-            self.config.clone(),
-            start_block,
-            end_block,
+        let chain_reader = self.chain_reader.clone();
+        let config = self.config.clone();
+        self.spawn_job(
+            chat_id,
+            user_id,
+            locale,
+            format!("/transactions {} {}", start_block, end_block),
+            move |progress| {
+                do_handle_blocks(
+                    chain_reader,
+                    config,
+                    locale,
+                    start_block,
+                    end_block,
+                    progress,
+                )
+            },
         )
-        .await?;
-        if let Some(msg) = respon_message {
-            self.send_message_to_chat(chat_id, &msg).await?;
+        .await
+    }
+
+    async fn handle_jobs_command(&self, chat_id: ChatId, locale: Locale) -> Result<()> {
+        let m = locale.messages();
+        let jobs = self.jobs.jobs(chat_id.0);
+        if jobs.is_empty() {
+            return self.send_message_to_chat(chat_id, m.no_jobs).await;
         }
-        Ok(())
+        let lines: Vec<String> = jobs
+            .iter()
+            .map(|job| {
+                let user_id = job.user_id.unwrap_or_default();
+                let started_at = job.started_at.format("%H:%M:%S UTC").to_string();
+                (m.job)(job.id, &job.description, user_id, &started_at)
+            })
+            .collect();
+        let message = format!("{}\n{}", m.jobs, lines.join("\n"));
+        self.send_message_to_chat(chat_id, &message).await
     }
 
-    fn get_help_message(&self) -> String {
-        r#"
-🤖 **Starcoin Monitor Bot Commands**
+    async fn handle_cancel_command(
+        &self,
+        chat_id: ChatId,
+        user_id: Option<u64>,
+        role: Role,
+        locale: Locale,
+        job_id: Option<JobId>,
+    ) -> Result<()> {
+        let m = locale.messages();
+        let cancelled = self.jobs.cancel(job_id, user_id, role >= Role::Admin);
+        if cancelled.is_empty() {
+            return self.send_message_to_chat(chat_id, m.no_job_to_cancel).await;
+        }
+        for job in &cancelled {
+            info!("Job #{} cancelled by user {:?}", job.id, user_id);
+            if let Some(message_id) = job.status_message_id {
+                let text = (m.job_cancelled)(job.id, &job.description);
+                self.edit_status(ChatId(job.chat_id), MessageId(message_id), text)
+                    .await;
+            }
+        }
+        let ids: Vec<String> = cancelled.iter().map(|job| format!("#{}", job.id)).collect();
+        let message = (m.jobs_cancelled)(cancelled.len(), &ids.join(", "));
+        self.send_message_to_chat(chat_id, &message).await
+    }
 
-📊 **查询命令:**
-• `/transactions <start_block> <end_block>` - 查询两个区块之间的大交易
-        "#
-        .trim()
-        .to_string()
+    async fn handle_balance_command(
+        &self,
+        chat_id: ChatId,
+        locale: Locale,
+        address: AccountAddress,
+    ) -> Result<()> {
+        let chain_reader = self.chain_reader.clone();
+        let balance =
+            tokio::task::spawn_blocking(move || chain_reader.get_balance(address)).await??;
+        let address = helper::format_address(address);
+        let m = locale.messages();
+        let balance = match balance {
+            Some(balance) => balance,
+            None => {
+                return self
+                    .send_message_to_chat(chat_id, &(m.no_balance_resource)(&address))
+                    .await
+            }
+        };
+
+        // The history is only kept for the watched addresses, the balance
+        // being replied without it if it cannot be read
+        let history: Result<BalanceHistory> =
+            storage::load_json(Path::new(&self.config.data_dir).join(BalanceHistory::FILE_NAME));
+        let delta = match history
+            .map(|history| history.delta_since(&address, Utc::now() - Duration::hours(24), balance))
+        {
+            Err(e) => {
+                error!("Failed to load balance history: {}", e);
+                m.history_unreadable.to_string()
+            }
+            Ok(Some((delta, since))) => format!(
+                "{:+.9} STC ({}{})",
+                delta as f64 / 1e9,
+                m.since,
+                since.format("%Y-%m-%d %H:%M:%S UTC")
+            ),
+            Ok(None) => m.no_history.to_string(),
+        };
+
+        let message = templates::balance_reply(locale, &address, balance, &delta);
+        self.send_rich_text_to_chat(chat_id, &message).await
+    }
+
+    async fn handle_label_command(
+        &self,
+        chat_id: ChatId,
+        locale: Locale,
+        address: AccountAddress,
+        label: Option<AddressLabel>,
+    ) -> Result<()> {
+        let address = helper::format_address(address);
+        let label = match label {
+            Some(label) => label,
+            None => {
+                let message = self
+                    .stores
+                    .labels
+                    .read(|labels| match labels.get(&address) {
+                        Some(_) => format!("🏷 {}", labels.describe(&address, locale)),
+                        None => (locale.messages().no_label)(&address),
+                    });
+                return self.send_message_to_chat(chat_id, &message).await;
+            }
+        };
+
+        let described = self.stores.labels.update(|labels| {
+            labels.set(&address, label)?;
+            Ok::<_, anyhow::Error>(labels.describe(&address, locale))
+        })??;
+        info!("Address label updated: {}", described);
+
+        let message = format!("{}{}", locale.messages().label_saved, described);
+        self.send_message_to_chat(chat_id, &message).await
+    }
+
+    /// Subscribe the chat, or its topic thread, or list its subscriptions
+    async fn handle_subscribe_command(
+        &self,
+        destination: AlertDestination,
+        locale: Locale,
+        subscription: Option<Subscription>,
+    ) -> Result<()> {
+        let m = locale.messages();
+        let message = match subscription {
+            Some(subscription) => {
+                self.stores.subscriptions.update(|subscriptions| {
+                    subscriptions.subscribe(destination, subscription.clone())
+                })?;
+                info!("Subscription added for {:?}: {}", destination, subscription);
+                format!("{}{}", m.subscribed, subscription)
+            }
+            None => {
+                let chat_subscriptions: Vec<String> =
+                    self.stores.subscriptions.read(|subscriptions| {
+                        subscriptions
+                            .of_chat(destination)
+                            .iter()
+                            .map(|subscription| format!("- {}", subscription))
+                            .collect()
+                    });
+                if chat_subscriptions.is_empty() {
+                    m.no_subscriptions.to_string()
+                } else {
+                    format!("{}\n{}", m.subscriptions, chat_subscriptions.join("\n"))
+                }
+            }
+        };
+        self.send_message_to_thread(
+            ChatId(destination.chat_id),
+            destination.thread_id,
+            &RichText::from(message),
+            None,
+        )
+        .await
+    }
+
+    /// Remove a subscription of the chat, or all of them if `None`
+    async fn handle_unsubscribe_command(
+        &self,
+        destination: AlertDestination,
+        locale: Locale,
+        subscription: Option<Subscription>,
+    ) -> Result<()> {
+        let removed = self.stores.subscriptions.update(|subscriptions| {
+            subscriptions.unsubscribe(destination, subscription.as_ref())
+        })?;
+        let message = if removed == 0 {
+            locale.messages().no_matching_subscription.to_string()
+        } else {
+            info!("{} subscriptions removed for {:?}", removed, destination);
+            (locale.messages().subscriptions_removed)(removed)
+        };
+        self.send_message_to_thread(
+            ChatId(destination.chat_id),
+            destination.thread_id,
+            &RichText::from(message),
+            None,
+        )
+        .await
+    }
+
+    async fn handle_language_command(&self, chat_id: ChatId, locale: Locale) -> Result<()> {
+        self.stores
+            .locales
+            .update(|locales| locales.set(chat_id.0, locale))?;
+        info!("Locale of chat {} set to {}", chat_id, locale);
+        let message = (locale.messages().language_set)(locale);
+        self.send_message_to_chat(chat_id, &message).await
+    }
+
+    async fn handle_trace_command(
+        &self,
+        chat_id: ChatId,
+        user_id: Option<u64>,
+        locale: Locale,
+        txn_hash: HashValue,
+        depth: usize,
+    ) -> Result<()> {
+        let txn_hash = txn_hash.to_hex_literal();
+        let index = EsTransferIndex::new(
+            &self.config.es_url,
+            &self.config.es_user_name,
+            &self.config.es_password,
+        );
+        let stores = self.stores.clone();
+        self.spawn_job(
+            chat_id,
+            user_id,
+            locale,
+            format!("/trace {} {}", txn_hash, depth),
+            move |progress| async move {
+                let m = locale.messages();
+                progress.set(m.tracing);
+                let config = TraceConfig {
+                    depth,
+                    ..TraceConfig::default()
+                };
+                let message = match trace_funds(&index, &txn_hash, &config).await? {
+                    Some(tree) => format!(
+                        "{}\n{}",
+                        (m.fund_trace)(depth),
+                        stores
+                            .labels
+                            .read(|labels| render_trace(&tree, labels, locale))
+                    ),
+                    None => (m.txn_not_indexed)(&txn_hash),
+                };
+                Ok::<_, anyhow::Error>(RichText::from(message))
+            },
+        )
+        .await
     }
 
     pub async fn send_message(&self, message: &str) -> Result<()> {
@@ -266,79 +1265,93 @@ impl TelegramBot {
             .await
     }
 
-    fn escape_markdown_v2(text: &str) -> String {
-        // Characters that need to be escaped in MarkdownV2
-        let special_chars = [
-            '_', '*', '[', ']', '(', ')', '~', '`', '>', '#', '+', '-', '=', '|', '{', '}', '.',
-            '!',
-        ];
-        let mut escaped = String::new();
+    async fn send_message_to_chat(&self, chat_id: ChatId, message: &str) -> Result<()> {
+        self.send_rich_text_to_chat(chat_id, &RichText::from(message))
+            .await
+    }
 
-        for ch in text.chars() {
-            if special_chars.contains(&ch) {
-                escaped.push('\\');
-            }
-            escaped.push(ch);
-        }
+    async fn send_rich_text_to_chat(&self, chat_id: ChatId, message: &RichText) -> Result<()> {
+        self.send_message_to_thread(chat_id, None, message, None)
+            .await
+    }
 
-        escaped
+    /// Queue a message to a chat, or to a topic thread of a forum chat, with
+    /// optional buttons
+    async fn send_message_to_thread(
+        &self,
+        chat_id: ChatId,
+        thread_id: Option<i32>,
+        message: &RichText,
+        buttons: Option<InlineKeyboardMarkup>,
+    ) -> Result<()> {
+        self.send_queue.push(
+            Outgoing::new(chat_id, message.clone())
+                .in_thread(thread_id)
+                .with_buttons(buttons),
+        )
     }
+}
 
-    async fn send_message_to_chat(&self, chat_id: ChatId, message: &str) -> Result<()> {
-        // Escape the message for MarkdownV2
-        let escaped_message = Self::escape_markdown_v2(message);
-
-        // Retry mechanism for network errors
-        let mut retries = 0;
-        let max_retries = 3;
-
-        loop {
-            match self
-                .bot
-                .send_message(chat_id, &escaped_message)
-                .parse_mode(teloxide::types::ParseMode::MarkdownV2)
+#[async_trait::async_trait]
+impl AlertSender for TelegramBot {
+    async fn send_message(&self, message: &str) -> Result<()> {
+        TelegramBot::send_message(self, message).await
+    }
+
+    /// Queue the alert for each of its routed destinations and the chats
+    /// subscribed to it, each in its own locale, the send queue logging the
+    /// failures. Muted alerts are dropped here, whichever dispatcher raised
+    /// them.
+    async fn send_alert(&self, alert: &Alert) -> Result<()> {
+        if self.is_muted(alert) {
+            info!("Alert {} muted: {}", alert.key(), alert.message);
+            return Ok(());
+        }
+        // Warnings and above can be acked, snoozed or muted from the chat
+        let buttons = |locale: Locale| {
+            (alert.severity >= Severity::Warning).then(|| {
+                InlineKeyboardMarkup::new(vec![MuteAction::for_alert(alert)
+                    .into_iter()
+                    .map(|action| {
+                        InlineKeyboardButton::callback(action.label(locale), action.to_string())
+                    })
+                    .collect::<Vec<_>>()])
+            })
+        };
+        let mut destinations = if alert.subscribers_only {
+            Vec::new()
+        } else {
+            self.alert_router.destinations(alert)
+        };
+        for destination in self
+            .stores
+            .subscriptions
+            .read(|subscriptions| subscriptions.destinations(alert))
+        {
+            if !destinations.contains(&destination) {
+                destinations.push(destination);
+            }
+        }
+        let mut result = Ok(());
+        for destination in destinations {
+            let locale = self.chat_locale(ChatId(destination.chat_id));
+            if let Err(e) = self
+                .send_message_to_thread(
+                    ChatId(destination.chat_id),
+                    destination.thread_id,
+                    &self.alert_router.render(alert, locale),
+                    buttons(locale),
+                )
                 .await
             {
-                Ok(_) => {
-                    info!("Message sent to chat {}", chat_id);
-                    return Ok(());
-                }
-                Err(e) => {
-                    error!(
-                        "Failed to send message to chat {} (attempt {}/{}): {}",
-                        chat_id,
-                        retries + 1,
-                        max_retries,
-                        e
-                    );
-
-                    if retries >= max_retries {
-                        // Try sending without markdown as last resort
-                        match self.bot.send_message(chat_id, message).await {
-                            Ok(_) => {
-                                info!("Message sent to chat {} (without markdown)", chat_id);
-                                return Ok(());
-                            }
-                            Err(e2) => {
-                                error!(
-                                    "Failed to send message to chat {} (without markdown): {}",
-                                    chat_id, e2
-                                );
-                                return Err(anyhow::anyhow!(
-                                    "Failed to send message after {} retries: {}",
-                                    max_retries,
-                                    e
-                                ));
-                            }
-                        }
-                    }
-
-                    // Wait before retrying
-                    tokio::time::sleep(tokio::time::Duration::from_secs(2 * (retries + 1))).await;
-                    retries += 1;
-                }
+                error!(
+                    "Failed to queue {:?} alert for {:?}: {}",
+                    alert.category, destination, e
+                );
+                result = Err(e);
             }
         }
+        result
     }
 }
 
@@ -348,6 +1361,181 @@ mod tests {
     use crate::helper;
     use starcoin_crypto::HashValue;
     use starcoin_rpc_api::chain::GetTransactionOption;
+    use starcoin_rpc_client::RpcClient;
+
+    #[test]
+    fn test_parse_commands() -> Result<()> {
+        let address = AccountAddress::from_hex_literal("0x4a50777e0e4f67625400148b04afd572")?;
+        assert_eq!(
+            Command::parse("/transactions 1000 1100", "monitor_bot")?,
+            Command::Transactions {
+                start_block: 1000,
+                end_block: 1100
+            }
+        );
+        assert_eq!(
+            Command::parse(
+                "/balance@monitor_bot 0x4a50777e0e4f67625400148b04afd572",
+                "monitor_bot"
+            )?,
+            Command::Balance { address }
+        );
+        assert_eq!(
+            Command::parse(
+                "/label 0x4a50777e0e4f67625400148b04afd572 exchange Binance Hot",
+                "monitor_bot"
+            )?,
+            Command::Label {
+                address,
+                label: Some(AddressLabel {
+                    category: AddressCategory::Exchange,
+                    name: "Binance Hot".to_string()
+                })
+            }
+        );
+        assert_eq!(
+            Command::parse("/help /trace", "monitor_bot")?,
+            Command::Help {
+                command: Some("trace".to_string())
+            }
+        );
+        assert_eq!(
+            Command::parse("/help", "monitor_bot")?,
+            Command::Help { command: None }
+        );
+        match Command::parse(
+            "/trace 0x6ed3afdf412404f98fc16d9350b9a19d3258598be5f6b73215a6ab06247b6a53",
+            "monitor_bot",
+        )? {
+            Command::Trace { depth, .. } => assert_eq!(depth, TraceConfig::default().depth),
+            command => panic!("Unexpected command: {:?}", command),
+        }
+
+        assert!(Command::parse("/transactions 1000", "monitor_bot").is_err());
+        assert!(Command::parse("/balance not_an_address", "monitor_bot").is_err());
+        assert!(Command::parse("/label 0x1 exchange", "monitor_bot").is_err());
+        assert!(Command::parse("/trace 0x01 9", "monitor_bot").is_err());
+        assert!(matches!(
+            Command::parse("/balance@other_bot 0x1", "monitor_bot"),
+            Err(ParseError::WrongBotName(_))
+        ));
+        assert_eq!(
+            Command::parse("/cancel #3", "monitor_bot")?,
+            Command::Cancel { job_id: Some(3) }
+        );
+        assert_eq!(
+            Command::parse("/cancel", "monitor_bot")?,
+            Command::Cancel { job_id: None }
+        );
+        assert!(Command::parse("/cancel all", "monitor_bot").is_err());
+        assert_eq!(
+            Command::parse("/subscribe token 0x1::STC::STC 50000", "monitor_bot")?,
+            Command::Subscribe {
+                subscription: Some(Subscription::parse(&["token", "0x1::STC::STC", "50000"])?)
+            }
+        );
+        assert_eq!(
+            Command::parse("/unsubscribe all", "monitor_bot")?,
+            Command::Unsubscribe { subscription: None }
+        );
+        assert!(Command::parse("/unsubscribe", "monitor_bot").is_err());
+        assert!(Command::parse("/subscribe category mempool", "monitor_bot").is_err());
+        assert_eq!(
+            Command::parse("/snooze index_lag:warning", "monitor_bot")?,
+            Command::Snooze {
+                key: "index_lag:warning".to_string(),
+                duration: Duration::hours(1)
+            }
+        );
+        assert_eq!(
+            Command::parse("/mute 0x4a50777e0e4f67625400148b04afd572 2d", "monitor_bot")?,
+            Command::Mute {
+                address: Some(helper::format_address(address)),
+                duration: Some(Duration::days(2))
+            }
+        );
+        assert_eq!(
+            Command::parse("/unmute 0x4a50777e0e4f67625400148b04afd572", "monitor_bot")?,
+            Command::Unmute {
+                target: Some(helper::format_address(address))
+            }
+        );
+        assert!(Command::parse("/snooze mempool:warning", "monitor_bot").is_err());
+        assert!(Command::parse("/snooze index_lag", "monitor_bot").is_err());
+        assert!(Command::parse(
+            "/mute 0x4a50777e0e4f67625400148b04afd572 forever",
+            "monitor_bot"
+        )
+        .is_err());
+        assert!(Command::parse("/unmute", "monitor_bot").is_err());
+        assert_eq!(
+            Command::parse("/language en-US", "monitor_bot")?,
+            Command::Language {
+                locale: Some(Locale::EnUs)
+            }
+        );
+        assert!(Command::parse("/language fr", "monitor_bot").is_err());
+        assert_eq!(command_name("/trace@monitor_bot 0x01"), "trace");
+
+        // The invalid arguments and the usage are worded in the chat locale
+        let e = Command::parse(
+            "/trace 0x6ed3afdf412404f98fc16d9350b9a19d3258598be5f6b73215a6ab06247b6a53 9",
+            "monitor_bot",
+        )
+        .unwrap_err();
+        assert_eq!(arg_error(Locale::EnUs, &e), "Invalid depth, from 1 to 5");
+        assert_eq!(arg_error(Locale::ZhCn, &e), "无效的深度, 应为 1 到 5");
+        let e = Command::parse("/transactions 1000", "monitor_bot").unwrap_err();
+        assert_eq!(arg_error(Locale::EnUs, &e), "Invalid arguments");
+        assert!(command_usage(Locale::EnUs, "balance").starts_with("Usage: /balance"));
+        assert!(command_usage(Locale::ZhCn, "balance").starts_with("用法: /balance"));
+        assert!(command_usage(Locale::EnUs, "nope").starts_with("❓ Unknown command: /nope"));
+
+        // Showing a label is a lookup, changing it needs an admin
+        assert_eq!(
+            Command::parse("/label 0x4a50777e0e4f67625400148b04afd572", "monitor_bot")?
+                .required_role(),
+            Role::Viewer
+        );
+        assert_eq!(
+            Command::parse("/transactions 1000 1100", "monitor_bot")?.required_role(),
+            Role::Operator
+        );
+        assert_eq!(
+            Command::parse(
+                "/label 0x4a50777e0e4f67625400148b04afd572 team Core",
+                "monitor_bot"
+            )?
+            .required_role(),
+            Role::Admin
+        );
+        // Listing the subscriptions is a lookup, changing them is not
+        assert_eq!(
+            Command::parse("/subscribe", "monitor_bot")?.required_role(),
+            Role::Viewer
+        );
+        assert_eq!(
+            Command::parse("/unsubscribe all", "monitor_bot")?.required_role(),
+            Role::Operator
+        );
+        assert_eq!(
+            Command::parse("/mute", "monitor_bot")?.required_role(),
+            Role::Viewer
+        );
+        assert_eq!(
+            Command::parse("/snooze gas:warning 30m", "monitor_bot")?.required_role(),
+            Role::Operator
+        );
+        assert_eq!(
+            Command::parse("/language", "monitor_bot")?.required_role(),
+            Role::Viewer
+        );
+        assert_eq!(
+            Command::parse("/language zh-CN", "monitor_bot")?.required_role(),
+            Role::Operator
+        );
+        Ok(())
+    }
 
     #[test]
     pub fn test_parse_p2p_txn_amount() -> Result<()> {
@@ -0,0 +1,518 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The alerts and bot replies as rich text, their phrases taken from the
+//! catalog of each locale, with links to StcScan and the amounts in bold.
+
+use crate::{
+    address_label::LabeledAddress, balance_check::BalanceChangeAlert,
+    block_anomaly_check::BlockAnomaly, gas_check::GasAlert, governance_check::GovernanceAction,
+    locale::Locale, node_health_check::NodeHealthAlert, price_provider::FiatPrice,
+    rich_text::RichText, supply_check::SupplyAlert, velocity_check::VelocityAlert,
+};
+use starcoin_types::block::BlockNumber;
+
+const STCSCAN_URL: &str = "https://stcscan.io/main";
+
+pub fn block_url(number: BlockNumber) -> String {
+    format!("{}/blocks/height/{}", STCSCAN_URL, number)
+}
+
+pub fn txn_url(txn_hash: &str) -> String {
+    format!("{}/transactions/detail/{}", STCSCAN_URL, txn_hash)
+}
+
+pub fn address_url(address: &str) -> String {
+    format!("{}/address/{}", STCSCAN_URL, address)
+}
+
+/// An amount in the smallest unit, as whole tokens
+fn amount(amount: u128) -> String {
+    format!("{:.9}", amount as f64 / 1e9)
+}
+
+fn fiat_value(value: Option<FiatPrice>) -> String {
+    value
+        .map(|value| format!(" ({})", value))
+        .unwrap_or_default()
+}
+
+/// A transfer above the thresholds, `sender` and `receiver` being described
+/// with their labels
+pub struct TransferAlert<'a> {
+    pub txn_hash: &'a str,
+    pub sender: &'a LabeledAddress,
+    pub receiver: &'a LabeledAddress,
+    pub amount: u128,
+    pub value: Option<FiatPrice>,
+}
+
+pub fn large_transfer(
+    locale: Locale,
+    block_number: BlockNumber,
+    transfer: &TransferAlert,
+    pending_warned: bool,
+) -> RichText {
+    let m = locale.messages();
+    let text = RichText::new()
+        .text(m.large_transfer_title)
+        .link(block_number.to_string(), block_url(block_number))
+        .text(format!(", {}", m.transaction))
+        .link(transfer.txn_hash, txn_url(transfer.txn_hash))
+        .text(format!(", {}", m.amount))
+        .bold(amount(transfer.amount))
+        .text(format!(
+            "{}, {}{}, {}{}",
+            fiat_value(transfer.value),
+            m.sender,
+            transfer.sender.describe(locale),
+            m.receiver,
+            transfer.receiver.describe(locale)
+        ));
+    if pending_warned {
+        text.text(m.pending_warned)
+    } else {
+        text
+    }
+}
+
+/// Large transfers of one or more blocks in a single message, each with its
+/// block and whether it was warned while in the txpool
+pub fn large_transfer_digest(
+    locale: Locale,
+    transfers: &[(BlockNumber, TransferAlert, bool)],
+) -> RichText {
+    let m = locale.messages();
+    let first_block = transfers.iter().map(|(number, ..)| *number).min();
+    let last_block = transfers.iter().map(|(number, ..)| *number).max();
+    let mut text = RichText::new().text(m.large_transfer_digest_title);
+    if let Some((first_block, last_block)) = first_block.zip(last_block) {
+        text = text.link(first_block.to_string(), block_url(first_block));
+        if last_block != first_block {
+            text = text
+                .text(" - ")
+                .link(last_block.to_string(), block_url(last_block));
+        }
+    }
+    let total_amount = transfers
+        .iter()
+        .map(|(_, transfer, _)| transfer.amount)
+        .sum::<u128>();
+    text = text
+        .text((m.large_transfers)(transfers.len()))
+        .bold(amount(total_amount));
+    for (block_number, transfer, pending_warned) in transfers {
+        text = text
+            .text("\n- ")
+            .link(block_number.to_string(), block_url(*block_number))
+            .text(" ")
+            .link(transfer.txn_hash, txn_url(transfer.txn_hash))
+            .text(": ")
+            .bold(amount(transfer.amount))
+            .text(format!(
+                "{}, {} → {}{}",
+                fiat_value(transfer.value),
+                transfer.sender.describe(locale),
+                transfer.receiver.describe(locale),
+                if *pending_warned {
+                    m.pending_warned
+                } else {
+                    ""
+                }
+            ));
+    }
+    text
+}
+
+pub fn pending_large_transfer(locale: Locale, transfer: &TransferAlert) -> RichText {
+    let m = locale.messages();
+    RichText::new()
+        .text(m.pending_large_transfer_title)
+        .link(transfer.txn_hash, txn_url(transfer.txn_hash))
+        .text(format!(
+            ", {}{}, {}{}, {}",
+            m.sender,
+            transfer.sender.describe(locale),
+            m.receiver,
+            transfer.receiver.describe(locale),
+            m.amount
+        ))
+        .bold(amount(transfer.amount))
+        .text(format!(
+            "{}{}",
+            fiat_value(transfer.value),
+            m.pending_waiting
+        ))
+}
+
+pub fn block_anomaly(locale: Locale, anomaly: &BlockAnomaly) -> RichText {
+    let m = locale.messages();
+    let text = RichText::new().text(m.block_anomaly_title);
+    match anomaly {
+        BlockAnomaly::SlowBlock {
+            number,
+            interval_secs,
+        } => text
+            .text(m.block)
+            .link(number.to_string(), block_url(*number))
+            .text((m.slow_block)(*interval_secs)),
+        BlockAnomaly::DifficultySwing {
+            number,
+            difficulty,
+            average_difficulty,
+            change_percent,
+        } => text
+            .text(m.block)
+            .link(number.to_string(), block_url(*number))
+            .text((m.difficulty_swing)(
+                *difficulty,
+                *average_difficulty,
+                *change_percent,
+            )),
+        BlockAnomaly::MinerDominance {
+            miner,
+            blocks,
+            window,
+        } => {
+            let percent = *blocks as f64 / *window as f64 * 100.0;
+            text.text((m.miner_dominance)(miner, *blocks, *window, percent))
+        }
+    }
+}
+
+pub fn gas_alert(locale: Locale, alert: &GasAlert) -> RichText {
+    let m = locale.messages();
+    match alert {
+        GasAlert::HighFailureRate {
+            block_number,
+            failed,
+            total,
+        } => RichText::new()
+            .text(m.failed_transactions_title)
+            .link(block_number.to_string(), block_url(*block_number))
+            .text((m.failed_transactions)(
+                *failed,
+                *total,
+                *failed as f64 / *total as f64 * 100.0,
+            )),
+        GasAlert::HighGasUsed {
+            block_number,
+            gas_used,
+            max_gas_used,
+        } => RichText::new()
+            .text(m.gas_title)
+            .link(block_number.to_string(), block_url(*block_number))
+            .text((m.high_gas_used)(*gas_used, *max_gas_used)),
+        GasAlert::HighGasPrice {
+            block_number,
+            percentile,
+            gas_price,
+            median_gas_price,
+            max_gas_price,
+        } => RichText::new()
+            .text(m.gas_title)
+            .link(block_number.to_string(), block_url(*block_number))
+            .text((m.high_gas_price)(
+                *percentile,
+                *gas_price,
+                *max_gas_price,
+                *median_gas_price,
+            )),
+    }
+}
+
+pub fn index_lag(locale: Locale, curr_number: BlockNumber, cached_number: BlockNumber) -> RichText {
+    let m = locale.messages();
+    RichText::new()
+        .text(m.index_lag_title)
+        .link(curr_number.to_string(), block_url(curr_number))
+        .text(m.index_lag_cached_block)
+        .link(cached_number.to_string(), block_url(cached_number))
+        .text(m.index_lag)
+        .bold((curr_number - cached_number).to_string())
+        .text(m.index_lag_hint)
+}
+
+pub fn node_health(locale: Locale, alert: &NodeHealthAlert) -> RichText {
+    let m = locale.messages();
+    let text = RichText::new().text(m.node_health_title);
+    match alert {
+        NodeHealthAlert::HeadStale {
+            url,
+            head_number,
+            stale_secs,
+            error,
+        } => text.text((m.head_stale)(
+            url,
+            *head_number,
+            *stale_secs,
+            error.as_deref(),
+        )),
+        NodeHealthAlert::HeadLagging {
+            url,
+            head_number,
+            best_url,
+            best_number,
+        } => text
+            .text((m.head_lagging)(url, *head_number, best_url, *best_number))
+            .bold((best_number - head_number).to_string()),
+        NodeHealthAlert::HeadHashMismatch { number, heads } => text
+            .text(m.hash_mismatch_block)
+            .link(number.to_string(), block_url(*number))
+            .text(m.hash_mismatch_heads)
+            .code(
+                heads
+                    .iter()
+                    .map(|(url, hash)| format!("{}: {}", url, hash.to_hex_literal()))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+    }
+}
+
+/// `address` being described with its label
+pub fn balance_change(
+    locale: Locale,
+    alert: &BalanceChangeAlert,
+    address: &LabeledAddress,
+) -> RichText {
+    RichText::new()
+        .text((locale.messages().balance_change)(
+            alert,
+            &address.describe(locale),
+        ))
+        .bold(format!("{:+.9}", alert.change() as f64 / 1e9))
+        .text(
+            alert
+                .change_percent()
+                .map(|percent| format!(" ({:+.2}%)", percent))
+                .unwrap_or_default(),
+        )
+}
+
+/// `address` being described with its label, the window in seconds and
+/// blocks
+pub fn velocity(
+    locale: Locale,
+    alert: &VelocityAlert,
+    address: &LabeledAddress,
+    window_secs: u64,
+    window_blocks: Option<u64>,
+    txn_hash: &str,
+) -> RichText {
+    let m = locale.messages();
+    RichText::new()
+        .text((m.velocity)(
+            alert,
+            &address.describe(locale),
+            window_secs,
+            window_blocks,
+        ))
+        .bold(amount(alert.total))
+        .text((m.velocity_limit)(&amount(alert.max_window_amount)))
+        .link(txn_hash, txn_url(txn_hash))
+}
+
+pub fn governance(
+    locale: Locale,
+    action: &GovernanceAction,
+    block_number: Option<BlockNumber>,
+    txn_hash: Option<&str>,
+    data: Option<String>,
+) -> RichText {
+    let m = locale.messages();
+    let mut text = RichText::new()
+        .text((m.governance_title)(action))
+        .text((m.governance_action)(action));
+    if let Some(block_number) = block_number {
+        text = text
+            .text(format!(", {}", m.block))
+            .link(block_number.to_string(), block_url(block_number));
+    }
+    if let Some(txn_hash) = txn_hash {
+        text = text
+            .text(format!(", {}", m.transaction))
+            .link(txn_hash, txn_url(txn_hash));
+    }
+    if let Some(data) = data {
+        text = text.text(format!(", {}", m.event_data)).code(data);
+    }
+    text
+}
+
+/// `amount` being the latest change, all in the smallest unit
+pub fn token_supply(
+    locale: Locale,
+    alert: &SupplyAlert,
+    amount: u128,
+    txn_hash: Option<&str>,
+) -> RichText {
+    let m = locale.messages();
+    let text = RichText::new()
+        .text((m.token_supply)(alert))
+        .bold(alert.total.to_string())
+        .text((m.token_supply_limit)(alert, amount));
+    match txn_hash {
+        Some(txn_hash) => text
+            .text(format!(", {}", m.transaction))
+            .link(txn_hash, txn_url(txn_hash)),
+        None => text,
+    }
+}
+
+/// The large transfers of a day, `total_amount` in the smallest unit
+pub fn daily_summary(
+    locale: Locale,
+    date: &str,
+    total_transfers: usize,
+    total_amount: u128,
+    value: Option<FiatPrice>,
+) -> RichText {
+    let m = locale.messages();
+    let text = RichText::new()
+        .bold(m.daily_summary_title)
+        .text(format!("\n\n📅 {}{}\n", m.date, date));
+    if total_transfers == 0 {
+        return text.text(m.no_large_transfers_today);
+    }
+    let text = text
+        .text(format!(
+            "🔢 {}{}\n💰 {}",
+            m.large_transfers_count, total_transfers, m.total_amount
+        ))
+        .bold(format!("{} STC", amount(total_amount)))
+        .text("\n");
+    match value {
+        Some(value) => text.text(format!("💵 {}{}\n", m.value, value)),
+        None => text,
+    }
+}
+
+/// Reply of `/transactions`, the large transfers between two blocks with
+/// their amounts in the smallest unit
+pub fn block_transfers(
+    locale: Locale,
+    start_block: BlockNumber,
+    end_block: BlockNumber,
+    transfers: &[(String, u128)],
+) -> RichText {
+    let m = locale.messages();
+    let text = RichText::new()
+        .text(m.from_block)
+        .link(start_block.to_string(), block_url(start_block))
+        .text(m.to_block)
+        .link(end_block.to_string(), block_url(end_block));
+    if transfers.is_empty() {
+        return text.text(m.no_large_transfers);
+    }
+    let total_amount = transfers.iter().map(|(_, amount)| amount).sum::<u128>();
+    let mut text = text
+        .text((m.large_transfers)(transfers.len()))
+        .bold(format!("{} STC", amount(total_amount)));
+    for (txn_hash, transfer_amount) in transfers {
+        text = text
+            .text("\n- ")
+            .link(txn_hash, txn_url(txn_hash))
+            .text(format!(": {} STC", amount(*transfer_amount)));
+    }
+    text
+}
+
+/// Reply of `/balance`, `delta` describing the change over the last 24 hours
+pub fn balance_reply(locale: Locale, address: &str, balance: u128, delta: &str) -> RichText {
+    let m = locale.messages();
+    RichText::new()
+        .text(m.balance_address)
+        .link(address, address_url(address))
+        .text(m.balance)
+        .bold(format!("{} STC", amount(balance)))
+        .text(format!("{}{}", m.balance_change_24h, delta))
+}
+
+/// Reply of `/help`, the commands and their descriptions under a heading in
+/// bold
+pub fn help(locale: Locale) -> RichText {
+    let m = locale.messages();
+    m.help_commands.iter().fold(
+        RichText::new().bold(m.help_heading),
+        |text, (command, description)| text.text(format!("\n/{} — {}", command, description)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        address_label::{AddressCategory, AddressLabel},
+        rich_text::TextFormat,
+    };
+
+    #[test]
+    fn test_large_transfer() {
+        let sender = LabeledAddress::from("0xa77e09f66ea8ed586467e36ce89362b9");
+        let receiver = LabeledAddress {
+            address: "0x4a50777e0e4f67625400148b04afd572".to_string(),
+            label: Some(AddressLabel {
+                category: AddressCategory::Exchange,
+                name: "Binance".to_string(),
+            }),
+        };
+        let transfer = TransferAlert {
+            txn_hash: "0x6a1c",
+            sender: &sender,
+            receiver: &receiver,
+            amount: 2_000_000_000_000,
+            value: Some(FiatPrice {
+                usd: 100.0,
+                cny: 700.0,
+            }),
+        };
+        let text = large_transfer(Locale::ZhCn, 1000000, &transfer, true);
+        assert_eq!(
+            text.to_string(),
+            "🚨[大交易事件告警]: 区块: https://stcscan.io/main/blocks/height/1000000, 交易: https://stcscan.io/main/transactions/detail/0x6a1c, 额度: 2000.000000000 (≈ $100.00 / ¥700.00), 发送方: 0xa77e09f66ea8ed586467e36ce89362b9, 接收方: 0x4a50777e0e4f67625400148b04afd572 [交易所: Binance], 已在交易池中预警"
+        );
+        assert_eq!(
+            text.render(TextFormat::MarkdownV2),
+            "🚨\\[大交易事件告警\\]: 区块: [1000000](https://stcscan.io/main/blocks/height/1000000), 交易: [0x6a1c](https://stcscan.io/main/transactions/detail/0x6a1c), 额度: *2000\\.000000000* \\(≈ $100\\.00 / ¥700\\.00\\), 发送方: 0xa77e09f66ea8ed586467e36ce89362b9, 接收方: 0x4a50777e0e4f67625400148b04afd572 \\[交易所: Binance\\], 已在交易池中预警"
+        );
+        assert_eq!(
+            large_transfer(Locale::EnUs, 1000000, &transfer, false).to_string(),
+            "🚨[Large Transfer Alert]: Block: https://stcscan.io/main/blocks/height/1000000, Transaction: https://stcscan.io/main/transactions/detail/0x6a1c, Amount: 2000.000000000 (≈ $100.00 / ¥700.00), Sender: 0xa77e09f66ea8ed586467e36ce89362b9, Receiver: 0x4a50777e0e4f67625400148b04afd572 [Exchange: Binance]"
+        );
+        let (small_sender, small_receiver) =
+            (LabeledAddress::from("0x1"), LabeledAddress::from("0x2"));
+        let small = TransferAlert {
+            txn_hash: "0x7b2d",
+            sender: &small_sender,
+            receiver: &small_receiver,
+            amount: 500_000_000_000,
+            value: None,
+        };
+        assert_eq!(
+            large_transfer_digest(
+                Locale::EnUs,
+                &[(1000001, small, false), (1000000, transfer, true)]
+            )
+            .render(TextFormat::MarkdownV2),
+            "🚨\\[Large Transfer Digest\\]: Blocks: [1000000](https://stcscan.io/main/blocks/height/1000000) \\- [1000001](https://stcscan.io/main/blocks/height/1000001), 2 large transfers, total amount: *2500\\.000000000*\n\\- [1000001](https://stcscan.io/main/blocks/height/1000001) [0x7b2d](https://stcscan.io/main/transactions/detail/0x7b2d): *500\\.000000000*, 0x1 → 0x2\n\\- [1000000](https://stcscan.io/main/blocks/height/1000000) [0x6a1c](https://stcscan.io/main/transactions/detail/0x6a1c): *2000\\.000000000* \\(≈ $100\\.00 / ¥700\\.00\\), 0xa77e09f66ea8ed586467e36ce89362b9 → 0x4a50777e0e4f67625400148b04afd572 \\[Exchange: Binance\\], already warned while in the txpool"
+        );
+        assert_eq!(
+            block_transfers(
+                Locale::EnUs,
+                1000,
+                1100,
+                &[
+                    ("0x6a1c".to_string(), 2_000_000_000_000),
+                    ("0x7b2d".to_string(), 500_000_000_000)
+                ]
+            )
+            .to_string(),
+            "From block https://stcscan.io/main/blocks/height/1000 to https://stcscan.io/main/blocks/height/1100, 2 large transfers, total amount: 2500.000000000 STC\n- https://stcscan.io/main/transactions/detail/0x6a1c: 2000.000000000 STC\n- https://stcscan.io/main/transactions/detail/0x7b2d: 500.000000000 STC"
+        );
+        assert_eq!(
+            daily_summary(Locale::ZhCn, "2025-08-20", 0, 0, None).to_string(),
+            "📊 【每日交易汇总】\n\n📅 日期: 2025-08-20\n今日没有发现大额交易"
+        );
+    }
+}
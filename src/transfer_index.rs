@@ -0,0 +1,217 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use base64::Engine;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Transfer index written by the StcScan indexer
+const TRANSFER_INDEX: &str = "main.0727.transfer";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TransferDocument {
+    pub amount: String,
+    pub amount_value: Option<u128>,
+    pub identifier: String,
+    pub receiver: String,
+    pub sender: String,
+    pub timestamp: i64,
+    pub txn_hash: String,
+    #[serde(rename = "type_tag")]
+    pub type_tag: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EsSearchResponse {
+    pub hits: EsHits,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EsHits {
+    pub total: EsTotal,
+    pub hits: Vec<EsHit>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EsTotal {
+    pub value: i64,
+    pub relation: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EsHit {
+    #[serde(rename = "_source")]
+    pub source: TransferDocument,
+}
+
+impl TransferDocument {
+    /// Amount in the smallest unit of the token
+    pub fn amount(&self) -> u128 {
+        self.amount_value
+            .or_else(|| parse_hex_amount(&self.amount).ok())
+            .unwrap_or(0)
+    }
+}
+
+/// Client of the transfer index in Elasticsearch
+pub struct EsTransferIndex {
+    client: Client,
+    url: String,
+    auth_header: String,
+}
+
+impl EsTransferIndex {
+    pub fn new(es_url: &str, es_user_name: &str, es_password: &str) -> Self {
+        Self {
+            client: Client::new(),
+            url: format!(
+                "{}/{}/_search",
+                es_url.trim_end_matches('/'),
+                TRANSFER_INDEX
+            ),
+            auth_header: format!(
+                "Basic {}",
+                base64::engine::general_purpose::STANDARD
+                    .encode(format!("{}:{}", es_user_name, es_password))
+            ),
+        }
+    }
+
+    pub async fn search(&self, query: &serde_json::Value) -> Result<Vec<EsHit>> {
+        debug!("EsTransferIndex::search | query: {}", query);
+        let response = self
+            .client
+            .post(&self.url)
+            .header("Authorization", &self.auth_header)
+            .header("Content-Type", "application/json")
+            .json(query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_body = response.text().await?;
+            anyhow::bail!(
+                "Elasticsearch request failed with status: {} - Error body: {}",
+                status,
+                error_body
+            );
+        }
+
+        let response_text = response.text().await?;
+        let es_response: EsSearchResponse = serde_json::from_str(&response_text)?;
+        Ok(es_response.hits.hits)
+    }
+
+    /// The transfer made by a transaction, matching its hash exactly
+    pub async fn find_transfer(&self, txn_hash: &str) -> Result<Option<TransferDocument>> {
+        let query = serde_json::json!({
+          "query": {"term": {"txn_hash": txn_hash}},
+          "size": 1
+        });
+        Ok(self
+            .search(&query)
+            .await?
+            .into_iter()
+            .next()
+            .map(|hit| hit.source))
+    }
+
+    /// The largest transfers sent by `sender` between the two timestamps, in
+    /// milliseconds
+    pub async fn find_outgoing(
+        &self,
+        sender: &str,
+        start_time_mill_sec: i64,
+        end_time_mill_sec: i64,
+        limit: usize,
+    ) -> Result<Vec<TransferDocument>> {
+        let query = serde_json::json!({
+          "query": {
+            "bool": {
+              "must": [{"match": {"sender": sender}}],
+              "filter": [
+                {
+                  "range": {
+                    "timestamp": {
+                      "gte": start_time_mill_sec,
+                      "lte": end_time_mill_sec
+                    }
+                  }
+                }
+              ]
+            }
+          },
+          "size": limit,
+          "sort": [{"amount_value": "desc"}]
+        });
+        Ok(self
+            .search(&query)
+            .await?
+            .into_iter()
+            .map(|hit| hit.source)
+            .collect())
+    }
+}
+
+pub fn parse_hex_amount(hex_amount: &str) -> Result<u128> {
+    // Remove "0x" prefix if present
+    let hex_str = if hex_amount.starts_with("0x") {
+        &hex_amount[2..]
+    } else {
+        hex_amount
+    };
+
+    // Convert hex string to bytes using hex crate
+    let bytes = hex::decode(hex_str)?;
+
+    // The amount is stored as a Move u128 in BCS format
+    // BCS stores u128 as 16 bytes in little-endian order
+    // We need to reverse the bytes to get the correct value
+
+    if bytes.len() > 16 {
+        anyhow::bail!("Amount exceeds u128 size: {} bytes", bytes.len());
+    }
+
+    // Pad to 16 bytes if necessary (add zeros at the end for little-endian)
+    let mut padded_bytes = vec![0u8; 16];
+    for (i, &byte) in bytes.iter().enumerate() {
+        if i < 16 {
+            padded_bytes[i] = byte;
+        }
+    }
+
+    // Convert from little-endian bytes to u128
+    // In little-endian, the least significant byte is at index 0
+    let mut amount = 0u128;
+    for (i, &byte) in padded_bytes.iter().enumerate() {
+        amount = amount.wrapping_add((byte as u128).wrapping_shl((i * 8) as u32));
+    }
+
+    Ok(amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hex_amount() {
+        // Test with actual ES data format
+        // These are real amounts from ES, stored as Move u128 in BCS format
+
+        // Test a small amount
+        let amount = parse_hex_amount("0x64").unwrap();
+        assert_eq!(amount, 100);
+
+        // Test with padding zeros, the least significant byte first
+        let amount = parse_hex_amount("0x0a000000000000000000000000000000").unwrap();
+        assert_eq!(amount, 10);
+
+        // Test another example from actual ES data, about 1814.9 STC
+        let amount = parse_hex_amount("0x583e2993a60100000000000000000000").unwrap();
+        assert_eq!(amount, 1_814_945_152_600);
+    }
+}
@@ -36,7 +36,7 @@ pub struct Block {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AccountBalance {
     pub address: String,
-    pub balance: u64,
+    pub balance: u128,
     pub token: String,
     pub last_updated: DateTime<Utc>,
 }
@@ -0,0 +1,242 @@
+// Copyright (c) The Starcoin Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    alert_throttle::AlertThrottle, balance_monitor::STC_TOKEN_CODE,
+    supply_check::normalize_token_code,
+};
+use starcoin_types::block::BlockNumber;
+use std::collections::{HashMap, VecDeque};
+use tracing::info;
+
+/// Configuration for the cumulative transfer amount detection
+#[derive(Debug, Clone)]
+pub struct VelocityCheckConfig {
+    /// Amount an address may send, or receive, within the window, in nano STC
+    pub max_window_amount: u128,
+    pub window_secs: u64,
+    /// Also limit the window to this number of blocks
+    pub window_blocks: Option<u64>,
+    /// Minimal seconds between two alerts for the same address and direction
+    pub max_notify_time_interval: u64,
+}
+
+impl Default for VelocityCheckConfig {
+    fn default() -> Self {
+        Self {
+            max_window_amount: 10_000_000_000_000, // 10000 STC in nano units
+            window_secs: 3600,
+            window_blocks: None,
+            max_notify_time_interval: 600,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransferDirection {
+    Sent,
+    Received,
+}
+
+/// One side of a transfer, seen from `address`
+#[derive(Debug, Clone)]
+pub struct AddressTransfer {
+    pub address: String,
+    pub direction: TransferDirection,
+    pub token: String,
+    pub amount: u128,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VelocityAlert {
+    pub address: String,
+    pub direction: TransferDirection,
+    pub token: String,
+    /// Amount transferred within the window, including the latest transfer
+    pub total: u128,
+    pub transfers: usize,
+    pub max_window_amount: u128,
+}
+
+/// (time, block number, amount) of a transfer
+type WindowEntry = (u64, BlockNumber, u128);
+
+/// Sums the STC amounts each address sends and receives over a sliding window,
+/// to catch a large amount split into transfers each below the alert threshold.
+/// The other tokens have their own decimals and are not compared to the limit.
+#[derive(Debug, Default)]
+pub struct VelocityTracker {
+    transfers: HashMap<(String, TransferDirection, String), VecDeque<WindowEntry>>,
+    throttle: AlertThrottle,
+}
+
+fn expired(
+    entry: &WindowEntry,
+    now: u64,
+    block_number: BlockNumber,
+    config: &VelocityCheckConfig,
+) -> bool {
+    let (time, number, _) = entry;
+    now.saturating_sub(*time) > config.window_secs
+        || config
+            .window_blocks
+            .is_some_and(|blocks| block_number.saturating_sub(*number) >= blocks)
+}
+
+impl VelocityTracker {
+    /// Record a transfer, returning an alert if the total of the address within
+    /// the window exceeds the limit. The transfers of other tokens than STC are
+    /// ignored.
+    pub fn observe(
+        &mut self,
+        transfer: AddressTransfer,
+        block_number: BlockNumber,
+        now: u64,
+        config: &VelocityCheckConfig,
+    ) -> Option<VelocityAlert> {
+        if normalize_token_code(&transfer.token).ok().as_deref() != Some(STC_TOKEN_CODE) {
+            return None;
+        }
+        let entries = self
+            .transfers
+            .entry((
+                transfer.address.clone(),
+                transfer.direction,
+                transfer.token.clone(),
+            ))
+            .or_default();
+        entries.retain(|entry| !expired(entry, now, block_number, config));
+        entries.push_back((now, block_number, transfer.amount));
+
+        let total = entries.iter().map(|(_, _, amount)| *amount).sum::<u128>();
+        if total <= config.max_window_amount {
+            return None;
+        }
+        let key = format!(
+            "velocity:{:?}:{}:{}",
+            transfer.direction, transfer.address, transfer.token
+        );
+        if !self
+            .throttle
+            .should_notify(&key, now, config.max_notify_time_interval)
+        {
+            return None;
+        }
+        let alert = VelocityAlert {
+            address: transfer.address,
+            direction: transfer.direction,
+            token: transfer.token,
+            total,
+            transfers: entries.len(),
+            max_window_amount: config.max_window_amount,
+        };
+        info!("Transfer velocity exception detected: {:?}", alert);
+        Some(alert)
+    }
+
+    /// Forget the transfers which left the window, for the addresses not seen recently
+    pub fn prune(&mut self, block_number: BlockNumber, now: u64, config: &VelocityCheckConfig) {
+        self.transfers.retain(|_, entries| {
+            entries.retain(|entry| !expired(entry, now, block_number, config));
+            !entries.is_empty()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STC: &str = "0x00000000000000000000000000000001::STC::STC";
+
+    fn sent(address: &str, amount: u128) -> AddressTransfer {
+        AddressTransfer {
+            address: address.to_string(),
+            direction: TransferDirection::Sent,
+            token: STC.to_string(),
+            amount,
+        }
+    }
+
+    #[test]
+    fn test_split_transfers() {
+        let config = VelocityCheckConfig {
+            max_window_amount: 1000,
+            ..VelocityCheckConfig::default()
+        };
+        let mut tracker = VelocityTracker::default();
+
+        assert!(tracker.observe(sent("0xa", 400), 1, 0, &config).is_none());
+        assert!(tracker.observe(sent("0xa", 400), 2, 10, &config).is_none());
+        // Other addresses and directions are summed separately
+        assert!(tracker.observe(sent("0xb", 400), 2, 10, &config).is_none());
+        let received = AddressTransfer {
+            direction: TransferDirection::Received,
+            ..sent("0xa", 400)
+        };
+        assert!(tracker.observe(received, 2, 10, &config).is_none());
+
+        assert_eq!(
+            tracker.observe(sent("0xa", 400), 3, 20, &config),
+            Some(VelocityAlert {
+                address: "0xa".to_string(),
+                direction: TransferDirection::Sent,
+                token: STC.to_string(),
+                total: 1200,
+                transfers: 3,
+                max_window_amount: 1000,
+            })
+        );
+        // Throttled
+        assert!(tracker.observe(sent("0xa", 400), 4, 30, &config).is_none());
+
+        // Only the transfer at 30 is left in the window
+        assert!(tracker
+            .observe(sent("0xa", 400), 5, 3625, &config)
+            .is_none());
+    }
+
+    #[test]
+    fn test_block_window() {
+        let config = VelocityCheckConfig {
+            max_window_amount: 1000,
+            window_blocks: Some(10),
+            ..VelocityCheckConfig::default()
+        };
+        let mut tracker = VelocityTracker::default();
+
+        assert!(tracker.observe(sent("0xa", 600), 1, 0, &config).is_none());
+        // 10 blocks later, within the time window
+        assert!(tracker
+            .observe(sent("0xa", 600), 11, 100, &config)
+            .is_none());
+        assert!(tracker
+            .observe(sent("0xa", 600), 12, 110, &config)
+            .is_some());
+
+        tracker.prune(22, 200, &config);
+        assert!(tracker.transfers.is_empty());
+    }
+
+    #[test]
+    fn test_stc_only() {
+        let config = VelocityCheckConfig {
+            max_window_amount: 1000,
+            ..VelocityCheckConfig::default()
+        };
+        let mut tracker = VelocityTracker::default();
+
+        let usdt = AddressTransfer {
+            token: "0x1::USDT::USDT".to_string(),
+            ..sent("0xa", 2000)
+        };
+        assert!(tracker.observe(usdt, 1, 0, &config).is_none());
+        assert!(tracker.transfers.is_empty());
+
+        let stc = AddressTransfer {
+            token: "0x1::STC::STC".to_string(),
+            ..sent("0xa", 2000)
+        };
+        assert!(tracker.observe(stc, 1, 0, &config).is_some());
+    }
+}